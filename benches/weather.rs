@@ -0,0 +1,118 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ratatui::{backend::TestBackend, Terminal};
+use std::{cell::OnceCell, time::Duration};
+
+use weather_searcher::api::{FetchTimings, GeoLocation, WeatherData, WeatherResponse};
+use weather_searcher::app::{App, AppState, KioskTile};
+use weather_searcher::config::Config;
+use weather_searcher::forecast;
+use weather_searcher::history::HistoryEntry;
+use weather_searcher::series;
+use weather_searcher::units::Units;
+
+// Open-Meteo caps hourly forecasts at 16 days
+const HOURS: usize = 16 * 24;
+
+// A forecast response shaped like the real thing, with `shift` degrees added to every hour
+fn forecast_json(shift: f64) -> String {
+    let mut time = Vec::with_capacity(HOURS);
+    let mut temperature = Vec::with_capacity(HOURS);
+    let mut precipitation = Vec::with_capacity(HOURS);
+    for hour in 0..HOURS {
+        time.push(format!("\"2024-05-{:02}T{:02}:00\"", 1 + hour / 24, hour % 24));
+        let wave = ((hour % 24) as f64 / 24.0 * std::f64::consts::TAU).sin();
+        temperature.push(format!("{:.1}", 12.0 + 6.0 * wave + shift));
+        precipitation.push(format!("{:.1}", if hour % 7 == 0 { 1.2 } else { 0.0 }));
+    }
+    format!(
+        r#"{{"current":{{"time":"2024-05-03T14:15","temperature_2m":17.4,"relative_humidity_2m":61,
+"apparent_temperature":16.9,"precipitation":0.0,"weather_code":3,"wind_speed_10m":14.2,"pressure_msl":1013.8}},
+"current_units":{{"temperature_2m":"°C","wind_speed_10m":"km/h","pressure_msl":"hPa"}},
+"hourly":{{"time":[{}],"temperature_2m":[{}],"precipitation":[{}]}},
+"hourly_units":{{"temperature_2m":"°C","precipitation":"mm"}}}}"#,
+        time.join(","),
+        temperature.join(","),
+        precipitation.join(",")
+    )
+}
+
+fn parse(json: &str) -> WeatherResponse {
+    serde_json::from_str(json).expect("benchmark forecast should parse")
+}
+
+fn parsing(c: &mut Criterion) {
+    let json = forecast_json(0.0);
+    c.bench_function("parse forecast json", |b| b.iter(|| parse(black_box(&json))));
+}
+
+fn series_processing(c: &mut Criterion) {
+    let old = parse(&forecast_json(0.0)).hourly.unwrap();
+    let new = parse(&forecast_json(2.5)).hourly.unwrap();
+
+    c.bench_function("resample hourly to 3h means", |b| {
+        b.iter(|| black_box(&new.temperature).resample(3, series::mean))
+    });
+    c.bench_function("slice next 12 hours", |b| {
+        b.iter(|| {
+            let start = new.temperature.slot_of(black_box("2024-05-09T14:15")).unwrap_or(0);
+            new.temperature.slice(start..start + 12)
+        })
+    });
+    c.bench_function("diff two forecasts", |b| {
+        b.iter(|| forecast::diff(black_box(&old), black_box(&new), "2024-05-03T14:15"))
+    });
+}
+
+fn tromso() -> WeatherData {
+    WeatherData {
+        location: GeoLocation {
+            name: "Tromsø".to_string(),
+            latitude: 69.6496,
+            longitude: 18.956,
+            country: "Norway".to_string(),
+            admin1: Some("Troms og Finnmark".to_string()),
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        },
+        weather: parse(&forecast_json(0.0)),
+        timings: FetchTimings { geocode: Some(Duration::from_millis(80)), forecast: Duration::from_millis(120), json_fallback: None },
+        fetched_at: 1_714_745_700,
+        units: Units::default(),
+    }
+}
+
+fn rendering(c: &mut Criterion) {
+    // Enough history to fill the side pane on a big terminal
+    let history = (0..200)
+        .map(|i| HistoryEntry::new(format!("City {}", i), 1_714_000_000 - i * 60, None))
+        .collect();
+    let mut app = App::new(history, true, Config { big_temperature: true, ..Config::default() });
+    app.weather_data = Some(tromso());
+    app.state = AppState::Display;
+
+    let mut terminal = Terminal::new(TestBackend::new(240, 70)).unwrap();
+    c.bench_function("render weather view 240x70", |b| {
+        b.iter(|| terminal.draw(|f| weather_searcher::view::ui(f, &app)).map(|_| ()).unwrap())
+    });
+
+    // A wall display on a small board: three cities, redrawn every tick
+    app.kiosk = true;
+    app.kiosk_tiles = ["Tromsø", "Bergen", "Oslo"]
+        .into_iter()
+        .map(|query| KioskTile {
+            query: query.to_string(),
+            data: Some(tromso()),
+            error: None,
+            pending: false,
+            text: OnceCell::new(),
+        })
+        .collect();
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    c.bench_function("render kiosk 120x40", |b| {
+        b.iter(|| terminal.draw(|f| weather_searcher::view::ui(f, &app)).map(|_| ()).unwrap())
+    });
+}
+
+criterion_group!(benches, parsing, series_processing, rendering);
+criterion_main!(benches);
@@ -0,0 +1,326 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{AppState, Mode};
+use crate::focus::Pane;
+
+// Everything the user can ask the app to do. Keys are translated into these by
+// map_key and App::handle_action decides what they mean for the current pane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    EnterInsert,
+    InsertAtStart,
+    Append,
+    AppendAtEnd,
+    LeaveInsert,
+    MoveCursorLeft,
+    MoveCursorRight,
+    MoveCursorStart,
+    MoveCursorEnd,
+    NextWord,
+    PrevWord,
+    InsertChar(char),
+    Backspace,
+    DeleteChar,
+    ClearInput,
+    ToggleMark,
+    SelectNext,
+    SelectPrev,
+    Complete,
+    AcceptSuggestionNumber(usize),
+    NextPane,
+    PrevPane,
+    FocusPane(Pane),
+    Submit,
+    NewSearch,
+    Dismiss,
+    PickRecent,
+    ToggleBigTemperature,
+    ToggleEffects,
+    ShowTrends,
+    TrendsWindow(u64),
+    // Wider is positive, as is later in time
+    ZoomTrends(isize),
+    PanTrends(isize),
+    ChartCursor(isize),
+    ShowAccuracy,
+    ShowCyclones,
+    ShowMountain,
+    ShowFreeFlight,
+    ShowMarine,
+    ShowBarograph,
+    ShowDewPoint,
+    PickChart,
+    ShowNearby,
+    PickAirport,
+    ShowEvents,
+    OpenMap,
+    OpenForecastPage,
+    ShowStats,
+    BackToWeather,
+    TryCorrection(usize),
+    ShowHelp,
+    GoHome,
+    SetHome,
+    OpenCommandLine,
+    OpenPalette,
+    Suspend,
+    Quit,
+}
+
+pub fn map_key(state: &AppState, mode: &Mode, key: KeyEvent) -> Option<Action> {
+    if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Action::Suspend);
+    }
+    if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Action::OpenPalette);
+    }
+    // Home and the command line work from every view; while typing a search they're just letters
+    let typing = *state == AppState::Input && *mode == Mode::Insert;
+    if !typing && *state != AppState::Loading {
+        match key.code {
+            KeyCode::Char('H') => return Some(Action::GoHome),
+            KeyCode::Char(':') => return Some(Action::OpenCommandLine),
+            _ => {}
+        }
+    }
+
+    match state {
+        AppState::Input => match mode {
+            Mode::Normal => map_normal_key(key),
+            Mode::Insert => map_insert_key(key),
+        },
+        AppState::Error if matches!(key.code, KeyCode::Char('1'..='9')) => match key.code {
+            KeyCode::Char(c) => c.to_digit(10).map(|n| Action::TryCorrection(n as usize)),
+            _ => None,
+        },
+        AppState::Display | AppState::Error => Some(match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::Quit,
+            KeyCode::Char('?') => Action::ShowHelp,
+            KeyCode::Char('r') => Action::PickRecent,
+            KeyCode::Char('B') => Action::ToggleBigTemperature,
+            KeyCode::Char('E') => Action::ToggleEffects,
+            KeyCode::Char('T') => Action::ShowTrends,
+            KeyCode::Char('A') => Action::ShowAccuracy,
+            KeyCode::Char('C') => Action::ShowCyclones,
+            KeyCode::Char('M') => Action::ShowMountain,
+            KeyCode::Char('F') => Action::ShowFreeFlight,
+            KeyCode::Char('W') => Action::ShowMarine,
+            KeyCode::Char('G') => Action::ShowBarograph,
+            KeyCode::Char('V') => Action::PickChart,
+            KeyCode::Char('N') => Action::ShowNearby,
+            KeyCode::Char('P') => Action::PickAirport,
+            KeyCode::Char('L') => Action::ShowEvents,
+            KeyCode::Char('o') => Action::OpenMap,
+            KeyCode::Char('O') => Action::OpenForecastPage,
+            KeyCode::Char('i') => Action::NewSearch,
+            _ => Action::Dismiss,
+        }),
+        AppState::Trends => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => Some(Action::BackToWeather),
+            KeyCode::Char('1') => Some(Action::TrendsWindow(12)),
+            KeyCode::Char('2') => Some(Action::TrendsWindow(24)),
+            KeyCode::Char('3') => Some(Action::TrendsWindow(48)),
+            KeyCode::Char('4') => Some(Action::TrendsWindow(7 * 24)),
+            KeyCode::Char('+') | KeyCode::Char('=') => Some(Action::ZoomTrends(-1)),
+            KeyCode::Char('-') => Some(Action::ZoomTrends(1)),
+            KeyCode::Char('h') => Some(Action::PanTrends(-1)),
+            KeyCode::Char('l') => Some(Action::PanTrends(1)),
+            KeyCode::Left => Some(Action::ChartCursor(-1)),
+            KeyCode::Right => Some(Action::ChartCursor(1)),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Accuracy => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('A') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Cyclones => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('C') => Some(Action::BackToWeather),
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::SelectNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::SelectPrev),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Barograph => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('G') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::DewPoint => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Mountain => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('M') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::FreeFlight => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('F') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Marine => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('W') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Nearby => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('N') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Brief => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('P') => Some(Action::BackToWeather),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Events => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L') => Some(Action::BackToWeather),
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::SelectNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::SelectPrev),
+            KeyCode::Char('?') => Some(Action::ShowHelp),
+            _ => None,
+        },
+        AppState::Loading => None,
+    }
+}
+
+fn map_normal_key(key: KeyEvent) -> Option<Action> {
+    let action = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) => Action::FocusPane(Pane::from_hotkey(c)?),
+        KeyCode::Char('i') => Action::EnterInsert,
+        KeyCode::Char('I') => Action::InsertAtStart,
+        KeyCode::Char('a') => Action::Append,
+        KeyCode::Char('A') => Action::AppendAtEnd,
+        KeyCode::Char('h') => Action::MoveCursorLeft,
+        KeyCode::Char('l') => Action::MoveCursorRight,
+        KeyCode::Char('j') => Action::SelectNext,
+        KeyCode::Char('k') => Action::SelectPrev,
+        KeyCode::Char('0') | KeyCode::Char('^') => Action::MoveCursorStart,
+        KeyCode::Char('$') => Action::MoveCursorEnd,
+        KeyCode::Char('w') => Action::NextWord,
+        KeyCode::Char('b') => Action::PrevWord,
+        KeyCode::Char('x') => Action::DeleteChar,
+        KeyCode::Char(' ') => Action::ToggleMark,
+        KeyCode::Char('?') => Action::ShowHelp,
+        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::ClearInput,
+        KeyCode::Tab => Action::NextPane,
+        KeyCode::BackTab => Action::PrevPane,
+        KeyCode::Enter => Action::Submit,
+        KeyCode::Esc => Action::Quit,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn map_insert_key(key: KeyEvent) -> Option<Action> {
+    let action = match key.code {
+        KeyCode::Esc => Action::LeaveInsert,
+        KeyCode::Char(c @ '1'..='9') if key.modifiers.contains(KeyModifiers::ALT) => {
+            Action::AcceptSuggestionNumber(c as usize - '0' as usize)
+        }
+        // Digits are taken by suggestions here, so only the letters jump
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) && !c.is_ascii_digit() => {
+            Action::FocusPane(Pane::from_hotkey(c)?)
+        }
+        KeyCode::Char(c) => Action::InsertChar(c),
+        KeyCode::Backspace => Action::Backspace,
+        KeyCode::Delete => Action::DeleteChar,
+        KeyCode::Left => Action::MoveCursorLeft,
+        KeyCode::Right => Action::MoveCursorRight,
+        KeyCode::Home => Action::MoveCursorStart,
+        KeyCode::End => Action::MoveCursorEnd,
+        KeyCode::Down => Action::SelectNext,
+        KeyCode::Up => Action::SelectPrev,
+        KeyCode::Tab => Action::Complete,
+        KeyCode::BackTab => Action::PrevPane,
+        KeyCode::Enter => Action::Submit,
+        _ => return None,
+    };
+    Some(action)
+}
+
+// Keys a hint may name, in the order they're preferred when an action has more than one
+fn hint_keys() -> impl Iterator<Item = KeyEvent> {
+    let plain = (' '..='~').map(KeyCode::Char).chain([
+        KeyCode::Enter,
+        KeyCode::Esc,
+        KeyCode::Tab,
+        KeyCode::BackTab,
+        KeyCode::Up,
+        KeyCode::Down,
+        KeyCode::Left,
+        KeyCode::Right,
+        KeyCode::Home,
+        KeyCode::End,
+        KeyCode::Backspace,
+        KeyCode::Delete,
+    ]);
+    let ctrl = ('a'..='z').map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+    plain.map(KeyEvent::from).chain(ctrl)
+}
+
+fn key_label(key: KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::BackTab => "Shift-Tab".to_string(),
+        code => format!("{:?}", code),
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        format!("Ctrl-{}", name)
+    } else {
+        name
+    }
+}
+
+// The key that does `action` here, e.g. "j" or "Down", found by asking map_key rather than
+// written into the hints by hand, so on-screen hints follow the keymap wherever it goes
+pub fn key_for(state: &AppState, mode: &Mode, action: Action) -> Option<String> {
+    hint_keys().find(|&key| map_key(state, mode, key) == Some(action)).map(key_label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn the_same_key_means_different_things_by_mode_and_pane() {
+        let (input, display) = (AppState::Input, AppState::Display);
+        assert_eq!(map_key(&input, &Mode::Normal, key(KeyCode::Char('j'))), Some(Action::SelectNext));
+        assert_eq!(map_key(&input, &Mode::Insert, key(KeyCode::Char('j'))), Some(Action::InsertChar('j')));
+        assert_eq!(map_key(&input, &Mode::Normal, key(KeyCode::Esc)), Some(Action::Quit));
+        assert_eq!(map_key(&input, &Mode::Insert, key(KeyCode::Esc)), Some(Action::LeaveInsert));
+        assert_eq!(map_key(&display, &Mode::Normal, key(KeyCode::Char('i'))), Some(Action::NewSearch));
+        assert_eq!(map_key(&display, &Mode::Normal, key(KeyCode::Char('z'))), Some(Action::Dismiss));
+        assert_eq!(map_key(&AppState::Loading, &Mode::Insert, key(KeyCode::Enter)), None);
+        assert_eq!(map_key(&input, &Mode::Normal, key(KeyCode::Char('z'))), None);
+    }
+
+    #[test]
+    fn modifiers_pick_the_action() {
+        let alt = KeyEvent::new(KeyCode::Char('3'), KeyModifiers::ALT);
+        let ctrl = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(map_key(&AppState::Input, &Mode::Insert, alt), Some(Action::AcceptSuggestionNumber(3)));
+        assert_eq!(map_key(&AppState::Input, &Mode::Insert, key(KeyCode::Char('3'))), Some(Action::InsertChar('3')));
+        assert_eq!(map_key(&AppState::Input, &Mode::Normal, ctrl), Some(Action::ClearInput));
+    }
+
+    #[test]
+    fn hints_come_from_the_keymap() {
+        assert_eq!(key_for(&AppState::Display, &Mode::Normal, Action::NewSearch).as_deref(), Some("i"));
+        assert_eq!(key_for(&AppState::Display, &Mode::Normal, Action::Quit).as_deref(), Some("q"));
+        assert_eq!(key_for(&AppState::Input, &Mode::Insert, Action::SelectNext).as_deref(), Some("Down"));
+        assert_eq!(key_for(&AppState::Input, &Mode::Normal, Action::Quit).as_deref(), Some("Esc"));
+        assert_eq!(key_for(&AppState::Input, &Mode::Normal, Action::ClearInput).as_deref(), Some("Ctrl-d"));
+        assert_eq!(key_for(&AppState::Input, &Mode::Normal, Action::ToggleMark).as_deref(), Some("Space"));
+        assert_eq!(key_for(&AppState::Loading, &Mode::Normal, Action::Quit), None);
+    }
+}
@@ -0,0 +1,1783 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bugreport;
+use crate::config::NtfyConfig;
+use crate::flatbuf;
+use crate::geo;
+use crate::geocache;
+use crate::httpcache;
+use crate::series::TimeSeries;
+use crate::schema;
+use crate::session;
+use crate::units::Units;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GeocodingResponse {
+    pub results: Option<Vec<GeoLocation>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GeoLocation {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub country: String,
+    pub admin1: Option<String>,
+    // County or district, then the level below; only shown to tell same-named places apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin2: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin3: Option<String>,
+    // Set when the user typed coordinates and `name` is just the closest named place
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub near_km: Option<f64>,
+}
+
+impl GeoLocation {
+    // "near Tromsø (12 km)" for coordinates that aren't in town, otherwise just the name
+    pub fn label(&self) -> String {
+        match self.near_km {
+            Some(km) if km >= 1.0 => format!("near {} ({:.0} km)", self.name, km),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+// A place name plus an optional country filter, from "Paris @FR" or "Paris, country:FR",
+// or bare coordinates like "69.65, 18.96"
+pub struct SearchQuery {
+    pub name: String,
+    pub country_code: Option<String>,
+    pub coordinates: Option<(f64, f64)>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str, default_country: Option<&str>) -> SearchQuery {
+        let input = input.trim();
+        if let Some(coordinates) = parse_coordinates(input) {
+            return SearchQuery { name: input.to_string(), country_code: None, coordinates: Some(coordinates) };
+        }
+        let is_code = |c: &str| c.len() == 2 && c.chars().all(|ch| ch.is_ascii_alphabetic());
+
+        let (name, code) = if let Some((name, code)) = input
+            .rsplit_once(char::is_whitespace)
+            .and_then(|(name, last)| last.strip_prefix('@').map(|code| (name, code)))
+            .filter(|(_, code)| is_code(code))
+        {
+            (name, Some(code))
+        } else if let Some((name, code)) = input
+            .rsplit_once(',')
+            .and_then(|(name, last)| last.trim().strip_prefix("country:").map(|code| (name, code.trim())))
+            .filter(|(_, code)| is_code(code))
+        {
+            (name, Some(code))
+        } else {
+            (input, None)
+        };
+
+        SearchQuery {
+            name: name.trim().to_string(),
+            country_code: code.or(default_country).map(|c| c.to_ascii_uppercase()),
+            coordinates: None,
+        }
+    }
+
+    pub fn country_param(&self) -> String {
+        self.country_code
+            .as_ref()
+            .map(|c| format!("&countryCode={}", urlencoding::encode(c)))
+            .unwrap_or_default()
+    }
+}
+
+// "lat, lon" or "lat lon" in decimal degrees
+fn parse_coordinates(input: &str) -> Option<(f64, f64)> {
+    let mut parts = input.split(|c: char| c == ',' || c.is_whitespace()).filter(|p| !p.is_empty());
+    let latitude: f64 = parts.next()?.parse().ok()?;
+    let longitude: f64 = parts.next()?.parse().ok()?;
+    let valid = parts.next().is_none() && (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude);
+    valid.then_some((latitude, longitude))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(from = "RawWeatherResponse")]
+pub struct WeatherResponse {
+    pub current: CurrentWeather,
+    pub current_units: CurrentUnits,
+    pub hourly: Option<HourlyForecast>,
+}
+
+// Hourly series starting at local midnight today, e.g. "2024-05-01T00:00"
+#[derive(Debug)]
+pub struct HourlyForecast {
+    pub temperature: TimeSeries<f64>,
+    pub precipitation: TimeSeries<f64>,
+    pub wind_speed: TimeSeries<f64>,
+    // Percent
+    pub precipitation_probability: TimeSeries<f64>,
+    // European AQI from the separate air quality API; empty when that didn't answer
+    pub air_quality: TimeSeries<f64>,
+    // Percent
+    pub humidity: TimeSeries<f64>,
+    pub cloud_cover: TimeSeries<f64>,
+    // Seconds of sun in the hour
+    pub sunshine: TimeSeries<f64>,
+    // 1 or 0
+    pub is_day: TimeSeries<f64>,
+    pub dew_point: TimeSeries<f64>,
+    // Ground temperature at the surface, which freezes before the air does on a clear night
+    pub surface_temperature: TimeSeries<f64>,
+}
+
+// The API's column-per-variable layout, before it's split into series
+#[derive(Deserialize)]
+struct RawWeatherResponse {
+    current: CurrentWeather,
+    current_units: CurrentUnits,
+    #[serde(default)]
+    hourly: Option<RawHourly>,
+    #[serde(default)]
+    hourly_units: RawHourlyUnits,
+}
+
+#[derive(Deserialize)]
+struct RawHourly {
+    time: Vec<String>,
+    temperature_2m: Vec<f64>,
+    #[serde(default)]
+    precipitation: Vec<f64>,
+    #[serde(default)]
+    wind_speed_10m: Vec<f64>,
+    #[serde(default)]
+    precipitation_probability: Vec<f64>,
+    #[serde(default)]
+    relative_humidity_2m: Vec<f64>,
+    #[serde(default)]
+    cloud_cover: Vec<f64>,
+    #[serde(default)]
+    sunshine_duration: Vec<f64>,
+    #[serde(default)]
+    is_day: Vec<f64>,
+    #[serde(default)]
+    dew_point_2m: Vec<f64>,
+    #[serde(default)]
+    soil_temperature_0cm: Vec<f64>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawHourlyUnits {
+    #[serde(default)]
+    temperature_2m: String,
+    #[serde(default)]
+    precipitation: String,
+}
+
+impl From<RawWeatherResponse> for WeatherResponse {
+    fn from(raw: RawWeatherResponse) -> WeatherResponse {
+        let units = raw.hourly_units;
+        let hourly = raw.hourly.map(|h| HourlyForecast {
+            precipitation: TimeSeries::new(h.time.clone(), h.precipitation, units.precipitation),
+            wind_speed: TimeSeries::new(h.time.clone(), h.wind_speed_10m, "km/h"),
+            precipitation_probability: TimeSeries::new(h.time.clone(), h.precipitation_probability, "%"),
+            humidity: TimeSeries::new(h.time.clone(), h.relative_humidity_2m, "%"),
+            cloud_cover: TimeSeries::new(h.time.clone(), h.cloud_cover, "%"),
+            sunshine: TimeSeries::new(h.time.clone(), h.sunshine_duration, "s"),
+            is_day: TimeSeries::new(h.time.clone(), h.is_day, ""),
+            dew_point: TimeSeries::new(h.time.clone(), h.dew_point_2m, "°C"),
+            surface_temperature: TimeSeries::new(h.time.clone(), h.soil_temperature_0cm, "°C"),
+            temperature: TimeSeries::new(h.time, h.temperature_2m, units.temperature_2m),
+            air_quality: TimeSeries::default(),
+        });
+        WeatherResponse { current: raw.current, current_units: raw.current_units, hourly }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CurrentWeather {
+    // Local time of the observation, e.g. "2024-05-01T14:15"
+    #[serde(default)]
+    pub time: String,
+    pub temperature_2m: f64,
+    pub relative_humidity_2m: u32,
+    pub apparent_temperature: f64,
+    pub precipitation: f64,
+    pub weather_code: u32,
+    pub wind_speed_10m: f64,
+    pub pressure_msl: f64,
+    // Cached answers from before it was asked for don't have it
+    #[serde(default)]
+    pub uv_index: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CurrentUnits {
+    pub temperature_2m: String,
+    pub wind_speed_10m: String,
+    pub pressure_msl: String,
+}
+
+// One entry per day, starting today, e.g. "2024-05-01"
+#[derive(Deserialize, Debug)]
+#[serde(from = "RawDailyResponse")]
+pub struct DailyForecast {
+    pub weather_code: TimeSeries<u32>,
+    pub temperature_max: TimeSeries<f64>,
+    pub temperature_min: TimeSeries<f64>,
+    pub precipitation: TimeSeries<f64>,
+    pub wind_speed_max: TimeSeries<f64>,
+}
+
+#[derive(Deserialize)]
+struct RawDailyResponse {
+    daily: RawDaily,
+    daily_units: RawDailyUnits,
+}
+
+#[derive(Deserialize)]
+struct RawDaily {
+    time: Vec<String>,
+    weather_code: Vec<u32>,
+    temperature_2m_max: Vec<f64>,
+    temperature_2m_min: Vec<f64>,
+    precipitation_sum: Vec<f64>,
+    wind_speed_10m_max: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct RawDailyUnits {
+    temperature_2m_max: String,
+    temperature_2m_min: String,
+    precipitation_sum: String,
+    wind_speed_10m_max: String,
+}
+
+impl From<RawDailyResponse> for DailyForecast {
+    fn from(raw: RawDailyResponse) -> DailyForecast {
+        let (daily, units) = (raw.daily, raw.daily_units);
+        let time = daily.time;
+        DailyForecast {
+            weather_code: TimeSeries::new(time.clone(), daily.weather_code, "wmo code"),
+            temperature_max: TimeSeries::new(time.clone(), daily.temperature_2m_max, units.temperature_2m_max),
+            temperature_min: TimeSeries::new(time.clone(), daily.temperature_2m_min, units.temperature_2m_min),
+            precipitation: TimeSeries::new(time.clone(), daily.precipitation_sum, units.precipitation_sum),
+            wind_speed_max: TimeSeries::new(time, daily.wind_speed_10m_max, units.wind_speed_10m_max),
+        }
+    }
+}
+
+// One entry of the NHC active storm list (Atlantic and eastern/central Pacific)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Cyclone {
+    pub name: String,
+    // "HU", "TS", "TD", "STS", "PTC", ...
+    pub classification: String,
+    // Maximum sustained wind in knots
+    #[serde(deserialize_with = "number_or_string")]
+    pub intensity: f64,
+    // Minimum central pressure in mb
+    #[serde(deserialize_with = "number_or_string")]
+    pub pressure: f64,
+    pub latitude_numeric: f64,
+    pub longitude_numeric: f64,
+    // Heading in degrees and forward speed in mph
+    #[serde(deserialize_with = "number_or_string")]
+    pub movement_dir: f64,
+    #[serde(deserialize_with = "number_or_string")]
+    pub movement_speed: f64,
+}
+
+impl Cyclone {
+    // Saffir-Simpson category from the wind speed, or the NHC classification below hurricane strength
+    pub fn category(&self) -> String {
+        match self.intensity as u32 {
+            137.. => "Cat 5".to_string(),
+            113..=136 => "Cat 4".to_string(),
+            96..=112 => "Cat 3".to_string(),
+            83..=95 => "Cat 2".to_string(),
+            64..=82 => "Cat 1".to_string(),
+            _ => self.classification.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CurrentStorms {
+    active_storms: Vec<Cyclone>,
+}
+
+// The NHC feed isn't consistent about quoting its numbers
+fn number_or_string<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f64),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(n),
+        Raw::Text(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+pub struct Earthquake {
+    pub magnitude: f64,
+    pub depth_km: f64,
+    // Unix seconds
+    pub time: u64,
+    // e.g. "12 km SW of Somewhere, CA"
+    pub place: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuakeCollection {
+    features: Vec<QuakeFeature>,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuakeFeature {
+    properties: QuakeProperties,
+    geometry: QuakeGeometry,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuakeProperties {
+    mag: Option<f64>,
+    place: Option<String>,
+    // Unix milliseconds
+    time: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuakeGeometry {
+    // longitude, latitude, depth in km
+    coordinates: Vec<f64>,
+}
+
+// Everything shown on the pre-flight brief for one airport
+pub struct FlightBrief {
+    pub icao: String,
+    pub name: String,
+    pub metar: String,
+    // None when the airport doesn't issue TAFs
+    pub taf: Option<String>,
+    // Direction (None when variable), speed and gusts in knots from the METAR
+    pub wind: Option<(Option<f64>, f64, Option<f64>)>,
+    pub surface: Vec<SurfaceWind>,
+}
+
+// One hour of Open-Meteo's 10 m wind forecast, in knots
+pub struct SurfaceWind {
+    pub time: String,
+    pub direction: f64,
+    pub speed: f64,
+    pub gusts: f64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MetarReport {
+    raw_ob: String,
+    #[serde(default)]
+    name: Option<String>,
+    lat: f64,
+    lon: f64,
+    // Degrees, or "VRB"
+    #[serde(default)]
+    wdir: Option<serde_json::Value>,
+    #[serde(default)]
+    wspd: Option<f64>,
+    #[serde(default)]
+    wgst: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TafReport {
+    #[serde(rename = "rawTAF")]
+    raw_taf: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SurfaceResponse {
+    hourly: SurfaceHourly,
+}
+
+#[derive(Deserialize, Debug)]
+struct SurfaceHourly {
+    time: Vec<String>,
+    wind_speed_10m: Vec<f64>,
+    wind_direction_10m: Vec<f64>,
+    wind_gusts_10m: Vec<f64>,
+}
+
+// The mountain view: conditions up the slope above one spot, hourly from local midnight
+pub struct MountainForecast {
+    // Metres above sea level
+    pub freezing_level: TimeSeries<f64>,
+    // The 850 hPa level, around 1500 m, in km/h
+    pub wind_850: TimeSeries<f64>,
+    pub wind_850_direction: TimeSeries<f64>,
+    // Lowest first
+    pub elevations: Vec<ElevationForecast>,
+}
+
+pub struct ElevationForecast {
+    pub elevation: f64,
+    pub temperature: TimeSeries<f64>,
+    // Centimetres per hour
+    pub snowfall: TimeSeries<f64>,
+}
+
+#[derive(Deserialize)]
+struct MountainResponse {
+    elevation: f64,
+    hourly: MountainHourly,
+}
+
+#[derive(Deserialize)]
+struct MountainHourly {
+    time: Vec<String>,
+    temperature_2m: Vec<f64>,
+    snowfall: Vec<f64>,
+    freezing_level_height: Vec<f64>,
+    #[serde(rename = "wind_speed_850hPa")]
+    wind_speed_850: Vec<f64>,
+    #[serde(rename = "wind_direction_850hPa")]
+    wind_direction_850: Vec<f64>,
+}
+
+// The forecast at one point of a route, downscaled to its height, hourly from local midnight
+pub struct RouteForecast {
+    // Metres: the track's own when it had them, Open-Meteo's terrain model otherwise
+    pub elevation: f64,
+    pub temperature: TimeSeries<f64>,
+    pub precipitation: TimeSeries<f64>,
+    pub precipitation_probability: TimeSeries<f64>,
+    pub wind_speed: TimeSeries<f64>,
+}
+
+#[derive(Deserialize)]
+struct RouteResponse {
+    elevation: f64,
+    hourly: RouteHourly,
+}
+
+#[derive(Deserialize)]
+struct RouteHourly {
+    time: Vec<String>,
+    temperature_2m: Vec<f64>,
+    precipitation: Vec<f64>,
+    precipitation_probability: Vec<Option<f64>>,
+    wind_speed_10m: Vec<f64>,
+}
+
+// Several locations in one request come back as a list; a single one as a bare object
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    Many(Vec<T>),
+    One(T),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrMany::Many(many) => many,
+            OneOrMany::One(one) => vec![one],
+        }
+    }
+}
+
+// The free-flight view: the wind profile and how well thermals should work, hourly from local midnight
+pub struct FreeFlightForecast {
+    // Lowest first
+    pub levels: Vec<PressureLevel>,
+    // Metres above ground; thermals rarely climb past the top of the boundary layer
+    pub boundary_layer: TimeSeries<f64>,
+    // °C lost per 1000 m between the ground and 700 hPa; the steeper, the stronger the thermals
+    pub lapse_rate: TimeSeries<f64>,
+}
+
+pub struct PressureLevel {
+    pub hpa: u32,
+    // Rough height in a standard atmosphere, for the column headings
+    pub altitude: u32,
+    // km/h
+    pub speed: TimeSeries<f64>,
+    pub direction: TimeSeries<f64>,
+}
+
+#[derive(Deserialize)]
+struct FreeFlightResponse {
+    elevation: f64,
+    hourly: FreeFlightHourly,
+}
+
+#[derive(Deserialize)]
+struct FreeFlightHourly {
+    time: Vec<String>,
+    temperature_2m: Vec<f64>,
+    #[serde(rename = "temperature_700hPa")]
+    temperature_700: Vec<f64>,
+    #[serde(rename = "geopotential_height_700hPa")]
+    height_700: Vec<f64>,
+    boundary_layer_height: Vec<f64>,
+    #[serde(rename = "wind_speed_925hPa")]
+    wind_speed_925: Vec<f64>,
+    #[serde(rename = "wind_direction_925hPa")]
+    wind_direction_925: Vec<f64>,
+    #[serde(rename = "wind_speed_850hPa")]
+    wind_speed_850: Vec<f64>,
+    #[serde(rename = "wind_direction_850hPa")]
+    wind_direction_850: Vec<f64>,
+    #[serde(rename = "wind_speed_700hPa")]
+    wind_speed_700: Vec<f64>,
+    #[serde(rename = "wind_direction_700hPa")]
+    wind_direction_700: Vec<f64>,
+}
+
+// The next highs and lows for the marine view
+pub struct Tides {
+    // e.g. "San Francisco, 12 km away"; None when the predictions come from a global model
+    pub station: Option<String>,
+    pub provider: Provider,
+    // Oldest first
+    pub extremes: Vec<Tide>,
+}
+
+pub struct Tide {
+    // Local time at the location, e.g. "2024-05-01T14:36"
+    pub time: String,
+    pub high: bool,
+    // Metres above the station datum
+    pub height: f64,
+}
+
+#[derive(Deserialize)]
+struct NoaaStations {
+    stations: Vec<NoaaStation>,
+}
+
+#[derive(Deserialize)]
+struct NoaaStation {
+    id: String,
+    name: String,
+    lat: f64,
+    lng: f64,
+}
+
+#[derive(Deserialize)]
+struct NoaaPredictions {
+    #[serde(default)]
+    predictions: Vec<NoaaPrediction>,
+    error: Option<NoaaError>,
+}
+
+#[derive(Deserialize)]
+struct NoaaPrediction {
+    // "2024-05-01 14:36"
+    t: String,
+    v: String,
+    // "H" or "L"
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct NoaaError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct WorldTidesResponse {
+    #[serde(default)]
+    extremes: Vec<WorldTidesExtreme>,
+    station: Option<String>,
+    error: Option<String>,
+}
+
+// The same forecast for the location and a few km to each side, hourly from local midnight
+pub struct NearbyPoint {
+    // "Here", or the compass point the grid point was taken towards
+    pub label: &'static str,
+    // Model terrain height, which is often most of the difference
+    pub elevation: f64,
+    pub temperature: TimeSeries<f64>,
+    pub precipitation: TimeSeries<f64>,
+    pub wind_speed: TimeSeries<f64>,
+}
+
+// The newest published build, for the opt-in update check
+#[derive(Debug, Clone)]
+pub struct Release {
+    // Without the leading "v" of the tag
+    pub version: String,
+    // The release page, which is where the changelog goes
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct NearbyResponse {
+    elevation: f64,
+    hourly: NearbyHourly,
+}
+
+#[derive(Deserialize)]
+struct NearbyHourly {
+    time: Vec<String>,
+    temperature_2m: Vec<f64>,
+    precipitation: Vec<f64>,
+    wind_speed_10m: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct PressureResponse {
+    hourly: PressureHourly,
+}
+
+#[derive(Deserialize)]
+struct PressureHourly {
+    time: Vec<String>,
+    pressure_msl: Vec<Option<f64>>,
+}
+
+#[derive(Deserialize)]
+struct SeaTemperatureResponse {
+    hourly: SeaTemperatureHourly,
+}
+
+#[derive(Deserialize)]
+struct SeaTemperatureHourly {
+    time: Vec<String>,
+    // Null on land and off the edge of the model
+    sea_surface_temperature: Vec<Option<f64>>,
+}
+
+#[derive(Deserialize)]
+struct WorldTidesExtreme {
+    // "2024-05-01T14:36+0100" with localtime set
+    date: String,
+    height: f64,
+    // "High" or "Low"
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+pub struct WeatherData {
+    pub location: GeoLocation,
+    pub weather: WeatherResponse,
+    pub timings: FetchTimings,
+    // Unix seconds
+    pub fetched_at: u64,
+    // What it's shown in: the favorite's own units, otherwise the config's
+    pub units: Units,
+}
+
+impl WeatherData {
+    // In the versioned shape from `schema`, never these structs directly
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(schema::Current::from(self)).unwrap_or_default()
+    }
+}
+
+// Geocoding is None when the coordinates came from history
+pub struct FetchTimings {
+    pub geocode: Option<Duration>,
+    pub forecast: Duration,
+    // Why a FlatBuffers forecast was fetched again as JSON, for --debug
+    pub json_fallback: Option<String>,
+}
+
+// Where the data on screen comes from; each wants to be credited
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provider {
+    OpenMeteo,
+    Nhc,
+    Usgs,
+    AviationWeather,
+    GeoNames,
+    NoaaTides,
+    WorldTides,
+}
+
+impl Provider {
+    pub fn attribution(self) -> &'static str {
+        match self {
+            Provider::OpenMeteo => "Weather data by Open-Meteo.com (CC BY 4.0)",
+            Provider::Nhc => "Storms: NOAA National Hurricane Center",
+            Provider::Usgs => "Earthquakes: U.S. Geological Survey",
+            Provider::AviationWeather => "METAR/TAF: aviationweather.gov",
+            Provider::GeoNames => "Places: GeoNames (CC BY 4.0)",
+            Provider::NoaaTides => "Tides: NOAA CO-OPS",
+            Provider::WorldTides => "Tides: WorldTides.info",
+        }
+    }
+}
+
+// Some APIs (met.no, for one) turn away anonymous clients, so say who we are
+pub const DEFAULT_USER_AGENT: &str =
+    concat!("weather-searcher/", env!("CARGO_PKG_VERSION"), " (+https://github.com/kantiankant/weather-tui)");
+
+static USER_AGENT: OnceLock<String> = OnceLock::new();
+
+// Only takes effect before the first request, since the client is built once
+pub fn set_user_agent(agent: String) {
+    let _ = USER_AGENT.set(agent);
+}
+
+static FLATBUFFERS: AtomicBool = AtomicBool::new(false);
+
+// Opt-in binary forecasts: smaller to download and cheaper to parse than JSON
+pub fn use_flatbuffers(enabled: bool) {
+    FLATBUFFERS.store(enabled, Ordering::Relaxed);
+}
+
+fn http_client() -> &'static reqwest::Client {
+    // Shared so repeated searches reuse pooled connections to both API hosts
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        let agent = USER_AGENT.get().map_or(DEFAULT_USER_AGENT, String::as_str);
+        // Hourly JSON compresses about tenfold, which matters on a metered connection
+        reqwest::Client::builder()
+            .user_agent(agent)
+            .gzip(true)
+            .deflate(true)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+// Why a search came back empty-handed; only NotFound is worth a "did you mean"
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    // The geocoder answered but knew no such place, as opposed to not answering at all
+    NotFound(String),
+    Failed(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::NotFound(message) | FetchError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for FetchError {
+    fn from(message: String) -> FetchError {
+        FetchError::Failed(message)
+    }
+}
+
+// So the subcommands can keep using `?` with their plain string errors
+impl From<FetchError> for String {
+    fn from(error: FetchError) -> String {
+        error.to_string()
+    }
+}
+
+// Every request goes out through here, so --record sees all of them, --replay can answer them all
+// and a bug report can include the last few
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, reqwest::Error> {
+    use reqwest::header::{ETAG, LAST_MODIFIED};
+
+    let (client, request) = request.build_split();
+    let request = request?;
+    let (method, url) = (request.method().to_string(), request.url().to_string());
+    if let Some(exchange) = session::replayed(&method, &url) {
+        return Ok(rebuilt(exchange));
+    }
+
+    let response = client.execute(request).await?;
+    let headers = [ETAG, LAST_MODIFIED]
+        .iter()
+        .filter_map(|name| Some((name.to_string(), response.headers().get(name)?.to_str().ok()?.to_string())))
+        .collect();
+    let status = response.status().as_u16();
+    let body = response.bytes().await?.to_vec();
+    let exchange = session::Exchange { method, url, status, headers, body: session::Body::new(body) };
+    session::record_exchange(exchange.clone());
+    bugreport::remember(&exchange);
+    Ok(rebuilt(exchange))
+}
+
+fn rebuilt(exchange: session::Exchange) -> reqwest::Response {
+    let mut response = http::Response::builder().status(exchange.status);
+    for (name, value) in &exchange.headers {
+        response = response.header(name, value);
+    }
+    let body = exchange.body.into_bytes();
+    reqwest::Response::from(response.body(body.clone()).unwrap_or_else(|_| http::Response::new(body)))
+}
+
+// Everything the running app asks the outside world for, so tests can answer with canned data
+pub trait Source: Send + Sync + 'static {
+    fn fetch_weather(
+        &self,
+        query: &SearchQuery,
+        cached: Option<GeoLocation>,
+    ) -> impl Future<Output = Result<WeatherData, FetchError>> + Send;
+    fn fetch_autocomplete(&self, query: &SearchQuery) -> impl Future<Output = Result<Vec<GeoLocation>, String>> + Send;
+    fn geocode(&self, query: &SearchQuery) -> impl Future<Output = Result<GeoLocation, String>> + Send;
+    fn fetch_earthquakes(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+    ) -> impl Future<Output = Result<Vec<Earthquake>, String>> + Send;
+    fn fetch_brief(&self, icao: &str) -> impl Future<Output = Result<FlightBrief, String>> + Send;
+    fn fetch_cyclones(&self) -> impl Future<Output = Result<Vec<Cyclone>, String>> + Send;
+    fn fetch_mountain(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        elevations: &[f64],
+    ) -> impl Future<Output = Result<MountainForecast, String>> + Send;
+    fn fetch_free_flight(&self, latitude: f64, longitude: f64) -> impl Future<Output = Result<FreeFlightForecast, String>> + Send;
+    fn fetch_daily(&self, location: &GeoLocation, days: u8) -> impl Future<Output = Result<DailyForecast, String>> + Send;
+    fn fetch_tides(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        worldtides_key: Option<&str>,
+    ) -> impl Future<Output = Result<Tides, String>> + Send;
+    fn fetch_sea_temperature(&self, latitude: f64, longitude: f64) -> impl Future<Output = Result<TimeSeries<f64>, String>> + Send;
+    fn fetch_pressure(&self, latitude: f64, longitude: f64) -> impl Future<Output = Result<TimeSeries<f64>, String>> + Send;
+    fn fetch_nearby(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        offset_km: f64,
+    ) -> impl Future<Output = Result<Vec<NearbyPoint>, String>> + Send;
+    fn fetch_latest_release(&self) -> impl Future<Output = Result<Release, String>> + Send;
+    fn post_webhook(&self, url: &str, payload: &serde_json::Value) -> impl Future<Output = Result<(), String>> + Send;
+    fn publish_ntfy(
+        &self,
+        ntfy: &NtfyConfig,
+        title: &str,
+        message: &str,
+        priority: u8,
+    ) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+// The real APIs
+pub struct Network;
+
+impl Source for Network {
+    async fn fetch_weather(&self, query: &SearchQuery, cached: Option<GeoLocation>) -> Result<WeatherData, FetchError> {
+        fetch_weather(query, cached).await
+    }
+
+    async fn fetch_autocomplete(&self, query: &SearchQuery) -> Result<Vec<GeoLocation>, String> {
+        fetch_autocomplete(query).await.map_err(|e| e.to_string())
+    }
+
+    async fn geocode(&self, query: &SearchQuery) -> Result<GeoLocation, String> {
+        geocode(query).await.map_err(String::from)
+    }
+
+    async fn fetch_earthquakes(&self, latitude: f64, longitude: f64, radius_km: f64) -> Result<Vec<Earthquake>, String> {
+        fetch_earthquakes(latitude, longitude, radius_km).await
+    }
+
+    async fn fetch_brief(&self, icao: &str) -> Result<FlightBrief, String> {
+        fetch_brief(icao).await
+    }
+
+    async fn fetch_cyclones(&self) -> Result<Vec<Cyclone>, String> {
+        fetch_cyclones().await
+    }
+
+    async fn fetch_mountain(&self, latitude: f64, longitude: f64, elevations: &[f64]) -> Result<MountainForecast, String> {
+        fetch_mountain(latitude, longitude, elevations).await
+    }
+
+    async fn fetch_free_flight(&self, latitude: f64, longitude: f64) -> Result<FreeFlightForecast, String> {
+        fetch_free_flight(latitude, longitude).await
+    }
+
+    async fn fetch_daily(&self, location: &GeoLocation, days: u8) -> Result<DailyForecast, String> {
+        fetch_daily(location, days).await
+    }
+
+    async fn fetch_tides(&self, latitude: f64, longitude: f64, worldtides_key: Option<&str>) -> Result<Tides, String> {
+        fetch_tides(latitude, longitude, worldtides_key).await
+    }
+
+    async fn fetch_sea_temperature(&self, latitude: f64, longitude: f64) -> Result<TimeSeries<f64>, String> {
+        fetch_sea_temperature(latitude, longitude).await
+    }
+
+    async fn fetch_pressure(&self, latitude: f64, longitude: f64) -> Result<TimeSeries<f64>, String> {
+        fetch_pressure(latitude, longitude).await
+    }
+
+    async fn fetch_nearby(&self, latitude: f64, longitude: f64, offset_km: f64) -> Result<Vec<NearbyPoint>, String> {
+        fetch_nearby(latitude, longitude, offset_km).await
+    }
+
+    async fn fetch_latest_release(&self) -> Result<Release, String> {
+        fetch_latest_release().await
+    }
+
+    async fn post_webhook(&self, url: &str, payload: &serde_json::Value) -> Result<(), String> {
+        post_webhook(url, payload).await
+    }
+
+    async fn publish_ntfy(&self, ntfy: &NtfyConfig, title: &str, message: &str, priority: u8) -> Result<(), String> {
+        publish_ntfy(ntfy, title, message, priority).await
+    }
+}
+
+pub async fn fetch_weather(query: &SearchQuery, cached: Option<GeoLocation>) -> Result<WeatherData, FetchError> {
+    let (location, geocode) = match (cached, query.coordinates) {
+        (Some(location), _) => (location, None),
+        (None, Some((latitude, longitude))) => {
+            let started = Instant::now();
+            let location = reverse_geocode(latitude, longitude).await;
+            (location, Some(started.elapsed()))
+        }
+        (None, None) => {
+            let started = Instant::now();
+            let location = geocode(query).await?;
+            (location, Some(started.elapsed()))
+        }
+    };
+
+    let started = Instant::now();
+    let (weather, air_quality) = tokio::join!(forecast(&location), fetch_air_quality(&location));
+    let (mut weather, json_fallback) = weather?;
+    // Nice to have; the weather is still worth showing without it
+    if let (Some(hourly), Ok(air_quality)) = (weather.hourly.as_mut(), air_quality) {
+        hourly.air_quality = air_quality;
+    }
+    let timings = FetchTimings {
+        geocode,
+        forecast: started.elapsed(),
+        json_fallback,
+    };
+
+    let fetched_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(WeatherData { location, weather, timings, fetched_at, units: Units::default() })
+}
+
+// Up to ten matches, best first. Autocomplete and search share the cache, so a place seen once never hits the geocoder again
+async fn search_places(query: &SearchQuery) -> Result<Vec<GeoLocation>, reqwest::Error> {
+    // A recorded session goes to the geocoder every time, and leaves the cache on disk as it found it
+    let use_cache = !session::active();
+    if let Some(results) = geocache::get(query).filter(|_| use_cache) {
+        return Ok(results);
+    }
+    let url = format!(
+        "https://geocoding-api.open-meteo.com/v1/search?name={}&count=10&language=en&format=json{}",
+        urlencoding::encode(&query.name),
+        query.country_param()
+    );
+    let response = send(http_client().get(&url)).await?;
+    let data: GeocodingResponse = response.json().await?;
+    let results = data.results.unwrap_or_default();
+    if use_cache {
+        geocache::insert(query, &results);
+    }
+    Ok(results)
+}
+
+#[derive(Deserialize)]
+struct ReverseResponse {
+    lat: String,
+    lon: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    address: HashMap<String, String>,
+}
+
+// Open-Meteo can't go from coordinates to a name, so this asks OpenStreetMap's Nominatim.
+// Any failure just leaves the coordinates as the name; the weather itself doesn't need one
+pub async fn reverse_geocode(latitude: f64, longitude: f64) -> GeoLocation {
+    let fallback = GeoLocation {
+        name: format!("{:.4}, {:.4}", latitude, longitude),
+        latitude,
+        longitude,
+        country: String::new(),
+        admin1: None,
+        admin2: None,
+        admin3: None,
+        near_km: None,
+    };
+    let url = format!(
+        "https://nominatim.openstreetmap.org/reverse?format=jsonv2&lat={}&lon={}&zoom=10&accept-language=en",
+        latitude, longitude
+    );
+    let Ok(place) = get_json::<ReverseResponse>(&url).await else {
+        return fallback;
+    };
+    let name = ["city", "town", "village", "hamlet", "municipality"]
+        .iter()
+        .find_map(|k| place.address.get(*k).cloned())
+        .unwrap_or(place.name);
+    if name.is_empty() {
+        return fallback;
+    }
+    let near_km = match (place.lat.parse(), place.lon.parse()) {
+        (Ok(lat), Ok(lon)) => Some(geo::distance_km(latitude, longitude, lat, lon)),
+        _ => None,
+    };
+    GeoLocation {
+        name,
+        country: place.address.get("country").cloned().unwrap_or_default(),
+        admin1: place.address.get("state").cloned(),
+        admin2: place.address.get("county").cloned(),
+        near_km,
+        ..fallback
+    }
+}
+
+pub async fn geocode(query: &SearchQuery) -> Result<GeoLocation, FetchError> {
+    let results = search_places(query).await.map_err(|e| {
+        if e.is_decode() {
+            "Failed to parse location data from weather service.".to_string()
+        } else if e.is_timeout() {
+            "Connection timeout. Check your internet connection.".to_string()
+        } else if e.is_connect() {
+            "Cannot connect to weather service. Check your internet connection.".to_string()
+        } else {
+            format!("Network error: {}", e)
+        }
+    })?;
+
+    let not_found = match &query.country_code {
+        Some(code) => format!("'{}' not found in {}. Try a different city name.", query.name, code),
+        None => format!("'{}' not found. Try a different city name.", query.name),
+    };
+    results
+        .into_iter()
+        .next()
+        .ok_or(FetchError::NotFound(not_found))
+}
+
+// The forecast, and why it came as JSON when FlatBuffers were asked for
+async fn forecast(location: &GeoLocation) -> Result<(WeatherResponse, Option<String>), String> {
+    let weather_url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=temperature_2m,relative_humidity_2m,apparent_temperature,precipitation,weather_code,wind_speed_10m,pressure_msl,uv_index&hourly=temperature_2m,precipitation,wind_speed_10m,precipitation_probability,relative_humidity_2m,cloud_cover,sunshine_duration,is_day,dew_point_2m,soil_temperature_0cm&forecast_days=3&temperature_unit=celsius&wind_speed_unit=kmh&precipitation_unit=mm&timezone=auto",
+        location.latitude, location.longitude
+    );
+
+    // Anything that doesn't decode falls through to plain JSON
+    let mut fallback = None;
+    if FLATBUFFERS.load(Ordering::Relaxed) {
+        let url = format!("{}&format=flatbuffers", weather_url);
+        match conditional_get(&url).await {
+            Ok(body) => match flatbuf::decode_forecast(&body) {
+                Some(response) => return Ok((response, None)),
+                None => fallback = Some(format!("{} bytes of FlatBuffers that didn't decode", body.len())),
+            },
+            Err(e) => fallback = Some(format!("FlatBuffers request failed: {}", e)),
+        }
+    }
+
+    let body = conditional_get(&weather_url)
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                "Connection timeout while fetching weather data.".to_string()
+            } else if e.is_connect() {
+                "Cannot connect to weather service.".to_string()
+            } else {
+                format!("Network error: {}", e)
+            }
+        })?;
+
+    let response = serde_json::from_slice(&body).map_err(|_| "Failed to parse weather data from service.".to_string())?;
+    Ok((response, fallback))
+}
+
+// Just the forecast at a point, for the subcommands that have no use for air quality or timings
+pub async fn fetch_forecast(location: &GeoLocation) -> Result<WeatherResponse, String> {
+    Ok(forecast(location).await?.0)
+}
+
+#[derive(Deserialize)]
+struct AirQualityResponse {
+    hourly: AirQualityHourly,
+}
+
+#[derive(Deserialize)]
+struct AirQualityHourly {
+    time: Vec<String>,
+    // Null for hours the model hasn't covered yet
+    european_aqi: Vec<Option<f64>>,
+}
+
+// Hourly European AQI on the same local times as the forecast
+pub async fn fetch_air_quality(location: &GeoLocation) -> Result<TimeSeries<f64>, String> {
+    let url = format!(
+        "https://air-quality-api.open-meteo.com/v1/air-quality?latitude={}&longitude={}&hourly=european_aqi&forecast_days=3&timezone=auto",
+        location.latitude, location.longitude
+    );
+    let response: AirQualityResponse = get_json(&url).await?;
+    let (time, values) = response
+        .hourly
+        .time
+        .into_iter()
+        .zip(response.hourly.european_aqi)
+        .filter_map(|(time, aqi)| Some((time, aqi?)))
+        .unzip();
+    Ok(TimeSeries::new(time, values, "EAQI"))
+}
+
+pub async fn fetch_daily(location: &GeoLocation, days: u8) -> Result<DailyForecast, String> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&daily=weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum,wind_speed_10m_max&forecast_days={}&temperature_unit=celsius&wind_speed_unit=kmh&precipitation_unit=mm&timezone=auto",
+        location.latitude, location.longitude, days
+    );
+
+    let response = send(http_client().get(&url))
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                "Connection timeout while fetching the forecast.".to_string()
+            } else if e.is_connect() {
+                "Cannot connect to weather service.".to_string()
+            } else {
+                format!("Network error: {}", e)
+            }
+        })?;
+
+    response
+        .json()
+        .await
+        .map_err(|_| "Failed to parse forecast data from service.".to_string())
+}
+
+pub async fn fetch_cyclones() -> Result<Vec<Cyclone>, String> {
+    let body = conditional_get("https://www.nhc.noaa.gov/CurrentStorms.json")
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                "Connection timeout while fetching storm advisories.".to_string()
+            } else if e.is_connect() {
+                "Cannot connect to the National Hurricane Center.".to_string()
+            } else {
+                format!("Network error: {}", e)
+            }
+        })?;
+
+    let storms: CurrentStorms =
+        serde_json::from_slice(&body).map_err(|_| "Failed to parse the storm advisories.".to_string())?;
+    Ok(storms.active_storms)
+}
+
+pub async fn fetch_earthquakes(latitude: f64, longitude: f64, radius_km: f64) -> Result<Vec<Earthquake>, String> {
+    const WEEK: u64 = 7 * 24 * 3600;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let url = format!(
+        "https://earthquake.usgs.gov/fdsnws/event/1/query?format=geojson&latitude={}&longitude={}&maxradiuskm={}&starttime={}&minmagnitude=2.5&orderby=time&limit=20",
+        latitude,
+        longitude,
+        radius_km.clamp(1.0, 20001.6),
+        iso_date(now.saturating_sub(WEEK))
+    );
+
+    let body = conditional_get(&url)
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                "Connection timeout while fetching earthquakes.".to_string()
+            } else if e.is_connect() {
+                "Cannot connect to the USGS earthquake service.".to_string()
+            } else {
+                format!("Network error: {}", e)
+            }
+        })?;
+
+    let collection: QuakeCollection =
+        serde_json::from_slice(&body).map_err(|_| "Failed to parse earthquake data.".to_string())?;
+
+    Ok(collection
+        .features
+        .into_iter()
+        .filter_map(|f| {
+            let [lon, lat, depth] = f.geometry.coordinates[..] else {
+                return None;
+            };
+            Some(Earthquake {
+                magnitude: f.properties.mag?,
+                depth_km: depth,
+                time: (f.properties.time / 1000).max(0) as u64,
+                place: f.properties.place.unwrap_or_else(|| "Unknown place".to_string()),
+                latitude: lat,
+                longitude: lon,
+            })
+        })
+        .collect())
+}
+
+// "YYYY-MM-DD" in UTC, using the days-to-civil algorithm to avoid a date crate
+fn iso_date(unix_secs: u64) -> String {
+    let days = (unix_secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub async fn fetch_brief(icao: &str) -> Result<FlightBrief, String> {
+    let icao = icao.trim().to_ascii_uppercase();
+    let metars: Vec<MetarReport> = get_json(&format!(
+        "https://aviationweather.gov/api/data/metar?ids={}&format=json",
+        urlencoding::encode(&icao)
+    ))
+    .await?;
+    let metar = metars
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No METAR for {}. Is that an ICAO code?", icao))?;
+
+    let taf_url = format!(
+        "https://aviationweather.gov/api/data/taf?ids={}&format=json",
+        urlencoding::encode(&icao)
+    );
+    let surface_url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=wind_speed_10m,wind_direction_10m,wind_gusts_10m&wind_speed_unit=kn&forecast_hours=12&timezone=auto",
+        metar.lat, metar.lon
+    );
+    let (tafs, surface) = tokio::join!(
+        get_json::<Vec<TafReport>>(&taf_url),
+        get_json::<SurfaceResponse>(&surface_url)
+    );
+    let hourly = surface?.hourly;
+
+    let direction = match &metar.wdir {
+        Some(serde_json::Value::Number(n)) => n.as_f64(),
+        _ => None,
+    };
+    Ok(FlightBrief {
+        name: metar.name.unwrap_or_else(|| icao.clone()),
+        icao,
+        metar: metar.raw_ob,
+        taf: tafs?.into_iter().next().map(|t| t.raw_taf),
+        wind: metar.wspd.map(|speed| (direction, speed, metar.wgst)),
+        surface: (0..hourly.time.len())
+            .map(|i| SurfaceWind {
+                time: hourly.time[i].clone(),
+                direction: hourly.wind_direction_10m[i],
+                speed: hourly.wind_speed_10m[i],
+                gusts: hourly.wind_gusts_10m[i],
+            })
+            .collect(),
+    })
+}
+
+// One request with the spot repeated once per elevation; Open-Meteo corrects the temperature
+// (and so rain vs snow) for each height
+pub async fn fetch_mountain(latitude: f64, longitude: f64, elevations: &[f64]) -> Result<MountainForecast, String> {
+    let repeat = |value: f64| vec![value.to_string(); elevations.len()].join(",");
+    let heights: Vec<String> = elevations.iter().map(|e| e.to_string()).collect();
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&elevation={}&hourly=temperature_2m,snowfall,freezing_level_height,wind_speed_850hPa,wind_direction_850hPa&forecast_days=3&temperature_unit=celsius&wind_speed_unit=kmh&timezone=auto",
+        repeat(latitude),
+        repeat(longitude),
+        heights.join(",")
+    );
+    let mut responses = get_json::<OneOrMany<MountainResponse>>(&url).await?.into_vec();
+    responses.sort_by(|a, b| a.elevation.total_cmp(&b.elevation));
+    // Freezing level and upper winds don't depend on the elevation asked for
+    let first = responses.first().ok_or_else(|| "No mountain forecast for this place".to_string())?;
+    let time = first.hourly.time.clone();
+    Ok(MountainForecast {
+        freezing_level: TimeSeries::new(time.clone(), first.hourly.freezing_level_height.clone(), "m"),
+        wind_850: TimeSeries::new(time.clone(), first.hourly.wind_speed_850.clone(), "km/h"),
+        wind_850_direction: TimeSeries::new(time, first.hourly.wind_direction_850.clone(), "°"),
+        elevations: responses
+            .into_iter()
+            .map(|r| ElevationForecast {
+                elevation: r.elevation,
+                temperature: TimeSeries::new(r.hourly.time.clone(), r.hourly.temperature_2m, "°C"),
+                snowfall: TimeSeries::new(r.hourly.time, r.hourly.snowfall, "cm"),
+            })
+            .collect(),
+    })
+}
+
+// Every point of a route in one request, in order. Heights from the track make Open-Meteo correct
+// the temperature for them; it only takes them for all points or none, so a gap means none
+pub async fn fetch_route(points: &[(f64, f64, Option<f64>)]) -> Result<Vec<RouteForecast>, String> {
+    let list = |values: Vec<f64>| values.iter().map(f64::to_string).collect::<Vec<_>>().join(",");
+    let elevation = match points.iter().map(|p| p.2).collect::<Option<Vec<f64>>>() {
+        Some(heights) => format!("&elevation={}", list(heights)),
+        None => String::new(),
+    };
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}{}&hourly=temperature_2m,precipitation,precipitation_probability,wind_speed_10m&forecast_days=3&temperature_unit=celsius&wind_speed_unit=kmh&precipitation_unit=mm&timezone=auto",
+        list(points.iter().map(|p| p.0).collect()),
+        list(points.iter().map(|p| p.1).collect()),
+        elevation
+    );
+    let responses = get_json::<OneOrMany<RouteResponse>>(&url).await?.into_vec();
+    Ok(responses
+        .into_iter()
+        .map(|r| {
+            let time = r.hourly.time;
+            let probability = r.hourly.precipitation_probability.into_iter().map(|p| p.unwrap_or(f64::NAN)).collect();
+            RouteForecast {
+                elevation: r.elevation,
+                temperature: TimeSeries::new(time.clone(), r.hourly.temperature_2m, "°C"),
+                precipitation: TimeSeries::new(time.clone(), r.hourly.precipitation, "mm"),
+                precipitation_probability: TimeSeries::new(time.clone(), probability, "%"),
+                wind_speed: TimeSeries::new(time, r.hourly.wind_speed_10m, "km/h"),
+            }
+        })
+        .collect())
+}
+
+pub async fn fetch_free_flight(latitude: f64, longitude: f64) -> Result<FreeFlightForecast, String> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m,temperature_700hPa,geopotential_height_700hPa,boundary_layer_height,wind_speed_925hPa,wind_direction_925hPa,wind_speed_850hPa,wind_direction_850hPa,wind_speed_700hPa,wind_direction_700hPa&forecast_days=2&temperature_unit=celsius&wind_speed_unit=kmh&timezone=auto",
+        latitude, longitude
+    );
+    let response: FreeFlightResponse = get_json(&url).await?;
+    let h = response.hourly;
+    let lapse_rate = (0..h.time.len().min(h.temperature_2m.len()).min(h.temperature_700.len()).min(h.height_700.len()))
+        .map(|i| {
+            let climb_km = ((h.height_700[i] - response.elevation) / 1000.0).max(0.5);
+            (h.temperature_2m[i] - h.temperature_700[i]) / climb_km
+        })
+        .collect();
+    let level = |hpa, altitude, speed, direction| PressureLevel {
+        hpa,
+        altitude,
+        speed: TimeSeries::new(h.time.clone(), speed, "km/h"),
+        direction: TimeSeries::new(h.time.clone(), direction, "°"),
+    };
+    Ok(FreeFlightForecast {
+        levels: vec![
+            level(925, 800, h.wind_speed_925, h.wind_direction_925),
+            level(850, 1500, h.wind_speed_850, h.wind_direction_850),
+            level(700, 3000, h.wind_speed_700, h.wind_direction_700),
+        ],
+        boundary_layer: TimeSeries::new(h.time.clone(), h.boundary_layer_height, "m"),
+        lapse_rate: TimeSeries::new(h.time.clone(), lapse_rate, "°C/km"),
+    })
+}
+
+// NOAA's free predictions where there's a US station nearby, otherwise WorldTides if there's a key for it
+pub async fn fetch_tides(latitude: f64, longitude: f64, worldtides_key: Option<&str>) -> Result<Tides, String> {
+    match (fetch_noaa_tides(latitude, longitude).await, worldtides_key) {
+        (Ok(tides), _) => Ok(tides),
+        (Err(_), Some(key)) => fetch_worldtides(latitude, longitude, key).await,
+        (Err(e), None) => Err(e),
+    }
+}
+
+async fn fetch_noaa_tides(latitude: f64, longitude: f64) -> Result<Tides, String> {
+    // Further than this and the tide can be hours off what it is at the coast here
+    const MAX_STATION_KM: f64 = 100.0;
+    // The station list barely changes, so the cache answers most of these with a 304
+    let body = conditional_get("https://api.tidesandcurrents.noaa.gov/mdapi/prod/webapi/stations.json?type=tidepredictions")
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    let stations: NoaaStations =
+        serde_json::from_slice(&body).map_err(|_| "Failed to parse the NOAA tide station list.".to_string())?;
+    let (station, distance) = stations
+        .stations
+        .into_iter()
+        .map(|s| {
+            let distance = geo::distance_km(latitude, longitude, s.lat, s.lng);
+            (s, distance)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .filter(|(_, distance)| *distance <= MAX_STATION_KM)
+        .ok_or_else(|| "No tide station nearby; set worldtides_key in the config for tides outside the US".to_string())?;
+
+    // From yesterday in UTC, so the next couple of days are covered whatever the local time zone
+    let yesterday = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        .saturating_sub(86400);
+    let url = format!(
+        "https://api.tidesandcurrents.noaa.gov/api/prod/datagetter?station={}&product=predictions&interval=hilo&datum=MLLW&time_zone=lst_ldt&units=metric&format=json&begin_date={}&range=96&application=weather-tui",
+        station.id,
+        iso_date(yesterday).replace('-', "")
+    );
+    let response: NoaaPredictions = get_json(&url).await?;
+    if let Some(error) = response.error {
+        return Err(format!("NOAA tides: {}", error.message));
+    }
+    Ok(Tides {
+        station: Some(format!("{}, {:.0} km away", station.name, distance)),
+        provider: Provider::NoaaTides,
+        extremes: response
+            .predictions
+            .into_iter()
+            .filter_map(|p| {
+                Some(Tide {
+                    time: p.t.replacen(' ', "T", 1),
+                    high: p.kind == "H",
+                    height: p.v.trim().parse().ok()?,
+                })
+            })
+            .collect(),
+    })
+}
+
+async fn fetch_worldtides(latitude: f64, longitude: f64, key: &str) -> Result<Tides, String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let url = format!(
+        "https://www.worldtides.info/api/v3?extremes&localtime&lat={}&lon={}&start={}&length=172800&stationDistance=50&key={}",
+        latitude,
+        longitude,
+        now.saturating_sub(6 * 3600),
+        urlencoding::encode(key)
+    );
+    let response: WorldTidesResponse = get_json(&url).await?;
+    if let Some(error) = response.error {
+        return Err(format!("WorldTides: {}", error));
+    }
+    if response.extremes.is_empty() {
+        return Err("No tide predictions for this place".to_string());
+    }
+    Ok(Tides {
+        station: response.station,
+        provider: Provider::WorldTides,
+        extremes: response
+            .extremes
+            .into_iter()
+            .map(|e| Tide {
+                // Drop the UTC offset; every other time on screen is local already
+                time: e.date.chars().take(16).collect(),
+                high: e.kind == "High",
+                height: e.height,
+            })
+            .collect(),
+    })
+}
+
+// Hourly sea surface temperature from a week ago to a week ahead, local time
+pub async fn fetch_sea_temperature(latitude: f64, longitude: f64) -> Result<TimeSeries<f64>, String> {
+    let url = format!(
+        "https://marine-api.open-meteo.com/v1/marine?latitude={}&longitude={}&hourly=sea_surface_temperature&past_days=7&forecast_days=7&timezone=auto",
+        latitude, longitude
+    );
+    let response: SeaTemperatureResponse = get_json(&url).await?;
+    let (time, values): (Vec<String>, Vec<f64>) = response
+        .hourly
+        .time
+        .into_iter()
+        .zip(response.hourly.sea_surface_temperature)
+        .filter_map(|(time, sst)| Some((time, sst?)))
+        .unzip();
+    if values.is_empty() {
+        return Err("No sea temperature for this place. Is it on the coast?".to_string());
+    }
+    Ok(TimeSeries::new(time, values, "°C"))
+}
+
+// Hourly sea-level pressure from two days ago to at least two days ahead, local time
+pub async fn fetch_pressure(latitude: f64, longitude: f64) -> Result<TimeSeries<f64>, String> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=pressure_msl&past_days=2&forecast_days=3&timezone=auto",
+        latitude, longitude
+    );
+    let response: PressureResponse = get_json(&url).await?;
+    let (time, values): (Vec<String>, Vec<f64>) = response
+        .hourly
+        .time
+        .into_iter()
+        .zip(response.hourly.pressure_msl)
+        .filter_map(|(time, pressure)| Some((time, pressure?)))
+        .unzip();
+    if values.is_empty() {
+        return Err("No pressure forecast for this place".to_string());
+    }
+    Ok(TimeSeries::new(time, values, "hPa"))
+}
+
+// The location plus a point `offset_km` away to the N, E, S and W, in that order, all in one request
+pub async fn fetch_nearby(latitude: f64, longitude: f64, offset_km: f64) -> Result<Vec<NearbyPoint>, String> {
+    const POINTS: [(&str, Option<f64>); 5] =
+        [("Here", None), ("N", Some(0.0)), ("E", Some(90.0)), ("S", Some(180.0)), ("W", Some(270.0))];
+    let coordinates: Vec<(f64, f64)> = POINTS
+        .iter()
+        .map(|(_, bearing)| match bearing {
+            Some(bearing) => geo::destination(latitude, longitude, *bearing, offset_km),
+            None => (latitude, longitude),
+        })
+        .collect();
+    let join = |pick: fn(&(f64, f64)) -> f64| coordinates.iter().map(|c| format!("{:.4}", pick(c))).collect::<Vec<_>>().join(",");
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m,precipitation,wind_speed_10m&forecast_days=2&temperature_unit=celsius&wind_speed_unit=kmh&precipitation_unit=mm&timezone=auto",
+        join(|c| c.0),
+        join(|c| c.1)
+    );
+    // Several locations always come back as a list, in the order they were asked for
+    let responses: Vec<NearbyResponse> = get_json(&url).await?;
+    if responses.len() != POINTS.len() {
+        return Err("Incomplete forecast for the nearby points".to_string());
+    }
+    Ok(POINTS
+        .iter()
+        .zip(responses)
+        .map(|((label, _), r)| NearbyPoint {
+            label,
+            elevation: r.elevation,
+            temperature: TimeSeries::new(r.hourly.time.clone(), r.hourly.temperature_2m, "°C"),
+            precipitation: TimeSeries::new(r.hourly.time.clone(), r.hourly.precipitation, "mm"),
+            wind_speed: TimeSeries::new(r.hourly.time, r.hourly.wind_speed_10m, "km/h"),
+        })
+        .collect())
+}
+
+// Drafts and pre-releases don't count; GitHub leaves them out of "latest" already
+pub async fn fetch_latest_release() -> Result<Release, String> {
+    let response = send(
+        http_client()
+            .get("https://api.github.com/repos/kantiankant/weather-tui/releases/latest")
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json"),
+    )
+    .await
+    .map_err(|e| format!("Couldn't check for a new version: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Couldn't check for a new version: GitHub said {}", response.status()));
+    }
+    let release: GithubRelease =
+        response.json().await.map_err(|_| "Couldn't make sense of the latest release.".to_string())?;
+    Ok(Release { version: release.tag_name.trim_start_matches('v').to_string(), url: release.html_url })
+}
+
+// Retries with a growing delay, since chat services rate-limit and home servers blip
+pub async fn post_webhook(url: &str, payload: &serde_json::Value) -> Result<(), String> {
+    send_with_retries(|| http_client().post(url).json(payload))
+        .await
+        .map_err(|e| format!("Webhook {} failed: {}", url, e))
+}
+
+// ntfy takes the message as the body and everything else as headers
+pub async fn publish_ntfy(ntfy: &NtfyConfig, title: &str, message: &str, priority: u8) -> Result<(), String> {
+    let url = format!("{}/{}", ntfy.server.trim_end_matches('/'), urlencoding::encode(&ntfy.topic));
+    send_with_retries(|| {
+        let request = http_client()
+            .post(&url)
+            .header("Title", title)
+            .header("Priority", priority.to_string())
+            .header("Tags", "warning")
+            .body(message.to_string());
+        match &ntfy.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    })
+    .await
+    .map_err(|e| format!("ntfy topic {} failed: {}", ntfy.topic, e))
+}
+
+async fn send_with_retries(request: impl Fn() -> reqwest::RequestBuilder) -> Result<(), String> {
+    const ATTEMPTS: u32 = 3;
+    let mut last_error = String::new();
+    for attempt in 0..ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+        }
+        match send(request()).await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => last_error = format!("HTTP {}", response.status()),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    Err(format!("{} after {} attempts", last_error, ATTEMPTS))
+}
+
+// GET that revalidates with If-None-Match / If-Modified-Since when we've seen the URL before,
+// so an unchanged answer costs a 304 instead of the whole body
+async fn conditional_get(url: &str) -> Result<Vec<u8>, reqwest::Error> {
+    use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+
+    let cached = httpcache::get(url).filter(|_| !session::active());
+    let mut request = http_client().get(url);
+    if let Some(cached) = &cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = send(request).await?;
+    if let (reqwest::StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), cached) {
+        return Ok(cached.body);
+    }
+    let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(String::from);
+    let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+    let ok = response.status().is_success();
+    let body = response.bytes().await?.to_vec();
+    if ok {
+        httpcache::store(url, etag, last_modified, &body);
+    }
+    Ok(body)
+}
+
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let response = send(http_client().get(url))
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Download of {} failed: {}", url, e))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Download of {} failed: {}", url, e))?;
+    Ok(bytes.to_vec())
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(url: &str) -> Result<T, String> {
+    let response = send(http_client().get(url))
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                "Connection timeout while fetching the brief.".to_string()
+            } else if e.is_connect() {
+                "Cannot connect to the aviation weather service.".to_string()
+            } else {
+                format!("Network error: {}", e)
+            }
+        })?;
+    response
+        .json()
+        .await
+        .map_err(|_| "Failed to parse aviation weather data.".to_string())
+}
+
+pub async fn fetch_autocomplete(query: &SearchQuery) -> Result<Vec<GeoLocation>, Box<dyn Error>> {
+    Ok(search_places(query).await?)
+}
+
+// Banner text for the weather codes worth interrupting someone for
+pub fn severe_weather(code: u32) -> Option<&'static str> {
+    match code {
+        65 => Some("Heavy rain"),
+        66 | 67 => Some("Freezing rain"),
+        75 => Some("Heavy snowfall"),
+        82 => Some("Violent rain showers"),
+        86 => Some("Heavy snow showers"),
+        95 => Some("Thunderstorm"),
+        96 | 99 => Some("Thunderstorm with hail"),
+        _ => None,
+    }
+}
+
+pub fn weather_code_to_description(code: u32) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Foggy",
+        51 | 53 | 55 => "Drizzle",
+        61 | 63 | 65 => "Rain",
+        71 | 73 | 75 => "Snow",
+        77 => "Snow grains",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates_parse_with_a_comma_or_a_space() {
+        assert_eq!(parse_coordinates("51.5,-0.12"), Some((51.5, -0.12)));
+        assert_eq!(parse_coordinates(" 69.65, 18.96 "), Some((69.65, 18.96)));
+        assert_eq!(parse_coordinates("-33.87 151.21"), Some((-33.87, 151.21)));
+        assert_eq!(parse_coordinates("90,180"), Some((90.0, 180.0)));
+    }
+
+    #[test]
+    fn anything_else_is_a_place_name() {
+        assert_eq!(parse_coordinates("91,0"), None);
+        assert_eq!(parse_coordinates("0,-181"), None);
+        assert_eq!(parse_coordinates("1,2,3"), None);
+        assert_eq!(parse_coordinates("51.5"), None);
+        assert_eq!(parse_coordinates("Paris"), None);
+        assert_eq!(parse_coordinates("Route 66, 12"), None);
+
+        let query = SearchQuery::parse("51.5, -0.12", Some("DE"));
+        assert_eq!((query.coordinates, query.country_code), (Some((51.5, -0.12)), None));
+        assert_eq!(SearchQuery::parse("Paris @FR", None).coordinates, None);
+    }
+
+    #[tokio::test]
+    async fn an_unchanged_response_reuses_the_cached_body() {
+        use std::io::{BufRead, BufReader, Write};
+
+        // Answers the first request in full and the second with a 304, handing back what each one asked with
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/storms.json", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let replies = [
+                "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 9\r\nConnection: close\r\n\r\n{\"a\":[1]}",
+                "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+            ];
+            replies.map(|reply| {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    request.push_str(&line.to_lowercase());
+                    request.push('\n');
+                }
+                stream.write_all(reply.as_bytes()).unwrap();
+                request
+            })
+        });
+
+        let first = conditional_get(&url).await.unwrap();
+        let second = conditional_get(&url).await.unwrap();
+        let [asked_first, asked_second] = server.join().unwrap();
+        assert_eq!(first, br#"{"a":[1]}"#);
+        assert_eq!(second, first);
+        assert!(!asked_first.contains("if-none-match"));
+        assert!(asked_second.contains("if-none-match: \"v1\""));
+    }
+}
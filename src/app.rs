@@ -0,0 +1,2640 @@
+use chrono::Timelike;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use std::{cell::OnceCell, collections::HashSet, path::PathBuf, time::Duration};
+
+use serde_json::Value;
+
+use crate::action::{self, Action};
+use crate::api::{
+    self, Cyclone, DailyForecast, Earthquake, FetchError, FlightBrief, FreeFlightForecast, GeoLocation,
+    MountainForecast, NearbyPoint, Release, SearchQuery, Tides, WeatherData,
+};
+use crate::config::{Config, NtfyConfig, Plugin};
+use crate::effects::Precipitation;
+use crate::events::{Event, EventKind};
+use crate::focus::{Focusable, Pane};
+use crate::forecast::{self, ForecastDiff};
+use crate::fuzzy;
+use crate::gazetteer::{self, Gazetteer};
+use crate::geo;
+use crate::history::{self, HistoryEntry};
+use crate::plugins::{self, PluginResult};
+use crate::popup::{Popup, PopupOutcome};
+use crate::ranking;
+use crate::records::{ForecastCheck, Observation, TrendPoint};
+use crate::report;
+use crate::rules;
+use crate::series::TimeSeries;
+use crate::summary;
+use crate::template;
+use crate::units::Units;
+use crate::view::{help_lines, stats_lines, TileText};
+
+// Timers (spinner, toasts, debounce, auto-refresh) count ticks rather than wall time
+pub const TICK_RATE: Duration = Duration::from_millis(250);
+pub const TICKS_PER_SECOND: u64 = 4;
+
+// A frame that takes this long eats a fifth of the tick, and this many in a row turn effects off
+const SLOW_FRAME: Duration = Duration::from_millis(50);
+const SLOW_FRAMES: u32 = 8;
+
+pub const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+pub struct Toast {
+    pub message: String,
+    pub expires_at: u64,
+}
+
+// One location on the kiosk dashboard
+pub struct KioskTile {
+    pub query: String,
+    pub data: Option<WeatherData>,
+    pub error: Option<String>,
+    // A fetch for this tile is on its way
+    pub pending: bool,
+    // Filled in on the first draw after new data arrives
+    pub text: OnceCell<TileText>,
+}
+
+#[derive(PartialEq)]
+pub enum Mode {
+    Normal,
+    Insert,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AppState {
+    Input,
+    Loading,
+    Display,
+    Error,
+    Trends,
+    Accuracy,
+    Cyclones,
+    Mountain,
+    FreeFlight,
+    Marine,
+    Barograph,
+    DewPoint,
+    Nearby,
+    Brief,
+    Events,
+}
+
+// Recorded observations for the displayed location, shown `hours` at a time, ending `offset` hours ago
+pub struct Trends {
+    pub now: u64,
+    pub hours: u64,
+    pub offset: u64,
+    pub points: Vec<TrendPoint>,
+    // The point picked with Left/Right, as an index into `recent()`
+    pub cursor: Option<usize>,
+    // Where the mouse last moved to; the view works out which point that's over
+    pub pointer: Option<(u16, u16)>,
+}
+
+impl Trends {
+    // The points inside the current window, oldest first
+    pub fn recent(&self) -> Vec<&TrendPoint> {
+        let until = self.now.saturating_sub(self.offset * 3600);
+        let since = until.saturating_sub(self.hours * 3600);
+        self.points.iter().filter(|p| (since..=until).contains(&p.fetched_at)).collect()
+    }
+
+    // Moves the window by `hours` (back in time when positive), never past what was loaded
+    fn pan(&mut self, hours: i64) {
+        self.offset = self.offset.saturating_add_signed(hours).min(TREND_HOURS - self.hours);
+        self.cursor = None;
+        self.pointer = None;
+    }
+}
+
+// What V offers on the weather view
+pub const CHARTS: [(&str, Action); 3] = [
+    ("Trends: your recorded temperature, rain and pressure", Action::ShowTrends),
+    ("Barograph: pressure and storm warnings", Action::ShowBarograph),
+    ("Dew point: how muggy the next 48 h will feel", Action::ShowDewPoint),
+];
+
+pub const TREND_HOURS: u64 = 7 * 24;
+// What 1-4 and +/- zoom between
+pub const TREND_WINDOWS: [u64; 4] = [12, 24, 48, TREND_HOURS];
+pub const ACCURACY_DAYS: u64 = 7;
+// Metres; valley, mid-station and summit of a typical Alpine resort
+const DEFAULT_ELEVATIONS: [f64; 3] = [1000.0, 2000.0, 3000.0];
+// Far enough to land in a neighbouring grid cell of even the coarser models
+pub const NEARBY_OFFSET_KM: f64 = 5.0;
+// A marker on the place, zoomed in to about town size
+const OPENSTREETMAP: &str = "https://www.openstreetmap.org/?mlat={lat}&mlon={lon}#map=12/{lat}/{lon}";
+// Charts of the same model run the TUI shows, with every variable Open-Meteo has to offer
+const OPEN_METEO_PAGE: &str = "https://open-meteo.com/en/docs?latitude={lat}&longitude={lon}";
+
+#[derive(Clone)]
+pub enum PopupAction {
+    DeleteHistoryEntry(usize),
+    MarkedHistory,
+    SearchHistoryEntry,
+    FetchBrief,
+    OpenChart,
+    Palette(Vec<PaletteEntry>),
+}
+
+// What a line in the Ctrl-P palette does
+#[derive(Clone)]
+pub enum PaletteEntry {
+    Run(Action),
+    Search(String),
+}
+
+pub struct App {
+    pub input: String,
+    pub cursor_position: usize,
+    pub state: AppState,
+    pub mode: Mode,
+    pub weather_data: Option<WeatherData>,
+    pub error_message: String,
+    pub search_history: Vec<HistoryEntry>,
+    pub autocomplete_candidates: Vec<GeoLocation>,
+    pub autocomplete_suggestions: Vec<GeoLocation>,
+    // ", Bavaria" and the like for each suggestion, worked out when the list changes rather than every frame
+    pub autocomplete_regions: Vec<String>,
+    // Local city list; when present it answers autocomplete without the network
+    pub gazetteer: Option<Gazetteer>,
+    // "Did you mean" offers after a search that found nothing
+    pub corrections: Vec<Correction>,
+    // Saved with :home set, otherwise resolved from config.home at startup
+    pub home: Option<GeoLocation>,
+    // Text typed after ':', while the command line is open
+    pub command_line: Option<String>,
+    pub selected_suggestion: usize,
+    pub show_autocomplete: bool,
+    last_autocomplete_query: String,
+    // Input text produced by accepting a suggestion, with the place it was picked from
+    accepted_suggestion: Option<(String, GeoLocation)>,
+    pub focused_pane: Pane,
+    pub selected_history_index: usize,
+    // History entries marked with Space for a batch action, by query
+    pub marked_history: HashSet<String>,
+    pub selected_recent: usize,
+    pub debug: bool,
+    // Nothing is written to disk: no history, no recorded observations
+    pub read_only: bool,
+    in_flight: HashSet<String>,
+    pub config: Config,
+    pub popup: Option<Popup<PopupAction>>,
+    pub big_temperature: bool,
+    pub effects: bool,
+    // Consecutive frames that took too long to draw while effects were on
+    slow_frames: u32,
+    pub kiosk: bool,
+    // Opened from the history list rather than with --kiosk, so Esc goes back to it
+    pub kiosk_from_history: bool,
+    pub kiosk_tiles: Vec<KioskTile>,
+    // Dashboard fetches still out when it was closed, to be dropped when they land
+    abandoned: HashSet<String>,
+    pub trends: Option<Trends>,
+    pub forecast_checks: Vec<ForecastCheck>,
+    // What the last background refresh changed in the hourly forecast
+    pub forecast_diff: ForecastDiff,
+    // Active storms, nearest to the displayed location first
+    pub cyclones: Vec<Cyclone>,
+    pub selected_cyclone: usize,
+    // Recent quakes near the displayed location when earthquake_radius_km is set
+    pub earthquakes: Option<Result<Vec<Earthquake>, String>>,
+    // Panels from configured plugins for the displayed location, in config order
+    pub plugin_panels: Vec<PluginResult>,
+    pub mountain: Option<MountainForecast>,
+    pub free_flight: Option<FreeFlightForecast>,
+    // Either can be missing from the marine view: no tide station nearby, or no sea model this far inland
+    pub tides: Option<Result<Tides, String>>,
+    pub sea_temperature: Option<Result<TimeSeries<f64>, String>>,
+    // Hourly sea-level pressure, two days back to two days ahead
+    pub barograph: Option<TimeSeries<f64>>,
+    // The location first, then the points around it
+    pub nearby: Vec<NearbyPoint>,
+    // Pre-flight brief and the runway heading configured for that airport
+    pub brief: Option<FlightBrief>,
+    pub brief_runway: Option<f64>,
+    // Persisted background events, oldest first
+    pub events: Vec<Event>,
+    pub events_scroll: usize,
+    // "rule@lat,lon" for rules currently breached, so each crossing fires once
+    fired_rules: HashSet<String>,
+    // Events that arrived during the current quiet hours
+    quiet_unread: usize,
+    was_quiet: bool,
+    pub tick: u64,
+    pub toast: Option<Toast>,
+    // A city from the command line, looked up as soon as the app starts
+    pub open_with: Option<String>,
+    // Stays in the footer until Esc, behind any toast that comes and goes meanwhile
+    pub notice: Option<String>,
+    // Ticks left until the displayed location is fetched again
+    pub refresh_countdown: Option<u64>,
+    autocomplete_pending: bool,
+    current_query: Option<String>,
+    refresh_key: Option<String>,
+    // Set whenever something visible changed since the last frame was drawn
+    dirty: bool,
+    commands: Vec<Command>,
+}
+
+impl App {
+    pub fn new(history: Vec<HistoryEntry>, debug: bool, config: Config) -> App {
+        let big_temperature = config.big_temperature;
+        let effects = config.weather_effects;
+        App {
+            input: String::new(),
+            cursor_position: 0,
+            state: AppState::Input,
+            mode: Mode::Normal,
+            weather_data: None,
+            error_message: String::new(),
+            search_history: history,
+            autocomplete_candidates: Vec::new(),
+            autocomplete_suggestions: Vec::new(),
+            autocomplete_regions: Vec::new(),
+            gazetteer: None,
+            corrections: Vec::new(),
+            home: None,
+            command_line: None,
+            selected_suggestion: 0,
+            show_autocomplete: false,
+            last_autocomplete_query: String::new(),
+            accepted_suggestion: None,
+            focused_pane: Pane::Search,
+            selected_history_index: 0,
+            marked_history: HashSet::new(),
+            selected_recent: 0,
+            debug,
+            read_only: false,
+            in_flight: HashSet::new(),
+            config,
+            popup: None,
+            big_temperature,
+            effects,
+            slow_frames: 0,
+            kiosk: false,
+            kiosk_from_history: false,
+            kiosk_tiles: Vec::new(),
+            abandoned: HashSet::new(),
+            trends: None,
+            forecast_checks: Vec::new(),
+            forecast_diff: ForecastDiff::default(),
+            cyclones: Vec::new(),
+            selected_cyclone: 0,
+            earthquakes: None,
+            plugin_panels: Vec::new(),
+            mountain: None,
+            free_flight: None,
+            tides: None,
+            sea_temperature: None,
+            barograph: None,
+            nearby: Vec::new(),
+            brief: None,
+            brief_runway: None,
+            events: Vec::new(),
+            events_scroll: 0,
+            fired_rules: HashSet::new(),
+            quiet_unread: 0,
+            was_quiet: false,
+            tick: 0,
+            toast: None,
+            open_with: None,
+            notice: None,
+            refresh_countdown: None,
+            autocomplete_pending: false,
+            current_query: None,
+            refresh_key: None,
+            dirty: true,
+            commands: Vec::new(),
+        }
+    }
+
+   
+    fn char_to_byte_pos(&self, char_pos: usize) -> usize {
+        self.input
+            .char_indices()
+            .nth(char_pos)
+            .map(|(byte_pos, _)| byte_pos)
+            .unwrap_or(self.input.len())
+    }
+
+    // Helper: get character count
+    fn char_count(&self) -> usize {
+        self.input.chars().count()
+    }
+
+    fn move_cursor_left(&mut self) {
+        if self.cursor_position > 0 {
+            self.cursor_position -= 1;
+        }
+    }
+
+    fn move_cursor_right(&mut self) {
+        if self.cursor_position < self.char_count() {
+            self.cursor_position += 1;
+        }
+    }
+
+    fn move_cursor_start(&mut self) {
+        self.cursor_position = 0;
+    }
+
+    fn move_cursor_end(&mut self) {
+        self.cursor_position = self.char_count();
+    }
+
+    fn delete_char(&mut self) {
+        let char_count = self.char_count();
+        if self.cursor_position < char_count {
+            let byte_pos = self.char_to_byte_pos(self.cursor_position);
+            self.input.remove(byte_pos);
+        }
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor_position > 0 {
+            self.cursor_position -= 1;
+            let byte_pos = self.char_to_byte_pos(self.cursor_position);
+            self.input.remove(byte_pos);
+        }
+    }
+
+    fn insert_char(&mut self, c: char) {
+        let byte_pos = self.char_to_byte_pos(self.cursor_position);
+        self.input.insert(byte_pos, c);
+        self.cursor_position += 1;
+    }
+
+    fn move_to_next_word(&mut self) {
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut pos = self.cursor_position;
+        
+       
+        while pos < chars.len() && !chars[pos].is_whitespace() {
+            pos += 1;
+        }
+       
+        while pos < chars.len() && chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        
+        self.cursor_position = pos;
+    }
+
+    fn move_to_prev_word(&mut self) {
+        if self.cursor_position == 0 {
+            return;
+        }
+        
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut pos = self.cursor_position.saturating_sub(1);
+        
+      
+        while pos > 0 && chars[pos].is_whitespace() {
+            pos -= 1;
+        }
+      
+        while pos > 0 && !chars[pos - 1].is_whitespace() {
+            pos -= 1;
+        }
+        
+        self.cursor_position = pos;
+    }
+
+    fn select_next_suggestion(&mut self) {
+        if !self.autocomplete_suggestions.is_empty() {
+            self.selected_suggestion = 
+                (self.selected_suggestion + 1) % self.autocomplete_suggestions.len();
+        }
+    }
+
+    fn select_prev_suggestion(&mut self) {
+        if !self.autocomplete_suggestions.is_empty() {
+            if self.selected_suggestion == 0 {
+                self.selected_suggestion = self.autocomplete_suggestions.len() - 1;
+            } else {
+                self.selected_suggestion -= 1;
+            }
+        }
+    }
+
+    fn search_query(&self, input: &str) -> SearchQuery {
+        SearchQuery::parse(input, self.config.default_country.as_deref())
+    }
+
+    // A favorite's own units win over the configured ones
+    fn units_for(&self, query: &str) -> Units {
+        self.search_history
+            .iter()
+            .find(|e| e.query.eq_ignore_ascii_case(query.trim()))
+            .and_then(|e| e.units)
+            .unwrap_or(self.config.units)
+    }
+
+    // The part of the input that names the place, without a country suffix or filter
+    pub fn autocomplete_query(&self) -> String {
+        let query = self.search_query(&self.input);
+        query.name.split(',').next().unwrap_or("").trim().to_string()
+    }
+
+    // The rest of the selected suggestion, shown dimmed after the cursor while typing at the
+    // end of a name it starts with; Right or End takes it
+    pub fn ghost_text(&self) -> Option<String> {
+        if self.mode != Mode::Insert || !self.show_autocomplete || self.cursor_position != self.char_count() {
+            return None;
+        }
+        let suggestion = self.autocomplete_suggestions.get(self.selected_suggestion)?;
+        let query = self.autocomplete_query();
+        // Nothing to complete once a country or a comma follows the name
+        if query.is_empty() || self.input.trim_start() != query {
+            return None;
+        }
+        let typed = query.chars().count();
+        let prefix: String = suggestion.name.chars().take(typed).collect();
+        // Compared folded so "zur" still completes to Zürich
+        if fuzzy::normalize(&prefix) != fuzzy::normalize(&query) {
+            return None;
+        }
+        let rest: String = suggestion.name.chars().skip(typed).collect();
+        Some(format!("{}, {}", rest, suggestion.country))
+    }
+
+    // Re-rank the last fetched candidates against the current input without waiting
+    // for a new geocoding round trip
+    fn refilter_suggestions(&mut self) {
+        // Accent-folded, so typing "zurich" keeps Zürich in the list
+        self.autocomplete_suggestions =
+            ranking::rank(&self.autocomplete_candidates, &self.autocomplete_query(), &self.search_history);
+        self.autocomplete_regions = suggestion_regions(&self.autocomplete_suggestions);
+        self.show_autocomplete = !self.autocomplete_suggestions.is_empty();
+        if self.selected_suggestion >= self.autocomplete_suggestions.len() {
+            self.selected_suggestion = 0;
+        }
+    }
+
+    fn accept_suggestion_number(&mut self, number: usize) {
+        if number >= 1 && number <= self.autocomplete_suggestions.len() {
+            self.selected_suggestion = number - 1;
+            self.accept_suggestion();
+        }
+    }
+
+    fn accept_suggestion(&mut self) {
+        if self.show_autocomplete && !self.autocomplete_suggestions.is_empty() {
+            let suggestion = self.autocomplete_suggestions[self.selected_suggestion].clone();
+            self.input = format!("{}, {}", suggestion.name, suggestion.country);
+            self.accepted_suggestion = Some((self.input.clone(), suggestion));
+            self.cursor_position = self.char_count();
+            self.show_autocomplete = false;
+            self.autocomplete_suggestions.clear();
+            self.autocomplete_regions.clear();
+            self.autocomplete_candidates.clear();
+        }
+    }
+
+    // With an empty search box in Insert mode the last few searches double as suggestions
+    pub fn showing_recent(&self) -> bool {
+        self.mode == Mode::Insert
+            && self.focused_pane == Pane::Search
+            && self.input.is_empty()
+            && !self.search_history.is_empty()
+    }
+
+    pub fn recent_count(&self) -> usize {
+        self.search_history.len().min(5)
+    }
+
+    fn select_next_recent(&mut self) {
+        if self.recent_count() > 0 {
+            self.selected_recent = (self.selected_recent + 1) % self.recent_count();
+        }
+    }
+
+    fn select_prev_recent(&mut self) {
+        if self.recent_count() > 0 {
+            if self.selected_recent == 0 {
+                self.selected_recent = self.recent_count() - 1;
+            } else {
+                self.selected_recent -= 1;
+            }
+        }
+    }
+
+    fn accept_recent(&mut self) {
+        if let Some(entry) = self.search_history.get(self.selected_recent) {
+            self.input = entry.query.clone();
+            self.cursor_position = self.char_count();
+            self.selected_recent = 0;
+        }
+    }
+
+    fn select_next_history(&mut self) {
+        if !self.search_history.is_empty() {
+            self.selected_history_index = 
+                (self.selected_history_index + 1) % self.search_history.len();
+        }
+    }
+
+    fn select_prev_history(&mut self) {
+        if !self.search_history.is_empty() {
+            if self.selected_history_index == 0 {
+                self.selected_history_index = self.search_history.len() - 1;
+            } else {
+                self.selected_history_index -= 1;
+            }
+        }
+    }
+
+    // Close spellings from history first, since those are places the user actually goes, then the gazetteer
+    fn corrections_for(&self, city: &str) -> Vec<Correction> {
+        const LIMIT: usize = 5;
+        let query = self.search_query(city);
+        let needle = fuzzy::normalize(&query.name);
+        let allowance = fuzzy::typo_allowance(needle.chars().count());
+
+        let mut corrections: Vec<Correction> = self
+            .search_history
+            .iter()
+            .filter(|e| {
+                let name = e.location.as_ref().map_or(e.query.as_str(), |l| l.name.as_str());
+                let distance = fuzzy::edit_distance(&fuzzy::normalize(name), &needle);
+                distance > 0 && distance <= allowance
+            })
+            .map(|e| Correction { query: e.query.clone(), location: e.location.clone() })
+            .collect();
+        if let Some(gazetteer) = &self.gazetteer {
+            for location in gazetteer.near_misses(&query, LIMIT) {
+                let known = corrections.iter().any(|c| {
+                    c.location.as_ref().is_some_and(|l| l.name == location.name && l.country == location.country)
+                });
+                if !known {
+                    corrections.push(Correction { query: format!("{}, {}", location.name, location.country), location: Some(location) });
+                }
+            }
+        }
+        corrections.truncate(LIMIT);
+        corrections
+    }
+
+    fn try_correction(&mut self, number: usize) {
+        let Some(correction) = number.checked_sub(1).and_then(|i| self.corrections.get(i)) else {
+            return;
+        };
+        let query = correction.query.clone();
+        if let Some(location) = correction.location.clone() {
+            self.accepted_suggestion = Some((query.clone(), location));
+        }
+        self.corrections.clear();
+        self.start_search(query);
+    }
+
+    fn load_selected_history(&mut self) {
+        if !self.search_history.is_empty() && self.selected_history_index < self.search_history.len() {
+            self.input = self.search_history[self.selected_history_index].query.clone();
+            self.cursor_position = self.char_count();
+            self.focused_pane = Pane::Search;
+            self.mode = Mode::Insert;
+        }
+    }
+
+    fn cached_location(&self, query: &str) -> Option<GeoLocation> {
+        if let Some((text, location)) = &self.accepted_suggestion {
+            if text == query {
+                return Some(location.clone());
+            }
+        }
+
+        let search = self.search_query(query);
+        self.search_history
+            .iter()
+            .find(|e| e.query.eq_ignore_ascii_case(query.trim()))
+            .and_then(|e| e.location.clone())
+            .or_else(|| {
+                // A bare name that was picked before, so the same one of several comes back
+                if search.country_code.is_some() || search.coordinates.is_some() {
+                    return None;
+                }
+                ranking::previous_choice(&search.name, &self.search_history)
+            })
+            .or_else(|| {
+                // Typed coordinates get named from the local city list when it has something close
+                let (latitude, longitude) = search.coordinates?;
+                self.gazetteer.as_ref()?.nearest(latitude, longitude, gazetteer::NEAREST_PLACE_KM)
+            })
+    }
+
+    fn toggle_history_mark(&mut self) {
+        if let Some(entry) = self.search_history.get(self.selected_history_index) {
+            if !self.marked_history.remove(&entry.query) {
+                self.marked_history.insert(entry.query.clone());
+            }
+            // On to the next one, so a run of entries is Space, Space, Space
+            if self.selected_history_index + 1 < self.search_history.len() {
+                self.selected_history_index += 1;
+            }
+        }
+    }
+
+    fn pick_batch_action(&mut self) {
+        let count = self.marked_history.len();
+        let marked = || self.search_history.iter().filter(|e| self.marked_history.contains(&e.query));
+        let favorite = if marked().all(|e| e.favorite) { "Remove from favorites" } else { "Add to favorites" };
+        let items = vec![format!("Delete {}", count), format!("{} {}", favorite, count), format!("Open all {} on a dashboard", count)];
+        self.popup = Some(Popup::picker(&format!("{} marked in history", count), items, PopupAction::MarkedHistory));
+    }
+
+    fn delete_marked_history(&mut self) {
+        if self.refuse_read_only("delete history entries") {
+            return;
+        }
+        let before = self.search_history.len();
+        self.search_history.retain(|e| !self.marked_history.contains(&e.query));
+        self.show_toast(format!("Removed {} from history", before - self.search_history.len()));
+        self.selected_history_index = self.selected_history_index.min(self.search_history.len().saturating_sub(1));
+        if !self.focused_pane.can_focus(self) {
+            self.focused_pane = Pane::Search;
+        }
+        self.save_history();
+    }
+
+    // Marks them all as favorites, or if they already all are, none of them
+    fn favorite_marked_history(&mut self) {
+        if self.refuse_read_only("change favorites") {
+            return;
+        }
+        let mut marked: Vec<&mut HistoryEntry> =
+            self.search_history.iter_mut().filter(|e| self.marked_history.contains(&e.query)).collect();
+        let favorite = !marked.iter().all(|e| e.favorite);
+        for entry in marked.iter_mut() {
+            entry.favorite = favorite;
+        }
+        let count = marked.len();
+        self.show_toast(if favorite { format!("Added {} to favorites", count) } else { format!("Removed {} from favorites", count) });
+        self.save_history();
+    }
+
+    // The kiosk dashboard, one tile per marked place in history order, until Esc
+    fn open_marked_on_dashboard(&mut self) {
+        self.kiosk = true;
+        self.kiosk_from_history = true;
+        self.kiosk_tiles = self
+            .search_history
+            .iter()
+            .filter(|e| self.marked_history.contains(&e.query))
+            .map(|e| KioskTile { query: e.query.clone(), data: None, error: None, pending: false, text: OnceCell::new() })
+            .collect();
+        self.refresh_kiosk();
+    }
+
+    fn close_dashboard(&mut self) {
+        self.kiosk = false;
+        self.kiosk_from_history = false;
+        self.kiosk_tiles.clear();
+        let dashboard: Vec<String> = self.in_flight.iter().filter(|&k| self.refresh_key.as_ref() != Some(k)).cloned().collect();
+        self.abandoned.extend(dashboard);
+        self.refresh_countdown = None;
+        if self.state == AppState::Display {
+            self.schedule_refresh();
+        }
+    }
+
+    fn confirm_delete_history(&mut self) {
+        if self.refuse_read_only("delete history entries") {
+            return;
+        }
+        if let Some(entry) = self.search_history.get(self.selected_history_index) {
+            self.popup = Some(Popup::confirm(
+                "Delete history entry",
+                format!("Remove '{}' from history?", entry.query),
+                PopupAction::DeleteHistoryEntry(self.selected_history_index),
+            ));
+        }
+    }
+
+    fn pick_recent(&mut self) {
+        if !self.search_history.is_empty() {
+            let items = self.search_history.iter().map(|e| e.query.clone()).collect();
+            self.popup = Some(Popup::picker(
+                "Recent locations (Enter to load, ESC to cancel)",
+                items,
+                PopupAction::SearchHistoryEntry,
+            ));
+        }
+    }
+
+    // The configured command, or the OpenStreetMap page with a marker on the place
+    fn map_command(&self, location: &GeoLocation) -> Result<String, String> {
+        let (lat, lon) = (format!("{:.5}", location.latitude), format!("{:.5}", location.longitude));
+        let values = [("lat", lat.as_str()), ("lon", lon.as_str())];
+        match &self.config.map_command {
+            Some(command) => template::format(command, &values).map_err(|e| format!("map_command: {}", e)),
+            None => Ok(plugins::opener(&template::format(OPENSTREETMAP, &values)?)),
+        }
+    }
+
+    // The provider's own page for the place, for the detail the TUI leaves out
+    fn forecast_page_command(&self, location: &GeoLocation) -> Result<String, String> {
+        let (lat, lon) = (format!("{:.5}", location.latitude), format!("{:.5}", location.longitude));
+        let name = urlencoding::encode(&location.name);
+        let values = [("lat", lat.as_str()), ("lon", lon.as_str()), ("name", &name)];
+        let page = self.config.forecast_page.as_deref().unwrap_or(OPEN_METEO_PAGE);
+        let url = template::format(page, &values).map_err(|e| format!("forecast_page: {}", e))?;
+        Ok(plugins::opener(&url))
+    }
+
+    fn pick_airport(&mut self) {
+        match self.config.airports.len() {
+            0 => self.show_toast(
+                "Add airports = [{ icao = \"EDDB\", runway_heading = 250 }] to config.toml".to_string(),
+            ),
+            1 => self.fetch_brief(0),
+            _ => {
+                let items = self.config.airports.iter().map(|a| a.icao.clone()).collect();
+                self.popup = Some(Popup::picker(
+                    "Pre-flight brief (Enter to fetch, ESC to cancel)",
+                    items,
+                    PopupAction::FetchBrief,
+                ));
+            }
+        }
+    }
+
+    fn pick_chart(&mut self) {
+        if self.state == AppState::Display {
+            let items = CHARTS.iter().map(|(label, _)| label.to_string()).collect();
+            self.popup = Some(Popup::picker("Charts (Enter to open, ESC to cancel)", items, PopupAction::OpenChart));
+        }
+    }
+
+    fn fetch_brief(&mut self, index: usize) {
+        if let Some(airport) = self.config.airports.get(index).cloned() {
+            self.brief_runway = airport.runway_heading;
+            self.show_toast(format!("Fetching the brief for {}...", airport.icao));
+            self.commands.push(Command::FetchBrief { icao: airport.icao });
+        }
+    }
+
+    fn show_help(&mut self) {
+        self.popup = Some(Popup::help("Help (j/k to scroll, ESC to close)", help_lines()));
+    }
+
+    fn show_stats(&mut self) {
+        if self.search_history.is_empty() {
+            self.show_toast("No searches yet, so no statistics either".to_string());
+            return;
+        }
+        let now = chrono::Local::now().timestamp().max(0) as u64;
+        let lines = stats_lines(&history::stats(&self.search_history, now), now);
+        self.popup = Some(Popup::help("Your searches (ESC to close)", lines));
+    }
+
+    fn handle_command_line_key(&mut self, key: KeyEvent) {
+        let Some(line) = self.command_line.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.command_line = None,
+            KeyCode::Enter => {
+                let line = self.command_line.take().unwrap_or_default();
+                self.run_command_line(&line);
+            }
+            // Backspacing past the ':' closes it, like vim
+            KeyCode::Backspace if line.is_empty() => self.command_line = None,
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            KeyCode::Char(c) => line.push(c),
+            _ => {}
+        }
+    }
+
+    fn run_command_line(&mut self, line: &str) {
+        match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [] => {}
+            ["home"] => self.go_home(),
+            ["home", "set"] => self.set_home(),
+            ["bugreport"] => self.commands.push(Command::WriteBugReport { context: format!("{:?}", self.state) }),
+            ["stats"] => self.show_stats(),
+            ["units", ref words @ ..] => self.set_units(words),
+            ["export"] => self.show_toast("Export to which file? e.g. :export report.md (or .org)".to_string()),
+            ["export", ..] => self.export_report(line.trim().trim_start_matches("export").trim()),
+            _ => self.show_toast(format!("Unknown command :{} (try :home, :home set, :stats, :units, :export or :bugreport)", line.trim())),
+        }
+    }
+
+    fn go_home(&mut self) {
+        let Some(home) = self.home.clone() else {
+            let message = if self.config.home.is_some() {
+                "Still looking up your home, try again in a moment"
+            } else {
+                "No home yet: show a place and type :home set, or add home = \"...\" to config.toml"
+            };
+            self.show_toast(message.to_string());
+            return;
+        };
+        let city = if home.country.is_empty() { home.name.clone() } else { format!("{}, {}", home.name, home.country) };
+        // Goes through the accepted-suggestion path so the exact place is fetched, not a fresh lookup
+        self.accepted_suggestion = Some((city.clone(), home));
+        self.popup = None;
+        self.start_search(city);
+    }
+
+    fn set_home(&mut self) {
+        if self.refuse_read_only("change home") {
+            return;
+        }
+        let Some(data) = self.weather_data.as_ref().filter(|_| self.state == AppState::Display) else {
+            self.show_toast("Show a place's weather first, then :home set".to_string());
+            return;
+        };
+        let location = data.location.clone();
+        self.show_toast(format!("Home is now {}", location.label()));
+        self.commands.push(Command::SaveHome(location.clone()));
+        self.home = Some(location);
+    }
+
+    // `:units fahrenheit` keeps this place in °F from now on, as a favorite so it doesn't fall out of
+    // the history; `:units` on its own puts it back to the configured ones
+    fn set_units(&mut self, words: &[&str]) {
+        let Some(city) = self.current_query.clone().filter(|_| self.state == AppState::Display) else {
+            self.show_toast("Show a place's weather first, then e.g. :units fahrenheit mph".to_string());
+            return;
+        };
+        let units = match words {
+            [] => None,
+            _ => match self.units_for(&city).with(words) {
+                Ok(units) => Some(units),
+                Err(e) => return self.show_toast(e),
+            },
+        };
+        let known = self.search_history.iter().any(|e| e.query.eq_ignore_ascii_case(city.trim()));
+        // Dropped off the end of the history since it was shown: it comes back as the favorite it's about to become
+        if let (false, Some(_), Some(data)) = (known, units, &self.weather_data) {
+            let entry = HistoryEntry::new(city.clone(), data.fetched_at, Some(data.location.clone()));
+            history::push_entry(&mut self.search_history, entry);
+        }
+        if let Some(entry) = self.search_history.iter_mut().find(|e| e.query.eq_ignore_ascii_case(city.trim())) {
+            entry.units = units;
+            entry.favorite |= units.is_some();
+        }
+        let shown = units.unwrap_or(self.config.units);
+        if let Some(data) = &mut self.weather_data {
+            data.units = shown;
+        }
+        let (temperature, wind_speed) = (shown.temperature_unit(), shown.wind_speed_unit());
+        let message = match units {
+            Some(_) if self.read_only => format!("{} is in {} and {} until you quit (the history is read-only)", city, temperature, wind_speed),
+            Some(_) => format!("{} is in {} and {} from now on (and a favorite)", city, temperature, wind_speed),
+            None => format!("{} is back to {} and {}", city, temperature, wind_speed),
+        };
+        self.show_toast(message);
+        self.save_history();
+    }
+
+    // The week ahead isn't part of the usual fetch, so it's asked for first and the report is written when it
+    // lands. An HTML snapshot is just the screen
+    fn export_report(&mut self, path: &str) {
+        let Some(data) = self.weather_data.as_ref().filter(|_| self.state == AppState::Display) else {
+            self.show_toast("Show a place's weather first, then :export".to_string());
+            return;
+        };
+        let path = match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+            (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(path),
+        };
+        let html = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("html") || e.eq_ignore_ascii_case("htm"));
+        if html {
+            self.commands.push(Command::WriteSnapshot { path, title: format!("Weather in {}", data.location.label()) });
+        } else {
+            self.commands.push(Command::FetchReport { path, location: data.location.clone() });
+        }
+    }
+
+    fn handle_popup_key(&mut self, key: KeyEvent) {
+        let Some(popup) = self.popup.as_mut() else {
+            return;
+        };
+        match popup.handle_key(key) {
+            PopupOutcome::Consumed => {}
+            PopupOutcome::Closed => self.popup = None,
+            PopupOutcome::Confirmed(action) => {
+                self.popup = None;
+                self.run_popup_action(action, None);
+            }
+            PopupOutcome::Picked(action, index) => {
+                self.popup = None;
+                self.run_popup_action(action, Some(index));
+            }
+        }
+    }
+
+    fn run_popup_action(&mut self, action: PopupAction, picked: Option<usize>) {
+        match action {
+            PopupAction::DeleteHistoryEntry(index) => {
+                if index < self.search_history.len() {
+                    let entry = self.search_history.remove(index);
+                    self.show_toast(format!("Removed '{}' from history", entry.query));
+                    if self.selected_history_index >= self.search_history.len() {
+                        self.selected_history_index = self.search_history.len().saturating_sub(1);
+                    }
+                    if !self.focused_pane.can_focus(self) {
+                        self.focused_pane = Pane::Search;
+                    }
+                    self.save_history();
+                }
+            }
+            PopupAction::MarkedHistory => {
+                match picked {
+                    Some(0) => self.delete_marked_history(),
+                    Some(1) => self.favorite_marked_history(),
+                    Some(2) => self.open_marked_on_dashboard(),
+                    _ => return,
+                }
+                self.marked_history.clear();
+            }
+            PopupAction::SearchHistoryEntry => {
+                if let Some(entry) = picked.and_then(|i| self.search_history.get(i)) {
+                    let city = entry.query.clone();
+                    self.start_search(city);
+                }
+            }
+            PopupAction::FetchBrief => {
+                if let Some(i) = picked {
+                    self.fetch_brief(i);
+                }
+            }
+            PopupAction::OpenChart => {
+                if let Some((_, action)) = picked.and_then(|i| CHARTS.get(i)) {
+                    self.handle_action(*action);
+                }
+            }
+            PopupAction::Palette(entries) => match picked.and_then(|i| entries.get(i)) {
+                Some(PaletteEntry::Run(action)) => self.handle_action(*action),
+                Some(PaletteEntry::Search(query)) => self.start_search(query.clone()),
+                None => {}
+            },
+        }
+    }
+
+    // Whatever makes sense from here, then every saved location, all in one filterable list
+    fn open_palette(&mut self) {
+        let mut commands = vec![("New search", "i", Action::NewSearch), ("Weather at home", "H", Action::GoHome)];
+        if self.state == AppState::Display {
+            commands.extend([
+                ("Make this place home", ":home set", Action::SetHome),
+                ("Toggle the big temperature", "B", Action::ToggleBigTemperature),
+                ("Toggle rain and snow effects", "E", Action::ToggleEffects),
+                ("Trends", "T", Action::ShowTrends),
+                ("Forecast accuracy", "A", Action::ShowAccuracy),
+                ("Tropical cyclones", "C", Action::ShowCyclones),
+                ("Mountain forecast", "M", Action::ShowMountain),
+                ("Free-flight wind profile", "F", Action::ShowFreeFlight),
+                ("Marine: tides and sea temperature", "W", Action::ShowMarine),
+                ("Barograph: pressure and storm warnings", "G", Action::ShowBarograph),
+                ("Dew point: how muggy it will feel", "", Action::ShowDewPoint),
+                ("Charts", "V", Action::PickChart),
+                ("Nearby: how much the forecast varies", "N", Action::ShowNearby),
+                ("Pre-flight brief", "P", Action::PickAirport),
+                ("Event log", "L", Action::ShowEvents),
+                ("Open on a map", "o", Action::OpenMap),
+                ("Forecast page in a browser", "O", Action::OpenForecastPage),
+            ]);
+        }
+        commands.extend([("Search statistics", ":stats", Action::ShowStats), ("Help", "?", Action::ShowHelp), ("Quit", "q", Action::Quit)]);
+
+        let mut labels = Vec::new();
+        let mut entries = Vec::new();
+        for (label, key, action) in commands {
+            labels.push(format!("{:<32}{}", label, key));
+            entries.push(PaletteEntry::Run(action));
+        }
+        for entry in &self.search_history {
+            labels.push(format!("{:<32}recent", entry.query));
+            entries.push(PaletteEntry::Search(entry.query.clone()));
+        }
+        self.popup = Some(Popup::palette(
+            "Go to (type to filter, Enter to run, ESC to cancel)",
+            labels,
+            PopupAction::Palette(entries),
+        ));
+    }
+
+    // False when there's no gazetteer or it doesn't know the place, so the geocoder gets asked
+    fn local_autocomplete(&mut self) -> bool {
+        let mut query = self.search_query(&self.input);
+        query.name = self.autocomplete_query();
+        if query.coordinates.is_some() {
+            return false;
+        }
+        let local = self.gazetteer.as_ref().map(|g| g.search(&query, 10)).unwrap_or_default();
+        if local.is_empty() {
+            return false;
+        }
+        self.last_autocomplete_query = self.input.clone();
+        self.autocomplete_candidates = local;
+        self.refilter_suggestions();
+        true
+    }
+
+    fn request_autocomplete(&mut self) {
+        if self.input.len() >= 3 && self.input != self.last_autocomplete_query {
+            if self.local_autocomplete() || self.search_query(&self.input).coordinates.is_some() {
+                return;
+            }
+            self.last_autocomplete_query = self.input.clone();
+            self.commands.push(Command::FetchAutocomplete {
+                input: self.input.clone(),
+                query: self.search_query(&self.input),
+            });
+        }
+    }
+
+    // Full-screen dashboard of the configured locations, falling back to the favorites, then the last search
+    pub fn enter_kiosk(&mut self) {
+        let mut queries = self.config.kiosk_locations.clone();
+        if queries.is_empty() {
+            queries.extend(self.search_history.iter().filter(|e| e.favorite).map(|e| e.query.clone()));
+        }
+        if queries.is_empty() {
+            queries.extend(self.search_history.first().map(|e| e.query.clone()));
+        }
+
+        self.kiosk = true;
+        self.kiosk_tiles = queries
+            .into_iter()
+            .map(|query| KioskTile { query, data: None, error: None, pending: false, text: OnceCell::new() })
+            .collect();
+    }
+
+    // Commands to run before the first key press
+    pub fn start(&mut self) -> Vec<Command> {
+        if self.kiosk {
+            self.refresh_kiosk();
+        } else if let Some(city) = self.open_with.take() {
+            self.start_search(city);
+        }
+        if self.config.check_for_updates {
+            self.commands.push(Command::CheckForUpdate);
+        }
+        if let (None, Some(home)) = (&self.home, &self.config.home) {
+            let query = self.search_query(home);
+            match query.coordinates {
+                Some((latitude, longitude)) => {
+                    self.home = Some(GeoLocation {
+                        name: "Home".to_string(),
+                        latitude,
+                        longitude,
+                        country: String::new(),
+                        admin1: None,
+                        admin2: None,
+                        admin3: None,
+                        near_km: None,
+                    })
+                }
+                None => self.commands.push(Command::ResolveHome(query)),
+            }
+        }
+        std::mem::take(&mut self.commands)
+    }
+
+    fn refresh_kiosk(&mut self) {
+        self.refresh_countdown = None;
+        for i in 0..self.kiosk_tiles.len() {
+            let tile = &self.kiosk_tiles[i];
+            let city = tile.query.clone();
+            let cached = tile
+                .data
+                .as_ref()
+                .map(|d| d.location.clone())
+                .or_else(|| self.cached_location(&city));
+            let query = self.search_query(&city);
+            let key = fetch_key(&city, cached.as_ref());
+            let units = self.units_for(&city);
+            if self.in_flight.insert(key.clone()) {
+                self.commands.push(Command::FetchWeather { key, city, query, cached, units });
+            }
+            self.kiosk_tiles[i].pending = true;
+        }
+    }
+
+    fn apply_kiosk_result(&mut self, city: &str, result: Result<Box<WeatherData>, FetchError>) {
+        if let Ok(data) = &result {
+            self.check_rules(data);
+        }
+        let mut event = None;
+        if let Some(tile) = self.kiosk_tiles.iter_mut().find(|t| t.query == city) {
+            tile.pending = false;
+            let was_severe = tile.data.as_ref().and_then(|d| api::severe_weather(d.weather.current.weather_code));
+            match result {
+                Ok(data) => {
+                    let severe = api::severe_weather(data.weather.current.weather_code);
+                    if let (None, Some(severe)) = (was_severe, severe) {
+                        let message = format!("Severe weather in {}: {}. {}", data.location.name, severe, summary::sentence(&data.weather, data.units));
+                        event = Some((EventKind::Alert, message));
+                    }
+                    tile.data = Some(*data);
+                    tile.text = OnceCell::new();
+                    tile.error = None;
+                }
+                // Keep showing the last good reading next to the error
+                Err(e) => {
+                    // Only the first failure in a row is worth logging
+                    if tile.error.is_none() {
+                        event = Some((EventKind::RefreshFailed, format!("Refresh of {} failed: {}", city, e)));
+                    }
+                    tile.error = Some(e.to_string());
+                }
+            }
+        }
+        if let Some((kind, message)) = event {
+            let ring = kind == EventKind::Alert && self.config.severe_bell;
+            self.notify(kind, message, ring);
+        }
+        if self.in_flight.is_empty() {
+            self.schedule_refresh();
+        }
+    }
+
+    pub fn update(&mut self, msg: Msg) -> Vec<Command> {
+        // Mouse movement only redraws when something is hovering over it
+        if !matches!(msg, Msg::Tick | Msg::Mouse(_)) {
+            self.dirty = true;
+        }
+
+        match msg {
+            Msg::Key(key) => {
+                if self.kiosk {
+                    if key.code == KeyCode::Char('q') {
+                        self.commands.push(Command::Quit);
+                    } else if key.code == KeyCode::Esc && self.kiosk_from_history {
+                        self.close_dashboard();
+                    }
+                } else if self.popup.is_some() {
+                    self.handle_popup_key(key);
+                } else if self.command_line.is_some() {
+                    self.handle_command_line_key(key);
+                } else if key.code == KeyCode::Esc && self.notice.is_some() {
+                    self.notice = None;
+                } else if let Some(action) = action::map_key(&self.state, &self.mode, key) {
+                    // On the search screen the focused pane gets first pick
+                    let pane = self.focused_pane;
+                    let action = match self.state {
+                        AppState::Input => pane.handle_action(self, action),
+                        _ => Some(action),
+                    };
+                    if let Some(action) = action {
+                        self.handle_action(action);
+                    }
+                }
+            }
+            Msg::AutocompleteResults(query, suggestions) => {
+                if self.input == query {
+                    self.autocomplete_candidates = suggestions;
+                    self.selected_suggestion = 0;
+                    self.refilter_suggestions();
+                }
+            }
+            Msg::WeatherResults(key, ..) if self.abandoned.remove(&key) => {
+                self.in_flight.remove(&key);
+            }
+            Msg::WeatherResults(key, city, result) if self.kiosk => {
+                self.in_flight.remove(&key);
+                self.record(&result);
+                self.run_fetch_hooks(&city, &result);
+                self.apply_kiosk_result(&city, result);
+            }
+            Msg::WeatherResults(key, city, result) => {
+                self.in_flight.remove(&key);
+                self.record(&result);
+                self.run_fetch_hooks(&city, &result);
+                let background = self.refresh_key.as_ref() == Some(&key);
+                if background {
+                    self.refresh_key = None;
+                }
+
+                match result {
+                    Ok(data) if background => {
+                        self.check_rules(&data);
+                        let was_severe = self.severe_weather().is_some();
+                        let previous = self.weather_data.as_ref().and_then(|d| d.weather.hourly.as_ref());
+                        if let (Some(old), Some(new)) = (previous, &data.weather.hourly) {
+                            self.forecast_diff = forecast::diff(old, new, &data.weather.current.time);
+                            if let Some(summary) = self.forecast_diff.summary.clone() {
+                                self.notify(EventKind::ForecastChange, summary, false);
+                            }
+                        }
+                        self.weather_data = Some(*data);
+                        self.alert_if_severe(was_severe);
+                        self.fetch_hazards();
+                        self.run_plugins();
+                        self.schedule_refresh();
+                    }
+                    Err(e) if background => {
+                        self.notify(EventKind::RefreshFailed, format!("Refresh failed: {}", e), false);
+                        self.schedule_refresh();
+                    }
+                    Ok(data) => {
+                        self.forecast_diff = ForecastDiff::default();
+                        self.earthquakes = None;
+                        self.plugin_panels.clear();
+                        self.check_rules(&data);
+                        self.add_to_history(city.clone(), data.location.clone(), data.fetched_at);
+                        self.weather_data = Some(*data);
+                        self.current_query = Some(city);
+                        self.state = AppState::Display;
+                        self.input.clear();
+                        self.cursor_position = 0;
+                        self.mode = Mode::Normal;
+                        self.alert_if_severe(false);
+                        self.fetch_hazards();
+                        self.run_plugins();
+                        self.schedule_refresh();
+                    }
+                    Err(e) => {
+                        self.corrections = match e {
+                            FetchError::NotFound(_) => self.corrections_for(&city),
+                            FetchError::Failed(_) => Vec::new(),
+                        };
+                        self.error_message = e.to_string();
+                        self.state = AppState::Error;
+                    }
+                }
+            }
+            Msg::HomeResolved(Ok(location)) => self.home = Some(location),
+            Msg::HomeResolved(Err(e)) => self.show_toast(format!("Couldn't find your home: {}", e)),
+            Msg::TrendsLoaded(Ok((now, points))) => {
+                if self.state == AppState::Display {
+                    self.trends = Some(Trends { now, hours: 24, offset: 0, points, cursor: None, pointer: None });
+                    self.state = AppState::Trends;
+                }
+            }
+            Msg::ForecastChecksLoaded(Ok(checks)) => {
+                if self.state == AppState::Display {
+                    self.forecast_checks = checks;
+                    self.state = AppState::Accuracy;
+                }
+            }
+            Msg::CyclonesLoaded(Ok(mut cyclones)) => {
+                if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    let (lat, lon) = (data.location.latitude, data.location.longitude);
+                    let distance = |c: &Cyclone| geo::distance_km(lat, lon, c.latitude_numeric, c.longitude_numeric);
+                    cyclones.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+                    self.cyclones = cyclones;
+                    self.selected_cyclone = 0;
+                    self.state = AppState::Cyclones;
+                }
+            }
+            Msg::CyclonesLoaded(Err(e)) => self.show_toast(e),
+            Msg::DeliveryFailed(e) => self.notify(EventKind::DeliveryFailed, e, false),
+            Msg::BugReportWritten(Ok(path)) => {
+                self.show_toast(format!("Bug report saved to {}; have a look before attaching it", path.display()))
+            }
+            Msg::BugReportWritten(Err(e)) => self.show_toast(e),
+            Msg::ReleaseChecked(Ok(release)) => {
+                let current = env!("CARGO_PKG_VERSION");
+                if newer(&release.version, current) {
+                    // Logged too, so it turns up along the bottom of the kiosk, which shows no toasts; once
+                    // per release, not every time the app starts until it's installed
+                    let message = format!("weather-tui {} is out (this is {}): {}", release.version, current, release.url);
+                    let logged = self.events.iter().any(|e| e.kind == EventKind::UpdateAvailable && e.message == message);
+                    if !logged {
+                        self.log_event(EventKind::UpdateAvailable, message.clone());
+                    }
+                    self.notice = Some(format!("{} - Esc to dismiss", message));
+                }
+            }
+            // Nobody asked for this just now, so a failed check isn't worth interrupting for
+            Msg::ReleaseChecked(Err(_)) => {}
+            Msg::HookFailed(e) => self.log_event(EventKind::DeliveryFailed, e),
+            Msg::LaunchFailed(e) => self.show_toast(e),
+            Msg::MountainLoaded(Ok(mountain)) => {
+                if self.state == AppState::Display {
+                    self.mountain = Some(*mountain);
+                    self.state = AppState::Mountain;
+                }
+            }
+            Msg::PressureLoaded(Ok(pressure)) => {
+                if self.state == AppState::Display {
+                    self.barograph = Some(pressure);
+                    self.state = AppState::Barograph;
+                }
+            }
+            Msg::FreeFlightLoaded(Ok(free_flight)) => {
+                if self.state == AppState::Display {
+                    self.free_flight = Some(*free_flight);
+                    self.state = AppState::FreeFlight;
+                }
+            }
+            Msg::TidesLoaded(result) => {
+                if matches!(self.state, AppState::Display | AppState::Marine) {
+                    self.tides = Some(result.map(|tides| *tides));
+                    self.open_marine();
+                }
+            }
+            Msg::SeaTemperatureLoaded(result) => {
+                if matches!(self.state, AppState::Display | AppState::Marine) {
+                    self.sea_temperature = Some(result);
+                    self.open_marine();
+                }
+            }
+            Msg::NearbyLoaded(Ok(nearby)) => {
+                if self.state == AppState::Display {
+                    self.nearby = nearby;
+                    self.state = AppState::Nearby;
+                }
+            }
+            Msg::MountainLoaded(Err(e)) | Msg::FreeFlightLoaded(Err(e)) | Msg::NearbyLoaded(Err(e)) | Msg::PressureLoaded(Err(e)) => {
+                self.show_toast(e)
+            }
+            Msg::BriefLoaded(Ok(brief)) => {
+                if matches!(self.state, AppState::Display | AppState::Error) {
+                    self.brief = Some(*brief);
+                    self.state = AppState::Brief;
+                }
+            }
+            Msg::BriefLoaded(Err(e)) => self.show_toast(e),
+            Msg::EarthquakesLoaded { latitude, longitude, result } => {
+                // Ignore answers for a location that's no longer on screen
+                let current = self
+                    .weather_data
+                    .as_ref()
+                    .is_some_and(|d| d.location.latitude == latitude && d.location.longitude == longitude);
+                if current {
+                    self.earthquakes = Some(result);
+                }
+            }
+            Msg::ReportLoaded { path, latitude, longitude, result: Ok(daily) } => {
+                let data = self
+                    .weather_data
+                    .as_ref()
+                    .filter(|d| d.location.latitude == latitude && d.location.longitude == longitude);
+                match data {
+                    Some(data) => {
+                        let text = report::report(data, &daily, report::Format::from_path(&path));
+                        self.commands.push(Command::WriteReport { path, text });
+                    }
+                    None => self.show_toast("Moved on to another place before the report was ready".to_string()),
+                }
+            }
+            Msg::ReportLoaded { result: Err(e), .. } => self.show_toast(format!("Couldn't export: {}", e)),
+            Msg::ReportWritten(Ok(path)) => self.show_toast(format!("Report saved to {}", path.display())),
+            Msg::ReportWritten(Err(e)) => self.show_toast(e),
+            Msg::PluginLoaded { latitude, longitude, result } => {
+                let current = self
+                    .weather_data
+                    .as_ref()
+                    .is_some_and(|d| d.location.latitude == latitude && d.location.longitude == longitude);
+                if current {
+                    self.plugin_panels.retain(|p| p.name != result.name);
+                    self.plugin_panels.push(result);
+                    let plugins = &self.config.plugins;
+                    self.plugin_panels.sort_by_key(|p| plugins.iter().position(|c| c.name == p.name));
+                }
+            }
+            Msg::TrendsLoaded(Err(e)) | Msg::ForecastChecksLoaded(Err(e)) => {
+                self.show_toast(format!("Couldn't read recorded observations: {}", e))
+            }
+            Msg::Tick => self.on_tick(),
+            Msg::Mouse(MouseEvent { kind: MouseEventKind::Moved, column, row, .. }) => {
+                if let (AppState::Trends, Some(trends)) = (&self.state, self.trends.as_mut()) {
+                    trends.pointer = Some((column, row));
+                    trends.cursor = None;
+                    self.dirty = true;
+                }
+            }
+            Msg::Mouse(_) => {}
+            Msg::Resize(width, height) => {
+                if let Some(popup) = self.popup.as_mut() {
+                    popup.clamp_scroll(width, height);
+                }
+            }
+            Msg::Suspend => self.commands.push(Command::Suspend),
+        }
+        std::mem::take(&mut self.commands)
+    }
+
+    pub fn severe_weather(&self) -> Option<&'static str> {
+        let data = self.weather_data.as_ref()?;
+        api::severe_weather(data.weather.current.weather_code)
+    }
+
+    // Only fires when severe weather appears, not on every refresh while it lasts
+    fn alert_if_severe(&mut self, was_severe: bool) {
+        if let (false, Some(severe), Some(data)) = (was_severe, self.severe_weather(), &self.weather_data) {
+            let message = format!("Severe weather in {}: {}. {}", data.location.name, severe, summary::sentence(&data.weather, data.units));
+            self.notify(EventKind::Alert, message, self.config.severe_bell);
+        }
+    }
+
+    pub fn quiet_now(&self) -> bool {
+        let now = chrono::Local::now();
+        let minute = now.hour() * 60 + now.minute();
+        self.config.quiet_hours.as_ref().is_some_and(|q| q.contains(minute))
+    }
+
+    // Background events are always logged, and shown as a toast (maybe with a bell)
+    // unless it's quiet hours
+    fn notify(&mut self, kind: EventKind, message: String, ring: bool) {
+        self.log_event(kind, message.clone());
+        let quiet = self.quiet_now();
+        if matches!(kind, EventKind::Alert | EventKind::Threshold) {
+            let env = vec![
+                ("WEATHER_EVENT".to_string(), "alert".to_string()),
+                ("WEATHER_MESSAGE".to_string(), message.clone()),
+            ];
+            let json = serde_json::json!({ "event": "alert", "kind": kind, "message": message });
+            self.run_hook(self.config.hooks.on_alert.clone(), env, json);
+        }
+        if let (EventKind::Alert | EventKind::Threshold, Some(ntfy)) = (kind, &self.config.ntfy) {
+            // Still delivered during quiet hours, but at ntfy's silent priority
+            self.commands.push(Command::PublishNtfy {
+                ntfy: ntfy.clone(),
+                title: "Weather alert".to_string(),
+                message: message.clone(),
+                priority: if quiet { 1 } else { 4 },
+            });
+        }
+        if quiet {
+            self.quiet_unread += 1;
+        } else {
+            if ring {
+                self.commands.push(Command::Bell);
+            }
+            self.show_toast(message);
+        }
+    }
+
+    fn log_event(&mut self, kind: EventKind, message: String) {
+        let timestamp = chrono::Local::now().timestamp().max(0) as u64;
+        let event = Event { timestamp, kind, message };
+        if !self.read_only {
+            self.commands.push(Command::LogEvent(event.clone()));
+        }
+        self.events.push(event);
+    }
+
+    fn check_rules(&mut self, data: &WeatherData) {
+        let mut alerts = Vec::new();
+        for rule in &self.config.rules {
+            let key = format!("{}@{:.4},{:.4}", rule.name, data.location.latitude, data.location.longitude);
+            let Some(firing) = rules::check(rule, data) else {
+                self.fired_rules.remove(&key);
+                continue;
+            };
+            if !self.fired_rules.insert(key) {
+                continue;
+            }
+            let payload = rules::payload(rule, &firing, data);
+            for url in &self.config.webhooks {
+                self.commands.push(Command::PostWebhook { url: url.clone(), payload: payload.clone() });
+            }
+            alerts.push(firing.message);
+        }
+        for message in alerts {
+            self.notify(EventKind::Threshold, message, self.config.severe_bell);
+        }
+    }
+
+    fn fetch_hazards(&mut self) {
+        if let (Some(radius_km), Some(data)) = (self.config.earthquake_radius_km, &self.weather_data) {
+            self.commands.push(Command::FetchEarthquakes {
+                latitude: data.location.latitude,
+                longitude: data.location.longitude,
+                radius_km,
+            });
+        }
+    }
+
+    // Plugins rerun on every fetch, so their panels refresh along with the weather
+    fn run_plugins(&mut self) {
+        let Some(data) = &self.weather_data else { return };
+        let input = data.to_json();
+        for plugin in &self.config.plugins {
+            self.commands.push(Command::RunPlugin {
+                plugin: plugin.clone(),
+                latitude: data.location.latitude,
+                longitude: data.location.longitude,
+                input: input.clone(),
+            });
+        }
+    }
+
+    fn run_fetch_hooks(&mut self, query: &str, result: &Result<Box<WeatherData>, FetchError>) {
+        match result {
+            Ok(data) => {
+                let current = &data.weather.current;
+                let env = vec![
+                    ("WEATHER_EVENT".to_string(), "fetch".to_string()),
+                    ("WEATHER_QUERY".to_string(), query.to_string()),
+                    ("WEATHER_LOCATION".to_string(), data.location.name.clone()),
+                    ("WEATHER_COUNTRY".to_string(), data.location.country.clone()),
+                    ("WEATHER_LATITUDE".to_string(), data.location.latitude.to_string()),
+                    ("WEATHER_LONGITUDE".to_string(), data.location.longitude.to_string()),
+                    ("WEATHER_TEMPERATURE".to_string(), current.temperature_2m.to_string()),
+                    (
+                        "WEATHER_DESCRIPTION".to_string(),
+                        api::weather_code_to_description(current.weather_code).to_string(),
+                    ),
+                    ("WEATHER_SUMMARY".to_string(), summary::sentence(&data.weather, data.units)),
+                ];
+                let mut json = data.to_json();
+                json["event"] = "fetch".into();
+                json["query"] = query.into();
+                self.run_hook(self.config.hooks.on_fetch.clone(), env, json);
+            }
+            Err(e) => {
+                let env = vec![
+                    ("WEATHER_EVENT".to_string(), "error".to_string()),
+                    ("WEATHER_QUERY".to_string(), query.to_string()),
+                    ("WEATHER_MESSAGE".to_string(), e.to_string()),
+                ];
+                let json = serde_json::json!({ "event": "error", "query": query, "message": e.to_string() });
+                self.run_hook(self.config.hooks.on_error.clone(), env, json);
+            }
+        }
+    }
+
+    fn run_hook(&mut self, command: Option<String>, env: Vec<(String, String)>, json: Value) {
+        if let Some(command) = command {
+            self.commands.push(Command::RunHook { command, env, stdin: json.to_string() });
+        }
+    }
+
+    fn record(&mut self, result: &Result<Box<WeatherData>, FetchError>) {
+        if let (true, false, Ok(data)) = (self.config.record_observations, self.read_only, result) {
+            self.commands.push(Command::RecordObservation(Observation::from_weather(data)));
+        }
+    }
+
+    fn start_search(&mut self, city: String) {
+        self.state = AppState::Loading;
+        self.show_autocomplete = false;
+
+        let cached = self.cached_location(&city);
+        let query = self.search_query(&city);
+        let key = fetch_key(&city, cached.as_ref());
+        if self.refresh_key.as_ref() == Some(&key) {
+            // A refresh for this place is already running; let its result land in the foreground
+            self.refresh_key = None;
+        }
+        let units = self.units_for(&city);
+        if self.in_flight.insert(key.clone()) {
+            self.commands.push(Command::FetchWeather { key, city, query, cached, units });
+        }
+    }
+
+    fn start_refresh(&mut self) {
+        self.refresh_countdown = None;
+        self.dirty = true;
+        let (Some(data), Some(city)) = (&self.weather_data, self.current_query.clone()) else {
+            return;
+        };
+
+        let cached = Some(data.location.clone());
+        let query = self.search_query(&city);
+        let key = fetch_key(&city, cached.as_ref());
+        let units = self.units_for(&city);
+        if self.in_flight.insert(key.clone()) {
+            self.refresh_key = Some(key.clone());
+            self.commands.push(Command::FetchWeather { key, city, query, cached, units });
+        }
+    }
+
+    fn schedule_refresh(&mut self) {
+        // A wall display is useless if it goes stale, so kiosk mode always refreshes
+        let minutes = match self.config.auto_refresh_minutes {
+            None if self.kiosk => Some(10),
+            minutes => minutes,
+        };
+        self.refresh_countdown = minutes
+            .filter(|&minutes| minutes > 0)
+            .map(|minutes| minutes * 60 * TICKS_PER_SECOND);
+    }
+
+    // Opens as soon as either half of the marine view is in; only a place with neither is turned away
+    fn open_marine(&mut self) {
+        match (&self.tides, &self.sea_temperature) {
+            (Some(Ok(_)), _) | (_, Some(Ok(_))) => self.state = AppState::Marine,
+            (Some(Err(_)), Some(Err(e))) => {
+                let e = e.clone();
+                self.tides = None;
+                self.sea_temperature = None;
+                self.show_toast(e);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn show_toast(&mut self, message: String) {
+        self.toast = Some(Toast {
+            message,
+            expires_at: self.tick + 3 * TICKS_PER_SECOND,
+        });
+    }
+
+    // Whether anything on screen has rain or snow falling through it
+    fn falling(&self) -> bool {
+        let falling = |data: &WeatherData| Precipitation::from_code(data.weather.current.weather_code).is_some();
+        if self.kiosk {
+            self.kiosk_tiles.iter().filter_map(|t| t.data.as_ref()).any(falling)
+        } else {
+            self.state == AppState::Display && self.weather_data.as_ref().is_some_and(falling)
+        }
+    }
+
+    // Redrawing every tick is too much for some terminals (serial consoles, slow SSH links), so
+    // effects switch themselves off once drawing keeps falling behind
+    pub fn frame_drawn(&mut self, took: Duration) {
+        if !self.effects {
+            return;
+        }
+        if took < SLOW_FRAME {
+            self.slow_frames = 0;
+            return;
+        }
+        self.slow_frames += 1;
+        if self.slow_frames >= SLOW_FRAMES {
+            self.effects = false;
+            self.slow_frames = 0;
+            self.show_toast("Rain and snow effects are off: this terminal can't keep up (E turns them back on)".to_string());
+        }
+    }
+
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    fn on_tick(&mut self) {
+        self.tick += 1;
+
+        let kiosk_loading = self.kiosk && self.kiosk_tiles.iter().any(|t| t.pending);
+        if self.state == AppState::Loading || kiosk_loading {
+            // Spinner animation
+            self.dirty = true;
+        }
+
+        if self.tick.is_multiple_of(TICKS_PER_SECOND) {
+            let quiet = self.quiet_now();
+            if self.was_quiet && !quiet && self.quiet_unread > 0 {
+                self.show_toast(format!(
+                    "{} event(s) came in during quiet hours - press L to read them",
+                    self.quiet_unread
+                ));
+                self.quiet_unread = 0;
+                self.dirty = true;
+            }
+            self.was_quiet = quiet;
+        }
+
+        if self.effects && self.falling() {
+            self.dirty = true;
+        }
+
+        let flashing = self.state == AppState::Display
+            && !self.config.static_alert_banner
+            && self.severe_weather().is_some();
+        if flashing && self.tick.is_multiple_of(TICKS_PER_SECOND) {
+            self.dirty = true;
+        }
+
+        if self.toast.as_ref().is_some_and(|t| self.tick >= t.expires_at) {
+            self.toast = None;
+            self.dirty = true;
+        }
+
+        // Typing within one tick only produces a single geocoding request
+        if self.autocomplete_pending {
+            self.autocomplete_pending = false;
+            self.request_autocomplete();
+        }
+
+        match self.refresh_countdown {
+            Some(0) if self.kiosk => self.refresh_kiosk(),
+            Some(0) if self.state == AppState::Display => self.start_refresh(),
+            Some(0) | None => {}
+            Some(remaining) => {
+                self.refresh_countdown = Some(remaining - 1);
+                // The countdown is shown in whole seconds
+                if self.state == AppState::Display && (remaining - 1) % TICKS_PER_SECOND == 0 {
+                    self.dirty = true;
+                }
+            }
+        }
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::EnterInsert => self.mode = Mode::Insert,
+            Action::InsertAtStart => {
+                self.mode = Mode::Insert;
+                self.move_cursor_start();
+            }
+            Action::Append => {
+                self.mode = Mode::Insert;
+                self.move_cursor_right();
+            }
+            Action::AppendAtEnd => {
+                self.mode = Mode::Insert;
+                self.move_cursor_end();
+            }
+            Action::LeaveInsert => {
+                self.mode = Mode::Normal;
+                if self.cursor_position > 0 {
+                    self.cursor_position -= 1;
+                }
+                self.show_autocomplete = false;
+            }
+            Action::MoveCursorRight | Action::MoveCursorEnd if self.ghost_text().is_some() => {
+                self.accept_suggestion();
+            }
+            Action::MoveCursorLeft => self.move_cursor_left(),
+            Action::MoveCursorRight => self.move_cursor_right(),
+            Action::MoveCursorStart => self.move_cursor_start(),
+            Action::MoveCursorEnd => self.move_cursor_end(),
+            Action::NextWord => self.move_to_next_word(),
+            Action::PrevWord => self.move_to_prev_word(),
+            Action::InsertChar(c) => {
+                self.insert_char(c);
+                if !self.autocomplete_candidates.is_empty() {
+                    self.refilter_suggestions();
+                }
+                // Local lookups are instant, so only the network gets debounced
+                if self.input.len() >= 3 {
+                    self.local_autocomplete();
+                }
+                self.autocomplete_pending = true;
+            }
+            Action::Backspace => {
+                self.backspace();
+                if self.input.len() < 3 {
+                    self.show_autocomplete = false;
+                    self.autocomplete_suggestions.clear();
+                    self.autocomplete_regions.clear();
+                    self.autocomplete_candidates.clear();
+                } else {
+                    self.local_autocomplete();
+                    self.autocomplete_pending = true;
+                }
+            }
+            Action::DeleteChar => self.delete_char(),
+            Action::ClearInput => {
+                self.input.clear();
+                self.cursor_position = 0;
+            }
+            Action::SelectNext if self.state == AppState::Cyclones => {
+                if self.selected_cyclone + 1 < self.cyclones.len() {
+                    self.selected_cyclone += 1;
+                }
+            }
+            Action::SelectPrev if self.state == AppState::Cyclones => {
+                self.selected_cyclone = self.selected_cyclone.saturating_sub(1);
+            }
+            Action::SelectNext if self.state == AppState::Events => {
+                if self.events_scroll + 1 < self.events.len() {
+                    self.events_scroll += 1;
+                }
+            }
+            Action::SelectPrev if self.state == AppState::Events => {
+                self.events_scroll = self.events_scroll.saturating_sub(1);
+            }
+            Action::SelectNext => {
+                if self.mode == Mode::Insert && self.show_autocomplete {
+                    self.select_next_suggestion();
+                } else if self.showing_recent() {
+                    self.select_next_recent();
+                }
+            }
+            Action::SelectPrev => {
+                if self.mode == Mode::Insert && self.show_autocomplete {
+                    self.select_prev_suggestion();
+                } else if self.showing_recent() {
+                    self.select_prev_recent();
+                }
+            }
+            Action::Complete => {
+                if self.showing_recent() {
+                    self.accept_recent();
+                } else if self.show_autocomplete {
+                    self.accept_suggestion();
+                } else {
+                    self.handle_action(Action::NextPane);
+                }
+            }
+            Action::AcceptSuggestionNumber(number) => {
+                if self.show_autocomplete {
+                    self.accept_suggestion_number(number);
+                } else if let Some(digit) = char::from_digit(number as u32, 10) {
+                    self.handle_action(Action::InsertChar(digit));
+                }
+            }
+            Action::NextPane => self.focused_pane = self.focused_pane.next(|p| p.can_focus(self)),
+            Action::PrevPane => self.focused_pane = self.focused_pane.prev(|p| p.can_focus(self)),
+            Action::FocusPane(pane) if pane.can_focus(self) => {
+                // Only the search box takes typing, so jumping elsewhere leaves Insert mode
+                if pane != Pane::Search {
+                    self.mode = Mode::Normal;
+                    self.show_autocomplete = false;
+                }
+                self.focused_pane = pane;
+            }
+            Action::FocusPane(_) => {}
+            // Only the history list has anything to mark
+            Action::ToggleMark => {}
+            Action::Submit => {
+                if self.showing_recent() {
+                    self.accept_recent();
+                }
+
+                if self.mode == Mode::Insert
+                    && self.show_autocomplete
+                    && !self.autocomplete_suggestions.is_empty()
+                {
+                    self.accept_suggestion();
+                } else if !self.input.is_empty() {
+                    self.start_search(self.input.clone());
+                }
+            }
+            Action::NewSearch => {
+                self.state = AppState::Input;
+                self.mode = Mode::Insert;
+                self.error_message.clear();
+                self.show_autocomplete = false;
+            }
+            Action::Dismiss => {
+                self.state = AppState::Input;
+                self.mode = Mode::Normal;
+                self.error_message.clear();
+                self.show_autocomplete = false;
+            }
+            Action::PickRecent => self.pick_recent(),
+            Action::GoHome => self.go_home(),
+            Action::SetHome => self.set_home(),
+            Action::OpenPalette => self.open_palette(),
+            Action::OpenCommandLine => self.command_line = Some(String::new()),
+            Action::TryCorrection(number) => self.try_correction(number),
+            Action::ToggleBigTemperature => self.big_temperature = !self.big_temperature,
+            Action::ToggleEffects => {
+                self.effects = !self.effects;
+                self.slow_frames = 0;
+            }
+            Action::ShowTrends | Action::ShowAccuracy => {
+                if !self.config.record_observations {
+                    self.show_toast("Set record_observations = true in config.toml to build up a record".to_string());
+                } else if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    let (latitude, longitude) = (data.location.latitude, data.location.longitude);
+                    self.commands.push(match action {
+                        Action::ShowTrends => Command::LoadTrends { latitude, longitude },
+                        _ => Command::LoadForecastChecks { latitude, longitude },
+                    });
+                }
+            }
+            Action::TrendsWindow(hours) => {
+                if let Some(trends) = self.trends.as_mut() {
+                    trends.hours = hours;
+                    trends.pan(0);
+                }
+            }
+            Action::ZoomTrends(step) => {
+                if let Some(trends) = self.trends.as_mut() {
+                    let at = TREND_WINDOWS.iter().position(|&h| h == trends.hours).unwrap_or(1);
+                    trends.hours = TREND_WINDOWS[at.saturating_add_signed(step).min(TREND_WINDOWS.len() - 1)];
+                    trends.pan(0);
+                }
+            }
+            Action::PanTrends(step) => {
+                if let Some(trends) = self.trends.as_mut() {
+                    // A quarter of the window at a time, so there's always something familiar on screen
+                    let hours = (trends.hours / 4) as i64;
+                    trends.pan(-(step as i64) * hours);
+                }
+            }
+            Action::ChartCursor(step) => {
+                if let Some(trends) = self.trends.as_mut() {
+                    let last = trends.recent().len().saturating_sub(1);
+                    // The first press picks the newest reading, wherever it's going
+                    trends.cursor = Some(match trends.cursor {
+                        Some(at) => at.saturating_add_signed(step).min(last),
+                        None => last,
+                    });
+                    trends.pointer = None;
+                }
+            }
+            Action::ShowCyclones => {
+                if self.state == AppState::Display && self.weather_data.is_some() {
+                    self.show_toast("Fetching active storms from the NHC...".to_string());
+                    self.commands.push(Command::FetchCyclones);
+                }
+            }
+            Action::ShowMountain => {
+                if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    let elevations = if self.config.mountain_elevations.is_empty() {
+                        DEFAULT_ELEVATIONS.to_vec()
+                    } else {
+                        self.config.mountain_elevations.clone()
+                    };
+                    self.commands.push(Command::FetchMountain {
+                        latitude: data.location.latitude,
+                        longitude: data.location.longitude,
+                        elevations,
+                    });
+                    self.show_toast("Fetching the mountain forecast...".to_string());
+                }
+            }
+            Action::ShowFreeFlight => {
+                if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    let (latitude, longitude) = (data.location.latitude, data.location.longitude);
+                    self.commands.push(Command::FetchFreeFlight { latitude, longitude });
+                    self.show_toast("Fetching the upper-air forecast...".to_string());
+                }
+            }
+            Action::ShowMarine => {
+                if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    let (latitude, longitude) = (data.location.latitude, data.location.longitude);
+                    let worldtides_key = self.config.worldtides_key.clone();
+                    self.commands.push(Command::FetchTides { latitude, longitude, worldtides_key });
+                    self.commands.push(Command::FetchSeaTemperature { latitude, longitude });
+                    self.tides = None;
+                    self.sea_temperature = None;
+                    self.show_toast("Fetching the marine forecast...".to_string());
+                }
+            }
+            Action::ShowBarograph => {
+                if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    let (latitude, longitude) = (data.location.latitude, data.location.longitude);
+                    self.commands.push(Command::FetchPressure { latitude, longitude });
+                    self.show_toast("Fetching the pressure forecast...".to_string());
+                }
+            }
+            Action::ShowNearby => {
+                if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    self.commands.push(Command::FetchNearby {
+                        latitude: data.location.latitude,
+                        longitude: data.location.longitude,
+                        offset_km: self.config.nearby_offset_km.unwrap_or(NEARBY_OFFSET_KM),
+                    });
+                    self.show_toast("Fetching forecasts for the surrounding area...".to_string());
+                }
+            }
+            Action::PickAirport => self.pick_airport(),
+            Action::PickChart => self.pick_chart(),
+            Action::ShowDewPoint => {
+                let hourly = self.weather_data.as_ref().and_then(|data| data.weather.hourly.as_ref());
+                if self.state == AppState::Display {
+                    if hourly.is_some_and(|hourly| !hourly.dew_point.is_empty()) {
+                        self.state = AppState::DewPoint;
+                    } else {
+                        self.show_toast("No hourly dew point for this place".to_string());
+                    }
+                }
+            }
+            Action::OpenMap | Action::OpenForecastPage => {
+                if let (AppState::Display, Some(data)) = (&self.state, &self.weather_data) {
+                    let (what, command) = match action {
+                        Action::OpenMap => ("a map", self.map_command(&data.location)),
+                        _ => ("its forecast page", self.forecast_page_command(&data.location)),
+                    };
+                    match command {
+                        Ok(command) => {
+                            self.show_toast(format!("Opening {} on {}...", data.location.label(), what));
+                            self.commands.push(Command::Launch(command));
+                        }
+                        Err(e) => self.show_toast(e),
+                    }
+                }
+            }
+            Action::ShowEvents => {
+                if matches!(self.state, AppState::Display | AppState::Error) {
+                    self.events_scroll = 0;
+                    self.state = AppState::Events;
+                }
+            }
+            Action::BackToWeather => {
+                self.trends = None;
+                self.forecast_checks.clear();
+                self.cyclones.clear();
+                self.mountain = None;
+                self.free_flight = None;
+                self.tides = None;
+                self.sea_temperature = None;
+                self.barograph = None;
+                self.nearby.clear();
+                self.brief = None;
+                self.state = if self.weather_data.is_some() { AppState::Display } else { AppState::Input };
+            }
+            Action::ShowHelp => self.show_help(),
+            Action::ShowStats => self.show_stats(),
+            Action::Suspend => self.commands.push(Command::Suspend),
+            Action::Quit => self.commands.push(Command::Quit),
+        }
+    }
+
+    fn add_to_history(&mut self, query: String, location: GeoLocation, timestamp: u64) {
+        history::push_entry(&mut self.search_history, HistoryEntry::new(query, timestamp, Some(location)));
+        self.save_history();
+    }
+
+    // Changes that would only last until quitting are turned down rather than shown as done
+    fn refuse_read_only(&mut self, what: &str) -> bool {
+        if self.read_only {
+            self.show_toast(format!("Can't {} in read-only mode", what));
+        }
+        self.read_only
+    }
+
+    fn save_history(&mut self) {
+        if !self.read_only {
+            self.commands.push(Command::SaveHistory(self.search_history.clone()));
+        }
+    }
+}
+
+// Everything that can change the app: key presses and results coming back from tasks
+pub enum Msg {
+    Key(KeyEvent),
+    AutocompleteResults(String, Vec<GeoLocation>),
+    WeatherResults(String, String, Result<Box<WeatherData>, FetchError>),
+    // Current unix time plus the recorded points, or what went wrong reading them
+    TrendsLoaded(Result<(u64, Vec<TrendPoint>), String>),
+    ForecastChecksLoaded(Result<Vec<ForecastCheck>, String>),
+    CyclonesLoaded(Result<Vec<Cyclone>, String>),
+    MountainLoaded(Result<Box<MountainForecast>, String>),
+    FreeFlightLoaded(Result<Box<FreeFlightForecast>, String>),
+    TidesLoaded(Result<Box<Tides>, String>),
+    SeaTemperatureLoaded(Result<TimeSeries<f64>, String>),
+    PressureLoaded(Result<TimeSeries<f64>, String>),
+    NearbyLoaded(Result<Vec<NearbyPoint>, String>),
+    BugReportWritten(Result<PathBuf, String>),
+    ReportLoaded { path: PathBuf, latitude: f64, longitude: f64, result: Result<Box<DailyForecast>, String> },
+    ReportWritten(Result<PathBuf, String>),
+    ReleaseChecked(Result<Release, String>),
+    BriefLoaded(Result<Box<FlightBrief>, String>),
+    EarthquakesLoaded { latitude: f64, longitude: f64, result: Result<Vec<Earthquake>, String> },
+    PluginLoaded { latitude: f64, longitude: f64, result: PluginResult },
+    HomeResolved(Result<GeoLocation, String>),
+    DeliveryFailed(String),
+    // Kept separate from DeliveryFailed so a failing on_error hook can't trigger itself
+    HookFailed(String),
+    LaunchFailed(String),
+    Tick,
+    Mouse(MouseEvent),
+    Resize(u16, u16),
+    Suspend,
+}
+
+// A known place whose name is a few typos away from a search that found nothing
+pub struct Correction {
+    pub query: String,
+    pub location: Option<GeoLocation>,
+}
+
+// Side effects requested by update; the run loop carries them out and reports back with a Msg
+pub enum Command {
+    FetchWeather {
+        key: String,
+        city: String,
+        query: SearchQuery,
+        cached: Option<GeoLocation>,
+        // Stamped on the result, which is fetched in °C and km/h whatever it's shown in
+        units: Units,
+    },
+    FetchAutocomplete { input: String, query: SearchQuery },
+    SaveHistory(Vec<HistoryEntry>),
+    RecordObservation(Observation),
+    LoadTrends { latitude: f64, longitude: f64 },
+    LoadForecastChecks { latitude: f64, longitude: f64 },
+    FetchCyclones,
+    FetchBrief { icao: String },
+    FetchMountain { latitude: f64, longitude: f64, elevations: Vec<f64> },
+    FetchFreeFlight { latitude: f64, longitude: f64 },
+    FetchTides { latitude: f64, longitude: f64, worldtides_key: Option<String> },
+    FetchSeaTemperature { latitude: f64, longitude: f64 },
+    FetchPressure { latitude: f64, longitude: f64 },
+    FetchNearby { latitude: f64, longitude: f64, offset_km: f64 },
+    CheckForUpdate,
+    // What was on screen goes in the report alongside the config, events and recent API answers
+    WriteBugReport { context: String },
+    // :export, in two steps: the week ahead, then the file
+    FetchReport { path: PathBuf, location: GeoLocation },
+    WriteReport { path: PathBuf, text: String },
+    // :export to .html: the frame as it is now, which needs the terminal
+    WriteSnapshot { path: PathBuf, title: String },
+    Bell,
+    LogEvent(Event),
+    PostWebhook { url: String, payload: Value },
+    PublishNtfy { ntfy: NtfyConfig, title: String, message: String, priority: u8 },
+    RunHook { command: String, env: Vec<(String, String)>, stdin: String },
+    RunPlugin { plugin: Plugin, latitude: f64, longitude: f64, input: Value },
+    // `o`: a browser or map app, left running on its own
+    Launch(String),
+    ResolveHome(SearchQuery),
+    SaveHome(GeoLocation),
+    FetchEarthquakes { latitude: f64, longitude: f64, radius_km: f64 },
+    Suspend,
+    Quit,
+}
+
+impl Focusable for Pane {
+    fn can_focus(&self, app: &App) -> bool {
+        match self {
+            Pane::Search => true,
+            Pane::History => !app.search_history.is_empty(),
+        }
+    }
+
+    fn handle_action(&self, app: &mut App, action: Action) -> Option<Action> {
+        // Insert mode always types into the search box, whatever has focus
+        if app.mode == Mode::Insert {
+            return Some(action);
+        }
+        match (self, action) {
+            (Pane::History, Action::SelectNext) => app.select_next_history(),
+            (Pane::History, Action::SelectPrev) => app.select_prev_history(),
+            (Pane::History, Action::ToggleMark) => app.toggle_history_mark(),
+            (Pane::History, Action::Submit | Action::DeleteChar) if !app.marked_history.is_empty() => app.pick_batch_action(),
+            (Pane::History, Action::Submit) => app.load_selected_history(),
+            (Pane::History, Action::DeleteChar) => app.confirm_delete_history(),
+            // The list has no cursor to move sideways
+            (Pane::History, Action::MoveCursorLeft | Action::MoveCursorRight) => {}
+            _ => return Some(action),
+        }
+        None
+    }
+}
+
+// What goes between the name and the country in each suggestion row: the state, plus the
+// county and the level below for rows that would otherwise read the same, and as a last
+// resort the coordinates
+pub fn suggestion_regions(suggestions: &[GeoLocation]) -> Vec<String> {
+    let mut regions: Vec<Vec<&str>> = suggestions.iter().map(|s| s.admin1.iter().map(String::as_str).collect()).collect();
+    let finer: [fn(&GeoLocation) -> Option<&str>; 2] = [|s| s.admin2.as_deref(), |s| s.admin3.as_deref()];
+    for level in finer {
+        for i in ambiguous_rows(suggestions, &regions) {
+            if let Some(region) = level(&suggestions[i]) {
+                regions[i].insert(0, region);
+            }
+        }
+    }
+
+    let mut labels: Vec<String> = regions.iter().map(|r| r.iter().map(|p| format!(", {}", p)).collect()).collect();
+    for i in ambiguous_rows(suggestions, &regions) {
+        labels[i].push_str(&format!(", at {:.2}, {:.2}", suggestions[i].latitude, suggestions[i].longitude));
+    }
+    labels
+}
+
+fn ambiguous_rows(suggestions: &[GeoLocation], regions: &[Vec<&str>]) -> Vec<usize> {
+    let same_row = |i: usize, j: usize| {
+        suggestions[i].name == suggestions[j].name
+            && suggestions[i].country == suggestions[j].country
+            && regions[i] == regions[j]
+    };
+    (0..suggestions.len())
+        .filter(|&i| (0..suggestions.len()).any(|j| j != i && same_row(i, j)))
+        .collect()
+}
+
+// Identical requests share one key, so coordinates win over the raw query when known
+fn fetch_key(query: &str, cached: Option<&GeoLocation>) -> String {
+    match cached {
+        Some(loc) => format!("{:.4},{:.4}", loc.latitude, loc.longitude),
+        None => query.trim().to_lowercase(),
+    }
+}
+
+// Compares dotted versions number by number, so 0.10.0 beats 0.9.3; anything after a "-" is ignored
+fn newer(latest: &str, current: &str) -> bool {
+    let parts = |version: &str| -> Vec<u64> {
+        let release = version.split('-').next().unwrap_or_default();
+        release.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+    };
+    parts(latest) > parts(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FetchTimings, Source};
+    use crate::demo::Demo;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use proptest::prelude::*;
+    use std::time::Duration;
+
+    fn app() -> App {
+        App::new(Vec::new(), false, Config::default())
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Vec<Command> {
+        app.update(Msg::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    fn type_text(app: &mut App, text: &str) -> Vec<Command> {
+        text.chars().flat_map(|c| press(app, KeyCode::Char(c))).collect()
+    }
+
+    fn berlin() -> GeoLocation {
+        GeoLocation { name: "Berlin".to_string(), latitude: 52.52, longitude: 13.41, country: "Germany".to_string(), admin1: None, admin2: None, admin3: None, near_km: None }
+    }
+
+    fn weather() -> Box<WeatherData> {
+        let weather = serde_json::from_value(serde_json::json!({
+            "current": {
+                "temperature_2m": 17.5,
+                "relative_humidity_2m": 60,
+                "apparent_temperature": 16.8,
+                "precipitation": 0.0,
+                "weather_code": 3,
+                "wind_speed_10m": 12.0,
+                "pressure_msl": 1014.2,
+            },
+            "current_units": { "temperature_2m": "°C", "wind_speed_10m": "km/h", "pressure_msl": "hPa" },
+        }))
+        .unwrap();
+        let timings = FetchTimings { geocode: None, forecast: Duration::ZERO, json_fallback: None };
+        Box::new(WeatherData { location: berlin(), weather, timings, fetched_at: 1_714_564_800, units: Units::default() })
+    }
+
+    #[test]
+    fn typing_edits_the_input_and_asks_for_suggestions_on_the_next_tick() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('i'));
+        assert!(type_text(&mut app, "Bern").is_empty());
+        assert_eq!((app.input.as_str(), app.cursor_position), ("Bern", 4));
+        // Suggestions wait for the next tick, so only the latest input is looked up
+        let commands = app.update(Msg::Tick);
+        assert!(matches!(&commands[..], [Command::FetchAutocomplete { input, .. }] if input == "Bern"));
+        assert!(app.update(Msg::Tick).is_empty());
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!((app.input.as_str(), app.cursor_position), ("Ben", 2));
+    }
+
+    #[test]
+    fn a_search_fetches_once_until_it_comes_back() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('i'));
+        type_text(&mut app, "Berlin @de");
+        let commands = press(&mut app, KeyCode::Enter);
+        let [Command::FetchWeather { key, city, query, cached: None, .. }] = &commands[..] else {
+            panic!("expected one fetch");
+        };
+        assert_eq!((city.as_str(), query.name.as_str(), query.country_code.as_deref()), ("Berlin @de", "Berlin", Some("DE")));
+        assert!(app.state == AppState::Loading);
+        // Asking again while it's in flight doesn't send a second request
+        app.state = AppState::Input;
+        assert!(press(&mut app, KeyCode::Enter).is_empty());
+
+        let commands = app.update(Msg::WeatherResults(key.clone(), city.clone(), Ok(weather())));
+        assert!(matches!(&commands[..], [Command::SaveHistory(history)] if history[0].query == "Berlin @de"));
+        assert!(app.state == AppState::Display && app.input.is_empty());
+        assert_eq!(app.weather_data.as_ref().map(|d| d.location.name.as_str()), Some("Berlin"));
+    }
+
+    #[test]
+    fn a_failed_search_shows_the_error_and_keeps_no_history() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('i'));
+        type_text(&mut app, "Atlantis");
+        let key = match &press(&mut app, KeyCode::Enter)[..] {
+            [Command::FetchWeather { key, .. }] => key.clone(),
+            _ => panic!("expected one fetch"),
+        };
+        let commands = app.update(Msg::WeatherResults(key, "Atlantis".to_string(), Err(FetchError::NotFound("'Atlantis' not found.".to_string()))));
+        assert!(commands.is_empty());
+        assert!(app.state == AppState::Error && app.error_message == "'Atlantis' not found.");
+        assert!(app.search_history.is_empty() && app.in_flight.is_empty());
+    }
+
+    #[test]
+    fn suggestions_only_land_for_the_input_they_were_asked_for() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('i'));
+        type_text(&mut app, "Berl");
+        app.update(Msg::AutocompleteResults("Ber".to_string(), vec![berlin()]));
+        assert!(app.autocomplete_candidates.is_empty());
+        app.update(Msg::AutocompleteResults("Berl".to_string(), vec![berlin()]));
+        assert_eq!(app.autocomplete_suggestions.len(), 1);
+    }
+
+    #[test]
+    fn read_only_turns_down_deleting_history() {
+        let entry = HistoryEntry::new("Bergen".to_string(), 0, None);
+        let mut app = App::new(vec![entry], false, Config::default());
+        app.read_only = true;
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('x'));
+        assert!(app.popup.is_none());
+        assert_eq!(app.search_history.len(), 1);
+        assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("Can't delete history entries in read-only mode"));
+    }
+
+    // Bergen and Berlin in history, both marked, with the batch picker open
+    fn marked_pair(read_only: bool) -> App {
+        let entries = ["Bergen", "Berlin"].map(|query| HistoryEntry::new(query.to_string(), 0, None));
+        let mut app = App::new(entries.to_vec(), false, Config::default());
+        app.read_only = read_only;
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Enter);
+        app
+    }
+
+    #[test]
+    fn marked_history_becomes_favorites_and_is_saved() {
+        let mut app = marked_pair(false);
+        press(&mut app, KeyCode::Down);
+        let commands = press(&mut app, KeyCode::Enter);
+        assert!(app.search_history.iter().all(|e| e.favorite));
+        assert!(app.marked_history.is_empty());
+        assert!(matches!(&commands[..], [Command::SaveHistory(saved)] if saved.iter().all(|e| e.favorite)));
+    }
+
+    #[test]
+    fn read_only_turns_down_changing_marked_history() {
+        let mut app = marked_pair(true);
+        let commands = press(&mut app, KeyCode::Enter);
+        assert!(commands.is_empty());
+        assert_eq!(app.search_history.len(), 2);
+        assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("Can't delete history entries in read-only mode"));
+
+        let mut app = marked_pair(true);
+        press(&mut app, KeyCode::Down);
+        let commands = press(&mut app, KeyCode::Enter);
+        assert!(commands.is_empty());
+        assert!(app.search_history.iter().all(|e| !e.favorite));
+        assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("Can't change favorites in read-only mode"));
+    }
+
+    #[test]
+    fn home_set_saves_the_place_on_screen() {
+        let mut app = app();
+        app.run_command_line("home set");
+        assert!(app.home.is_none());
+        app.update(Msg::WeatherResults("berlin".to_string(), "Berlin".to_string(), Ok(weather())));
+        app.commands.clear();
+        app.run_command_line("home set");
+        assert_eq!(app.home.as_ref().map(|h| h.name.as_str()), Some("Berlin"));
+        assert!(matches!(&app.commands[..], [Command::SaveHome(home)] if home.name == "Berlin"));
+    }
+
+    #[test]
+    fn read_only_keeps_home_where_it_was() {
+        let mut app = app();
+        app.read_only = true;
+        app.update(Msg::WeatherResults("berlin".to_string(), "Berlin".to_string(), Ok(weather())));
+        app.commands.clear();
+        app.run_command_line("home set");
+        assert!(app.home.is_none());
+        assert!(!app.commands.iter().any(|c| matches!(c, Command::SaveHome(_))));
+        assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("Can't change home in read-only mode"));
+    }
+
+    #[test]
+    fn only_an_unknown_place_gets_did_you_mean() {
+        let entry = HistoryEntry::new("Berlin".to_string(), 0, Some(berlin()));
+        let mut app = App::new(vec![entry], false, Config::default());
+        let fail = |app: &mut App, error: FetchError| {
+            app.update(Msg::WeatherResults("berln".to_string(), "Berln".to_string(), Err(error)));
+            app.corrections.iter().map(|c| c.query.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(fail(&mut app, FetchError::NotFound("'Berln' not found.".to_string())), ["Berlin"]);
+        // Same text, but the geocoder never answered, so there's nothing to correct
+        assert!(fail(&mut app, FetchError::Failed("'Berln' not found.".to_string())).is_empty());
+    }
+
+    #[derive(Debug, Clone)]
+    enum Edit {
+        Left,
+        Right,
+        Start,
+        End,
+        Insert(char),
+        Delete,
+        Backspace,
+        NextWord,
+        PrevWord,
+    }
+
+    fn edit() -> impl Strategy<Value = Edit> {
+        prop_oneof![
+            Just(Edit::Left),
+            Just(Edit::Right),
+            Just(Edit::Start),
+            Just(Edit::End),
+            // Whitespace gets its own arm so word motions have words to move between
+            prop_oneof![any::<char>(), Just(' '), Just('\t'), Just('\u{3000}')].prop_map(Edit::Insert),
+            Just(Edit::Delete),
+            Just(Edit::Backspace),
+            Just(Edit::NextWord),
+            Just(Edit::PrevWord),
+        ]
+    }
+
+    fn editor(input: &str, cursor: usize) -> App {
+        let mut app = App::new(Vec::new(), false, Config::default());
+        app.input = input.to_string();
+        app.cursor_position = cursor.min(app.char_count());
+        app
+    }
+
+    fn word_start(chars: &[char], pos: usize) -> bool {
+        pos == 0 || pos == chars.len() || (!chars[pos].is_whitespace() && chars[pos - 1].is_whitespace())
+    }
+
+    proptest! {
+        #[test]
+        fn edits_keep_cursor_on_a_char_boundary(
+            input in "[a-zøßü东京 \\PC]{0,24}",
+            cursor in 0usize..32,
+            edits in prop::collection::vec(edit(), 0..64),
+        ) {
+            let mut app = editor(&input, cursor);
+            // The same edits on a plain char vector, which can't get byte offsets wrong
+            let mut model: Vec<char> = app.input.chars().collect();
+            let mut at = app.cursor_position;
+
+            for edit in edits {
+                let before = app.cursor_position;
+                match edit {
+                    Edit::Left => {
+                        app.move_cursor_left();
+                        at = at.saturating_sub(1);
+                    }
+                    Edit::Right => {
+                        app.move_cursor_right();
+                        at = (at + 1).min(model.len());
+                    }
+                    Edit::Start => {
+                        app.move_cursor_start();
+                        at = 0;
+                    }
+                    Edit::End => {
+                        app.move_cursor_end();
+                        at = model.len();
+                    }
+                    Edit::Insert(c) => {
+                        app.insert_char(c);
+                        model.insert(at, c);
+                        at += 1;
+                    }
+                    Edit::Delete => {
+                        app.delete_char();
+                        if at < model.len() {
+                            model.remove(at);
+                        }
+                    }
+                    Edit::Backspace => {
+                        app.backspace();
+                        if at > 0 {
+                            at -= 1;
+                            model.remove(at);
+                        }
+                    }
+                    Edit::NextWord => {
+                        app.move_to_next_word();
+                        prop_assert!(app.cursor_position >= before);
+                        prop_assert!(word_start(&model, app.cursor_position));
+                        at = app.cursor_position;
+                    }
+                    Edit::PrevWord => {
+                        app.move_to_prev_word();
+                        prop_assert!(app.cursor_position <= before);
+                        prop_assert!(word_start(&model, app.cursor_position));
+                        at = app.cursor_position;
+                    }
+                }
+
+                prop_assert_eq!(app.char_count(), app.input.chars().count());
+                prop_assert!(app.cursor_position <= app.char_count());
+                prop_assert!(app.input.is_char_boundary(app.char_to_byte_pos(app.cursor_position)));
+                prop_assert_eq!(&app.input, &model.iter().collect::<String>());
+                prop_assert_eq!(app.cursor_position, at);
+            }
+        }
+
+        #[test]
+        fn word_motions_stop_at_every_word(input in "[a-zé \\t\\u{3000}]{0,32}") {
+            let mut app = editor(&input, 0);
+            let chars: Vec<char> = input.chars().collect();
+            let starts: Vec<usize> = (1..chars.len()).filter(|&i| word_start(&chars, i)).collect();
+
+            let mut forward = Vec::new();
+            while app.cursor_position < chars.len() {
+                app.move_to_next_word();
+                forward.push(app.cursor_position);
+            }
+            let mut expected = starts.clone();
+            if !chars.is_empty() {
+                expected.push(chars.len());
+            }
+            prop_assert_eq!(forward, expected);
+
+            // Backwards from the end lands on the same word starts, then the beginning
+            let mut backward = Vec::new();
+            while app.cursor_position > 0 {
+                app.move_to_prev_word();
+                backward.push(app.cursor_position);
+            }
+            let mut expected: Vec<usize> = starts.into_iter().rev().collect();
+            if !chars.is_empty() {
+                expected.push(0);
+            }
+            prop_assert_eq!(backward, expected);
+        }
+    }
+
+    #[test]
+    fn map_and_forecast_page_commands_get_the_place() {
+        let location = GeoLocation {
+            name: "Tromsø".to_string(),
+            latitude: 69.6496,
+            longitude: 18.956,
+            country: "Norway".to_string(),
+            admin1: None,
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        };
+        let mut app = App::new(Vec::new(), false, Config::default());
+        let command = app.map_command(&location).unwrap();
+        assert!(command.contains("https://www.openstreetmap.org/?mlat=69.64960&mlon=18.95600#map=12/69.64960/18.95600"), "{}", command);
+        app.config.map_command = Some("firefox 'geo:{lat},{lon}'".to_string());
+        assert_eq!(app.map_command(&location).unwrap(), "firefox 'geo:69.64960,18.95600'");
+        app.config.map_command = Some("open {latitude}".to_string());
+        assert!(app.map_command(&location).is_err());
+
+        let command = app.forecast_page_command(&location).unwrap();
+        assert!(command.contains("'https://open-meteo.com/en/docs?latitude=69.64960&longitude=18.95600'"), "{}", command);
+        app.config.forecast_page = Some("https://wttr.in/{name}".to_string());
+        assert!(app.forecast_page_command(&location).unwrap().contains("https://wttr.in/Troms%C3%B8"));
+    }
+
+    #[tokio::test]
+    async fn units_for_a_place_the_history_has_dropped_bring_it_back() {
+        let data = Demo.fetch_weather(&SearchQuery::parse("Lisbon", None), None).await.unwrap();
+        let mut app = App::new(Vec::new(), false, Config::default());
+        app.weather_data = Some(data);
+        app.current_query = Some("Lisbon".to_string());
+        app.state = AppState::Display;
+        app.set_units(&["imperial"]);
+
+        let entry = &app.search_history[0];
+        assert!(entry.query == "Lisbon" && entry.favorite && entry.units == Some(Units::IMPERIAL));
+        assert_eq!(app.weather_data.as_ref().unwrap().units, Units::IMPERIAL);
+        assert!(matches!(app.commands.last(), Some(Command::SaveHistory(_))));
+    }
+
+    #[test]
+    fn versions_compare_by_number_not_by_text() {
+        assert!(newer("0.10.0", "0.9.3"));
+        assert!(newer("1.0.0", "0.1.0"));
+        assert!(!newer("0.1.0", "0.1.0"));
+        assert!(!newer("0.1.0-rc.1", "0.1.0"));
+    }
+
+    #[test]
+    fn chart_cursor_starts_at_the_newest_reading_and_stops_at_the_ends() {
+        let mut app = App::new(Vec::new(), false, Config::default());
+        let point = |hours_ago: u64| TrendPoint { fetched_at: 1_000_000 - hours_ago * 3600, temperature: 10.0, pressure: 1013.0, precipitation: 0.0, weather_code: 0 };
+        // The one from 30 h ago is outside the 24 h window
+        let points = vec![point(30), point(20), point(10), point(1)];
+        app.trends = Some(Trends { now: 1_000_000, hours: 24, offset: 0, points, cursor: None, pointer: Some((5, 5)) });
+        app.state = AppState::Trends;
+        let cursor = |app: &mut App, code| {
+            app.update(Msg::Key(KeyEvent::from(code)));
+            app.trends.as_ref().and_then(|t| t.cursor)
+        };
+        assert_eq!(cursor(&mut app, KeyCode::Left), Some(2));
+        assert_eq!(app.trends.as_ref().and_then(|t| t.pointer), None);
+        assert_eq!(cursor(&mut app, KeyCode::Left), Some(1));
+        assert_eq!(cursor(&mut app, KeyCode::Left), Some(0));
+        assert_eq!(cursor(&mut app, KeyCode::Left), Some(0));
+        assert_eq!(cursor(&mut app, KeyCode::Right), Some(1));
+        assert_eq!(cursor(&mut app, KeyCode::Char('2')), None);
+    }
+
+    #[test]
+    fn panning_stays_inside_the_loaded_week() {
+        let mut app = App::new(Vec::new(), false, Config::default());
+        app.trends = Some(Trends { now: 1_000_000, hours: 24, offset: 0, points: Vec::new(), cursor: None, pointer: None });
+        app.state = AppState::Trends;
+        let window = |app: &mut App, code| {
+            app.update(Msg::Key(KeyEvent::from(code)));
+            app.trends.as_ref().map(|t| (t.hours, t.offset))
+        };
+        assert_eq!(window(&mut app, KeyCode::Char('l')), Some((24, 0)));
+        assert_eq!(window(&mut app, KeyCode::Char('h')), Some((24, 6)));
+        assert_eq!(window(&mut app, KeyCode::Char('-')), Some((48, 6)));
+        for _ in 0..20 {
+            app.update(Msg::Key(KeyEvent::from(KeyCode::Char('h'))));
+        }
+        assert_eq!(window(&mut app, KeyCode::Char('h')), Some((48, 120)));
+        // Zooming out to the whole week leaves nowhere to pan to
+        assert_eq!(window(&mut app, KeyCode::Char('4')), Some((168, 0)));
+        assert_eq!(window(&mut app, KeyCode::Char('+')), Some((48, 0)));
+        assert_eq!(window(&mut app, KeyCode::Char('1')), Some((12, 0)));
+        assert_eq!(window(&mut app, KeyCode::Char('+')), Some((12, 0)));
+    }
+}
@@ -0,0 +1,52 @@
+// The sailor's rule of thumb: a glass falling more than 3 hPa in three hours means wind is coming
+pub const STORM_DROP_HPA: f64 = 3.0;
+pub const DROP_HOURS: usize = 3;
+
+// Hours that end a fall of more than STORM_DROP_HPA over the DROP_HOURS before them, as indices
+// into hourly `pressure`. A run of them is one storm, marked once where it falls fastest
+pub fn storm_drops(pressure: &[f64]) -> Vec<usize> {
+    let drop = |i: usize| pressure[i - DROP_HOURS] - pressure[i];
+    let mut marks: Vec<usize> = Vec::new();
+    let mut previous = None;
+    for i in DROP_HOURS..pressure.len() {
+        if drop(i) <= STORM_DROP_HPA {
+            continue;
+        }
+        match marks.last_mut() {
+            Some(last) if previous == Some(i - 1) => {
+                if drop(i) > drop(*last) {
+                    *last = i;
+                }
+            }
+            _ => marks.push(i),
+        }
+        previous = Some(i);
+    }
+    marks
+}
+
+// hPa lost over the DROP_HOURS up to `i`; negative while rising
+pub fn fall(pressure: &[f64], i: usize) -> Option<f64> {
+    Some(pressure.get(i.checked_sub(DROP_HOURS)?)? - pressure.get(i)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_mark_per_storm_where_it_falls_fastest() {
+        let mut pressure = vec![1015.0; 6];
+        // A deepening low: 1.5, then 2, then 1 hPa an hour, and a slow recovery
+        for step in [1.5, 2.0, 1.0, 0.5, 0.0, -0.5, -0.5] {
+            let last = pressure[pressure.len() - 1];
+            pressure.push(last - step);
+        }
+        // Falling 1 hPa an hour never crosses the line
+        pressure.extend((1..=6).map(|h| 1011.0 - h as f64));
+        let marks = storm_drops(&pressure);
+        assert_eq!(marks, vec![8]);
+        assert_eq!(fall(&pressure, 8), Some(4.5));
+        assert_eq!(fall(&pressure, 2), None);
+    }
+}
@@ -0,0 +1,49 @@
+// Block-letter rendering for the large temperature banner. Each glyph is 3x5 cells and
+// every cell is drawn two columns wide so the digits come out roughly square.
+
+pub const HEIGHT: usize = 5;
+
+fn glyph(c: char) -> Option<[&'static str; HEIGHT]> {
+    let rows = match c {
+        '0' => ["###", "# #", "# #", "# #", "###"],
+        '1' => [" # ", "## ", " # ", " # ", "###"],
+        '2' => ["###", "  #", "###", "#  ", "###"],
+        '3' => ["###", "  #", "###", "  #", "###"],
+        '4' => ["# #", "# #", "###", "  #", "  #"],
+        '5' => ["###", "#  ", "###", "  #", "###"],
+        '6' => ["###", "#  ", "###", "# #", "###"],
+        '7' => ["###", "  #", "  #", "  #", "  #"],
+        '8' => ["###", "# #", "###", "# #", "###"],
+        '9' => ["###", "# #", "###", "  #", "###"],
+        '-' => ["   ", "   ", "###", "   ", "   "],
+        '.' => [" ", " ", " ", " ", "#"],
+        '°' => ["##", "##", "  ", "  ", "  "],
+        'C' => ["###", "#  ", "#  ", "#  ", "###"],
+        'F' => ["###", "#  ", "## ", "#  ", "#  "],
+        'K' => ["# #", "## ", "#  ", "## ", "# #"],
+        ' ' => ["  ", "  ", "  ", "  ", "  "],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+// Returns HEIGHT lines; characters without a glyph are skipped
+pub fn render(text: &str, fill: char) -> Vec<String> {
+    let mut lines = vec![String::new(); HEIGHT];
+    for c in text.chars() {
+        let Some(rows) = glyph(c) else {
+            continue;
+        };
+        for (line, row) in lines.iter_mut().zip(rows) {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            for cell in row.chars() {
+                let ch = if cell == '#' { fill } else { ' ' };
+                line.push(ch);
+                line.push(ch);
+            }
+        }
+    }
+    lines
+}
@@ -0,0 +1,218 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    fs,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::config;
+use crate::events;
+use crate::session::{self, Body, Exchange};
+
+// How much of the recent past goes in a report
+const PAYLOADS: usize = 5;
+const PAYLOAD_BYTES: usize = 16 * 1024;
+const EVENTS: usize = 50;
+
+// Anything under these names in config.toml is replaced before it goes in a report: tokens,
+// addresses that work as passwords, commands and links that tend to have either in them, and where you live
+const SECRET_KEYS: [&str; 10] = [
+    "token",
+    "worldtides_key",
+    "webhooks",
+    "topic",
+    "user_agent",
+    "home",
+    "hooks",
+    "plugins",
+    "map_command",
+    "forecast_page",
+];
+
+static RECENT: OnceLock<Mutex<VecDeque<Exchange>>> = OnceLock::new();
+
+fn recent() -> &'static Mutex<VecDeque<Exchange>> {
+    RECENT.get_or_init(|| Mutex::new(VecDeque::with_capacity(PAYLOADS)))
+}
+
+// Keeps the last few API answers around, cut short, in case they turn out to be what broke something
+pub fn remember(exchange: &Exchange) {
+    let Ok(mut recent) = recent().lock() else { return };
+    if recent.len() == PAYLOADS {
+        recent.pop_front();
+    }
+    let mut body = exchange.body.clone().into_bytes();
+    body.truncate(PAYLOAD_BYTES);
+    recent.push_back(Exchange { url: session::scrub(&exchange.url), body: Body::new(body), ..exchange.clone() });
+}
+
+pub fn get_reports_dir() -> PathBuf {
+    if let Some(mut path) = config::profile_dir() {
+        path.push("bugreports");
+        return path;
+    }
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("bugreports");
+    path
+}
+
+// Writes a report to attach to an issue and says where it went. `panic` is the panic message when
+// it's written on the way down, `context` whatever the app could say about what was on screen
+pub fn write(panic: Option<&str>, context: &str) -> Result<PathBuf, String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let dir = get_reports_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Can't create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("bugreport-{}.txt", now));
+    fs::write(&path, report(panic, context)).map_err(|e| format!("Can't write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+pub fn report(panic: Option<&str>, context: &str) -> String {
+    let env = |name| std::env::var(name).unwrap_or_else(|_| "-".to_string());
+    let size = crossterm::terminal::size().map_or("unknown".to_string(), |(w, h)| format!("{}x{}", w, h));
+
+    let mut out = String::new();
+    let _ = writeln!(out, "weather-tui {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "OS: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(out, "Terminal: TERM={} COLORTERM={} TERM_PROGRAM={} size {}", env("TERM"), env("COLORTERM"), env("TERM_PROGRAM"), size);
+    if !context.is_empty() {
+        let _ = writeln!(out, "State: {}", context);
+    }
+    if let Some(panic) = panic {
+        let _ = writeln!(out, "\nPanic: {}", panic);
+    }
+
+    let _ = writeln!(out, "\n== config.toml, secrets stripped ==");
+    out.push_str(&stripped_config());
+
+    let _ = writeln!(out, "\n== Last {} events ==", EVENTS);
+    let events = events::load_events().unwrap_or_default();
+    for event in events.iter().rev().take(EVENTS).rev() {
+        let _ = writeln!(out, "{} {:?} {}", event.timestamp, event.kind, event.message);
+    }
+
+    let _ = writeln!(out, "\n== Last {} API responses, first {} KB of each ==", PAYLOADS, PAYLOAD_BYTES / 1024);
+    if let Ok(recent) = recent().lock() {
+        for exchange in recent.iter() {
+            let _ = writeln!(out, "\n{} {} -> {}", exchange.method, exchange.url, exchange.status);
+            match &exchange.body {
+                Body::Text(text) => out.push_str(text),
+                Body::Bytes(bytes) => {
+                    let _ = write!(out, "({} bytes of binary)", bytes.len());
+                }
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn stripped_config() -> String {
+    let path = config::get_config_path();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return "(none)\n".to_string();
+    };
+    stripped(&content)
+}
+
+// A broken file is reported by position only: toml's own message quotes the line, secrets and all
+fn stripped(content: &str) -> String {
+    match toml::from_str::<toml::Value>(content) {
+        Ok(mut value) => {
+            strip(&mut value);
+            toml::to_string_pretty(&value).unwrap_or_default()
+        }
+        Err(e) => match e.span() {
+            Some(span) => {
+                let before = &content[..span.start.min(content.len())];
+                let line = before.matches('\n').count() + 1;
+                let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+                format!("(doesn't parse: line {}, column {})\n", line, column)
+            }
+            None => "(doesn't parse)\n".to_string(),
+        },
+    }
+}
+
+fn strip(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                if SECRET_KEYS.contains(&key.as_str()) {
+                    *value = toml::Value::String("<stripped>".to_string());
+                } else {
+                    strip(value);
+                }
+            }
+        }
+        toml::Value::Array(items) => items.iter_mut().for_each(strip),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_broken_config_is_reported_without_its_contents() {
+        let report = stripped("home = \"Leeds\"\n[ntfy]\ntoken = \"tk_secret123\n");
+        assert!(report.starts_with("(doesn't parse: line 3, column "), "{}", report);
+        assert!(!report.contains("tk_secret123"), "{}", report);
+    }
+
+    #[test]
+    fn secrets_are_stripped_at_any_depth() {
+        let mut config: toml::Value = toml::from_str(
+            r#"
+            home = "12 Acacia Avenue"
+            webhooks = ["https://hooks.slack.com/services/T0/B0/secret"]
+            big_temperature = true
+            [ntfy]
+            topic = "my-secret-topic"
+            token = "tk_abc"
+            server = "https://ntfy.sh"
+            "#,
+        )
+        .expect("config should parse");
+        strip(&mut config);
+        let text = toml::to_string(&config).expect("config should print");
+        for secret in ["Acacia", "slack", "my-secret-topic", "tk_abc"] {
+            assert!(!text.contains(secret), "{} leaked into\n{}", secret, text);
+        }
+        assert!(text.contains("big_temperature = true") && text.contains("https://ntfy.sh"), "{}", text);
+    }
+
+    #[test]
+    fn hook_and_plugin_commands_are_stripped() {
+        let report = stripped(
+            r#"
+            [hooks]
+            on_alert = "curl -H 'Authorization: Bearer hk_secret' https://example.com"
+            [[plugins]]
+            name = "Pollen"
+            command = "pollen --api-key pk_secret"
+            "#,
+        );
+        for secret in ["hk_secret", "pk_secret", "Pollen"] {
+            assert!(!report.contains(secret), "{} leaked into\n{}", secret, report);
+        }
+    }
+
+    #[test]
+    fn map_and_forecast_page_templates_are_stripped() {
+        let report = stripped(
+            r#"
+            map_command = "mymaps --key mk_secret '{lat},{lon}'"
+            forecast_page = "https://example.com/{lat},{lon}?apikey=fp_secret"
+            "#,
+        );
+        assert!(report.contains("map_command") && report.contains("forecast_page"), "{}", report);
+        for secret in ["mk_secret", "mymaps", "fp_secret"] {
+            assert!(!report.contains(secret), "{} leaked into\n{}", secret, report);
+        }
+    }
+}
@@ -0,0 +1,731 @@
+use chrono::{Local, NaiveDateTime, NaiveTime, Utc};
+use crossterm::{
+    queue,
+    style::{Print, ResetColor, SetForegroundColor},
+};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use futures::{stream, StreamExt};
+use serde_json::json;
+use std::{
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::api::{self, weather_code_to_description, GeoLocation, SearchQuery, WeatherData};
+use crate::config::{Config, Glyphs};
+use crate::gazetteer;
+use crate::glyphs::{self, Icon};
+use crate::history::{self, HistoryEntry};
+use crate::ics;
+use crate::records::{self, Observation};
+use crate::route::{self, ProfilePoint};
+use crate::rules::{self, Predicate};
+use crate::schema;
+use crate::series::TimeSeries;
+use crate::summary;
+use crate::template;
+use crate::theme::Theme;
+use crate::units::Units;
+use crate::view;
+use crate::waypoints;
+
+// Apart from 1 for errors, so "it's raining" and "no network" aren't the same answer
+const CONDITION_HELD: i32 = 3;
+const BATCH_SPACING: Duration = Duration::from_millis(100);
+
+#[derive(Parser)]
+#[command(version, about = "Search for a city and see its current weather")]
+pub struct Cli {
+    #[arg(num_args = 1.., help = "Open straight to this city's weather, optionally with @CC for the country")]
+    pub city: Vec<String>,
+    #[arg(long, requires = "city", help = "Print the city's weather and exit, like `get`")]
+    pub once: bool,
+    #[arg(long, requires = "once", help = "With --once, print JSON instead of text")]
+    pub json: bool,
+    #[arg(long, global = true, value_enum, help = "Colour theme, instead of the one in the config")]
+    pub theme: Option<Theme>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "UNITS",
+        value_delimiter = ',',
+        help = "Units for this run, e.g. imperial or f,mph, instead of the config's (favorites keep their own)"
+    )]
+    pub units: Vec<String>,
+    #[command(flatten)]
+    pub tui: TuiArgs,
+    #[arg(long, global = true, help = "Never write history or recorded observations")]
+    pub read_only: bool,
+    #[arg(long, global = true, value_name = "NAME", help = "Use a separate config and history set")]
+    pub profile: Option<String>,
+    // Make requests fail at random, for trying out error handling (or set WEATHER_TUI_SIMULATE_ERRORS)
+    #[arg(long, hide = true)]
+    pub simulate_errors: bool,
+    #[arg(long, value_name = "FILE", help = "Save key presses and API responses, e.g. for a bug report")]
+    pub record: Option<PathBuf>,
+    #[arg(long, value_name = "FILE", conflicts_with = "record", help = "Play back a --record session without the network")]
+    pub replay: Option<PathBuf>,
+    #[arg(long, conflicts_with_all = ["record", "replay"], help = "Tour made-up weather offline, e.g. for screenshots")]
+    pub demo: bool,
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Args, Clone, Copy)]
+pub struct TuiArgs {
+    #[arg(long, help = "Show how long each fetch took (or set WEATHER_TUI_DEBUG)")]
+    pub debug: bool,
+    #[arg(long, help = "Full-screen dashboard of kiosk_locations")]
+    pub kiosk: bool,
+    #[arg(long, help = "Large print: big, bold and spaced out, without tables (or set view = \"large\")")]
+    pub large: bool,
+}
+
+#[derive(Subcommand)]
+pub enum CliCommand {
+    #[command(about = "Start the interactive interface (the default)")]
+    Tui(TuiArgs),
+    #[command(about = "Print the current weather for a city")]
+    Get {
+        #[arg(required_unless_present = "batch", num_args = 1.., help = "City, optionally with @CC for the country")]
+        city: Vec<String>,
+        #[arg(long, help = "Print JSON instead of text")]
+        json: bool,
+        #[arg(long, conflicts_with = "json", help = "Print a one-line summary, e.g. for a status bar")]
+        oneline: bool,
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            conflicts_with_all = ["json", "oneline"],
+            help = "Print your own line, e.g. \"{city}: {temp}{unit} {icon}\" (see the README for every {placeholder})"
+        )]
+        format: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["city", "oneline"],
+            help = "Read one city per line from stdin; with --json, print a JSON object per line"
+        )]
+        batch: bool,
+        #[arg(
+            long,
+            value_name = "CONDITION",
+            conflicts_with = "batch",
+            help = "Exit with status 3 if this holds, e.g. \"rain_ahead>0\" (repeatable)"
+        )]
+        fail_if: Vec<Predicate>,
+    },
+    #[command(about = "Print the daily forecast for a city")]
+    Forecast {
+        #[arg(required = true, num_args = 1.., help = "City, optionally with @CC for the country")]
+        city: Vec<String>,
+        #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u8).range(1..=16))]
+        days: u8,
+        #[arg(long, help = "Print JSON instead of text")]
+        json: bool,
+        #[arg(long, value_name = "FILE", conflicts_with = "json", help = "Write the days and rule alert windows as a calendar (.ics)")]
+        ics: Option<PathBuf>,
+    },
+    #[command(about = "Manage the saved locations shown in the history pane")]
+    Locations {
+        #[command(subcommand)]
+        action: LocationsCommand,
+    },
+    #[command(about = "Check the webhooks configured for rule alerts")]
+    Webhooks {
+        #[command(subcommand)]
+        action: WebhooksCommand,
+    },
+    #[command(about = "Manage the offline city list used for autocomplete")]
+    Gazetteer {
+        #[command(subcommand)]
+        action: GazetteerCommand,
+    },
+    #[command(about = "Print the forecast along a GPX track for when you'll get to each part of it")]
+    Route {
+        file: PathBuf,
+        #[arg(long, default_value_t = 15.0, help = "Average speed in km/h, stops included")]
+        speed: f64,
+        #[arg(long, help = "When you set off, as HH:MM today or YYYY-MM-DDTHH:MM (default: now)")]
+        start: Option<String>,
+        #[arg(long, value_name = "KM", help = "Distance between forecasts (default: an hour's travel)")]
+        every: Option<f64>,
+        #[arg(long, help = "Also chart height, temperature and wind along the way")]
+        profile: bool,
+    },
+    #[command(about = "Print the JSON Schema of what --json prints")]
+    Schema,
+    #[command(about = "Print a shell completion script to stdout")]
+    Completions { shell: Shell },
+}
+
+#[derive(Subcommand)]
+pub enum LocationsCommand {
+    #[command(about = "List saved locations, most recent first")]
+    List,
+    #[command(about = "Look up a city and save it")]
+    Add {
+        #[arg(required = true, num_args = 1..)]
+        city: Vec<String>,
+    },
+    #[command(about = "Remove a saved location by name")]
+    Rm {
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+    },
+    #[command(about = "Save the waypoints in a GPX or KML file as favorites, for a dashboard of the route")]
+    Import { file: PathBuf },
+}
+
+#[derive(Subcommand)]
+pub enum WebhooksCommand {
+    #[command(about = "Send a test alert to every configured webhook")]
+    Test {
+        #[arg(long, help = "Print the payload and URLs instead of sending")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GazetteerCommand {
+    #[command(about = "Download the GeoNames list of cities with 15,000+ people")]
+    Download,
+}
+
+pub fn print_completions(shell: Shell) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut io::stdout());
+}
+
+// Runs one of the non-interactive subcommands; the error is printed by the caller
+pub async fn run(command: CliCommand, config: &Config, read_only: bool) -> Result<(), String> {
+    match command {
+        CliCommand::Get { batch: true, json, format, .. } => get_batch(json, format.as_deref(), config, read_only).await,
+        CliCommand::Get { city, json, oneline, format, fail_if, .. } => {
+            let output = match (json, oneline, format) {
+                (true, ..) => Output::Json,
+                (_, true, _) => Output::Oneline,
+                (_, _, Some(format)) => Output::Format(format),
+                _ => Output::Text,
+            };
+            get(&city.join(" "), &output, &fail_if, config, read_only).await
+        }
+        CliCommand::Forecast { city, days, json, ics } => forecast(&city.join(" "), days, json, ics.as_deref(), config).await,
+        CliCommand::Locations { action } => locations(action, config, read_only).await,
+        CliCommand::Webhooks { action: WebhooksCommand::Test { dry_run } } => test_webhooks(config, dry_run).await,
+        CliCommand::Gazetteer { action: GazetteerCommand::Download } => download_gazetteer(read_only).await,
+        CliCommand::Route { file, speed, start, every, profile } => {
+            route(&file, speed, start.as_deref(), every, profile, config).await
+        }
+        CliCommand::Schema => {
+            println!("{}", serde_json::to_string_pretty(&schema::json_schema()).map_err(|e| e.to_string())?);
+            Ok(())
+        }
+        CliCommand::Tui(_) | CliCommand::Completions { .. } => Ok(()),
+    }
+}
+
+async fn fetch_and_record(city: &str, config: &Config, read_only: bool) -> Result<WeatherData, String> {
+    let query = SearchQuery::parse(city, config.default_country.as_deref());
+    let data = api::fetch_weather(&query, nearby_place(&query)).await?;
+    if config.record_observations && !read_only {
+        records::record_observation(&Observation::from_weather(&data)).map_err(|e| e.to_string())?;
+    }
+    Ok(WeatherData { units: units_for(city, config), ..data })
+}
+
+// A favorite shows in its own units here too
+fn units_for(city: &str, config: &Config) -> Units {
+    history::load_history()
+        .ok()
+        .and_then(|entries| entries.into_iter().find(|e| e.query.eq_ignore_ascii_case(city.trim()))?.units)
+        .unwrap_or(config.units)
+}
+
+enum Output {
+    Text,
+    Json,
+    Oneline,
+    Format(String),
+}
+
+// What `--format` can use, in the order the error message lists them
+const PLACEHOLDERS: [&str; 15] = [
+    "city",
+    "country",
+    "temp",
+    "feels",
+    "unit",
+    "icon",
+    "description",
+    "summary",
+    "wind",
+    "wind_unit",
+    "humidity",
+    "pressure",
+    "precipitation",
+    "uv",
+    "time",
+];
+
+fn placeholders(data: &WeatherData, glyphs: Glyphs) -> Vec<(&'static str, String)> {
+    let current = &data.weather.current;
+    let units = data.units;
+    let values = [
+        data.location.label(),
+        data.location.country.clone(),
+        format!("{:.0}", units.temperature(current.temperature_2m)),
+        format!("{:.0}", units.temperature(current.apparent_temperature)),
+        units.temperature_unit().to_string(),
+        Icon::sky(current.weather_code).glyph(glyphs).to_string(),
+        weather_code_to_description(current.weather_code).to_string(),
+        summary::sentence(&data.weather, data.units),
+        format!("{:.0}", units.wind_speed(current.wind_speed_10m)),
+        units.wind_speed_unit().to_string(),
+        current.relative_humidity_2m.to_string(),
+        format!("{:.0}", current.pressure_msl),
+        format!("{:.1}", current.precipitation),
+        format!("{:.0}", current.uv_index),
+        current.time.replace('T', " "),
+    ];
+    PLACEHOLDERS.into_iter().zip(values).collect()
+}
+
+// A typo in the format is worth knowing about before any lookups go out
+fn check_format(format: &str) -> Result<(), String> {
+    template::format(format, &PLACEHOLDERS.map(|name| (name, ""))).map(|_| ())
+}
+
+async fn get(city: &str, output: &Output, fail_if: &[Predicate], config: &Config, read_only: bool) -> Result<(), String> {
+    if let Output::Format(format) = output {
+        check_format(format)?;
+    }
+    let data = fetch_and_record(city, config, read_only).await?;
+    // The weather still prints, and a script that only wants the status can send it to /dev/null
+    let held: Vec<String> = fail_if.iter().filter_map(|p| p.check(&data)).collect();
+    print_weather(&data, output, config.glyphs)?;
+    if !held.is_empty() {
+        eprintln!("{}", held.join(", "));
+        std::process::exit(CONDITION_HELD);
+    }
+    Ok(())
+}
+
+fn print_weather(data: &WeatherData, output: &Output, glyphs: Glyphs) -> Result<(), String> {
+    let current = &data.weather.current;
+    let units = data.units;
+    match output {
+        Output::Json => {
+            println!("{}", data.to_json());
+            return Ok(());
+        }
+        Output::Oneline => {
+            let temperature = units.temperature(current.temperature_2m);
+            println!("{} {:.0}{}: {}", data.location.label(), temperature, units.temperature_unit(), summary::sentence(&data.weather, units));
+            return Ok(());
+        }
+        Output::Format(format) => {
+            println!("{}", template::format(format, &placeholders(data, glyphs))?);
+            return Ok(());
+        }
+        Output::Text => {}
+    }
+
+    println!("{}", place_name(&data.location));
+    println!("{}", summary::sentence(&data.weather, data.units));
+    println!(
+        "{}, {:.1}{} (feels like {:.1}{})",
+        weather_code_to_description(current.weather_code),
+        units.temperature(current.temperature_2m),
+        units.temperature_unit(),
+        units.temperature(current.apparent_temperature),
+        units.temperature_unit()
+    );
+    println!(
+        "Humidity {}%, wind {:.1} {}, pressure {:.1} {}, precipitation {:.1} mm",
+        current.relative_humidity_2m,
+        units.wind_speed(current.wind_speed_10m),
+        units.wind_speed_unit(),
+        current.pressure_msl,
+        data.weather.current_units.pressure_msl,
+        current.precipitation
+    );
+    Ok(())
+}
+
+// Typed coordinates are named from the city list when it has something close, as in the TUI
+fn nearby_place(query: &SearchQuery) -> Option<GeoLocation> {
+    let (latitude, longitude) = query.coordinates?;
+    gazetteer::load().ok()??.nearest(latitude, longitude, gazetteer::NEAREST_PLACE_KM)
+}
+
+async fn locate(query: &SearchQuery) -> Result<GeoLocation, String> {
+    match (nearby_place(query), query.coordinates) {
+        (Some(location), _) => Ok(location),
+        (None, Some((latitude, longitude))) => Ok(api::reverse_geocode(latitude, longitude).await),
+        (None, None) => Ok(api::geocode(query).await?),
+    }
+}
+
+// A city a line, skipping blanks and # comments. A line that can't be read stops the lot, rather than
+// quietly leaving off everything after it
+fn read_cities(input: impl BufRead) -> Result<Vec<String>, String> {
+    let mut cities = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| format!("Couldn't read line {} of the city list: {}", i + 1, e))?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            cities.push(line.to_string());
+        }
+    }
+    Ok(cities)
+}
+
+// Cities from stdin, answered in input order, one line per city; a city that fails gets a line saying so
+// rather than stopping the rest
+async fn get_batch(as_json: bool, format: Option<&str>, config: &Config, read_only: bool) -> Result<(), String> {
+    if let Some(format) = format {
+        check_format(format)?;
+    }
+    let cities = read_cities(io::stdin().lock())?;
+    // As many at once as the TUI allows, and no more than ten starts a second, under Open-Meteo's free limit
+    let in_flight = config.max_concurrent_fetches.unwrap_or(4).max(1);
+    let started = tokio::time::Instant::now();
+    let mut answers = stream::iter(cities.iter().enumerate())
+        .map(|(i, city)| async move {
+            tokio::time::sleep_until(started + BATCH_SPACING * i as u32).await;
+            (city, fetch_and_record(city, config, read_only).await)
+        })
+        .buffered(in_flight);
+
+    let mut failed = 0;
+    while let Some((city, result)) = answers.next().await {
+        match (result, as_json, format) {
+            (Ok(data), true, _) => {
+                let current = schema::Current { query: Some(city.clone()), ..schema::Current::from(&data) };
+                println!("{}", json!(current));
+            }
+            (Ok(data), false, Some(format)) => {
+                println!("{}\t{}", city, template::format(format, &placeholders(&data, config.glyphs))?);
+            }
+            (Ok(data), false, None) => {
+                let temperature = data.units.temperature(data.weather.current.temperature_2m);
+                let unit = data.units.temperature_unit();
+                println!("{}\t{:.0}{}: {}", city, temperature, unit, summary::sentence(&data.weather, data.units));
+            }
+            (Err(e), true, _) => {
+                failed += 1;
+                println!("{}", json!(schema::BatchError::new(city, &e)));
+            }
+            (Err(e), false, _) => {
+                failed += 1;
+                println!("{}\terror: {}", city, e);
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} cities failed", failed, cities.len()));
+    }
+    Ok(())
+}
+
+async fn forecast(city: &str, days: u8, as_json: bool, ics: Option<&Path>, config: &Config) -> Result<(), String> {
+    let query = SearchQuery::parse(city, config.default_country.as_deref());
+    let location = locate(&query).await?;
+    let daily = api::fetch_daily(&location, days).await?;
+    let units = units_for(city, config);
+    let value_at = |series: &TimeSeries<f64>, date: &str| series.at(date).copied().unwrap_or(f64::NAN);
+
+    if let Some(path) = ics {
+        // Rules only have an hourly forecast to go on for the first three days
+        let mut windows = Vec::new();
+        if !config.rules.is_empty() {
+            if let Some(hourly) = api::fetch_forecast(&location).await?.hourly {
+                windows.extend(config.rules.iter().flat_map(|rule| rules::windows(rule, &hourly)));
+            }
+        }
+        windows.sort_by_key(|w| w.start);
+        let calendar = ics::calendar(&place_name(&location), &location, &daily, units, &windows, Utc::now());
+        fs::write(path, calendar).map_err(|e| format!("Couldn't write {}: {}", path.display(), e))?;
+        println!("Wrote {} days and {} alert windows to {}", daily.weather_code.len(), windows.len(), path.display());
+        return Ok(());
+    }
+
+    if as_json {
+        println!("{}", json!(schema::Forecast::new(&location, &daily)));
+        return Ok(());
+    }
+
+    println!("{}", place_name(&location));
+    for (date, code) in daily.weather_code.iter() {
+        println!(
+            "{}  {:<24}{:>6.1} / {:>5.1}{}  {:>5.1} {}  wind {:.0} {}",
+            date,
+            weather_code_to_description(*code),
+            units.temperature(value_at(&daily.temperature_min, date)),
+            units.temperature(value_at(&daily.temperature_max, date)),
+            units.temperature_unit(),
+            value_at(&daily.precipitation, date),
+            daily.precipitation.unit,
+            units.wind_speed(value_at(&daily.wind_speed_max, date)),
+            units.wind_speed_unit()
+        );
+    }
+    Ok(())
+}
+
+async fn locations(action: LocationsCommand, config: &Config, read_only: bool) -> Result<(), String> {
+    let mut entries = history::load_history().map_err(|e| e.to_string())?;
+    match action {
+        LocationsCommand::List => {
+            for entry in &entries {
+                match &entry.location {
+                    Some(location) => println!(
+                        "{}\t{}\t{:.4},{:.4}",
+                        entry.query,
+                        place_name(location),
+                        location.latitude,
+                        location.longitude
+                    ),
+                    None => println!("{}", entry.query),
+                }
+            }
+            return Ok(());
+        }
+        LocationsCommand::Add { city } => {
+            refuse_if_read_only(read_only)?;
+            let city = city.join(" ");
+            let query = SearchQuery::parse(&city, config.default_country.as_deref());
+            let location = locate(&query).await?;
+            println!("Added {}", place_name(&location));
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            history::push_entry(&mut entries, HistoryEntry::new(city, timestamp, Some(location)));
+        }
+        LocationsCommand::Rm { query } => {
+            refuse_if_read_only(read_only)?;
+            let query = query.join(" ");
+            let before = entries.len();
+            entries.retain(|e| !e.query.eq_ignore_ascii_case(&query));
+            if entries.len() == before {
+                return Err(format!("'{}' is not a saved location", query));
+            }
+            println!("Removed {}", query);
+        }
+        LocationsCommand::Import { file } => {
+            refuse_if_read_only(read_only)?;
+            let text = fs::read_to_string(&file).map_err(|e| format!("Couldn't read {}: {}", file.display(), e))?;
+            let waypoints = waypoints::parse(&text)?;
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let mut seen: Vec<String> = Vec::new();
+            let mut imported = Vec::new();
+            for mut location in waypoints {
+                // Two camps both called "Camp" need telling apart in the history
+                let base = location.name.clone();
+                let mut n = 1;
+                while seen.contains(&location.name) {
+                    n += 1;
+                    location.name = format!("{} ({})", base, n);
+                }
+                seen.push(location.name.clone());
+                imported.push(location);
+            }
+            // Pushed last first, so the route reads top to bottom in the history and on the dashboard
+            for location in imported.iter().rev() {
+                let entry = HistoryEntry::new(location.name.clone(), timestamp, Some(location.clone()));
+                history::push_entry(&mut entries, HistoryEntry { favorite: true, ..entry });
+            }
+            println!("Imported {} waypoints as favorites", imported.len());
+            if imported.len() > history::MAX_ENTRIES {
+                println!("Only the first {} fit in the history", history::MAX_ENTRIES);
+            }
+            println!("`weather-searcher --kiosk` shows them side by side unless kiosk_locations is set");
+        }
+    }
+    history::save_history(&entries).map_err(|e| e.to_string())
+}
+
+async fn route(file: &Path, speed: f64, start: Option<&str>, every: Option<f64>, profile: bool, config: &Config) -> Result<(), String> {
+    if speed <= 0.0 || every.is_some_and(|km| km <= 0.0) {
+        return Err("--speed and --every need to be more than 0".to_string());
+    }
+    let text = fs::read_to_string(file).map_err(|e| format!("Couldn't read {}: {}", file.display(), e))?;
+    let track = waypoints::track(&text)?;
+    // Forecast times are local to each point, so the start is taken as the time on the route
+    let now = Local::now().naive_local();
+    let start = match start {
+        None => now,
+        Some(s) => NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M").map(|t| now.date().and_time(t)))
+            .map_err(|_| format!("'{}' isn't HH:MM or YYYY-MM-DDTHH:MM", s))?,
+    };
+    let stops = route::stops(&track, speed, start, every.unwrap_or(speed));
+    let Some(finish) = stops.last() else {
+        return Ok(());
+    };
+    let hours = (finish.arrival - start).num_minutes();
+    println!("{:.1} km at {:.0} km/h: {}h{:02} from {}", finish.km, speed, hours / 60, hours % 60, start.format("%a %H:%M"));
+
+    let points: Vec<_> = stops.iter().map(|s| (s.latitude, s.longitude, s.elevation)).collect();
+    let forecasts = api::fetch_route(&points).await?;
+    let units = config.units;
+    let mut chart = Vec::new();
+    for (stop, forecast) in stops.iter().zip(&forecasts) {
+        let hour = stop.arrival.format("%Y-%m-%dT%H:00").to_string();
+        let at = |series: &TimeSeries<f64>| series.at(&hour).copied().unwrap_or(f64::NAN);
+        let lead = format!("{:>6.1} km  {:>5.0} m  {}", stop.km, forecast.elevation, stop.arrival.format("%a %H:%M"));
+        let temperature = at(&forecast.temperature);
+        if temperature.is_nan() {
+            println!("{}  outside the 3-day forecast", lead);
+        } else {
+            println!(
+                "{}  {:>5.1}{}  {:>4.1} mm  {:>3.0}%  wind {:.0} {}",
+                lead,
+                units.temperature(temperature),
+                units.temperature_unit(),
+                at(&forecast.precipitation),
+                at(&forecast.precipitation_probability),
+                units.wind_speed(at(&forecast.wind_speed)),
+                units.wind_speed_unit()
+            );
+        }
+        chart.push(ProfilePoint { km: stop.km, elevation: forecast.elevation, temperature, wind: at(&forecast.wind_speed) });
+    }
+    if profile {
+        let width = crossterm::terminal::size().map_or(80, |(width, _)| width);
+        let area = Rect::new(0, 0, width, 16);
+        let mut buf = Buffer::empty(area);
+        view::route_profile(&chart, units).render(area, &mut buf);
+        if config.glyphs == Glyphs::Ascii {
+            glyphs::asciify(&mut buf);
+        }
+        print_buffer(&buf, io::stdout().is_terminal()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// A frame drawn off-screen, printed line by line like any other output
+fn print_buffer(buf: &Buffer, colour: bool) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    for y in 0..buf.area.height {
+        for x in 0..buf.area.width {
+            let cell = &buf[(x, y)];
+            if colour {
+                queue!(out, SetForegroundColor(cell.fg.into()))?;
+            }
+            queue!(out, Print(cell.symbol()))?;
+        }
+        if colour {
+            queue!(out, ResetColor)?;
+        }
+        queue!(out, Print("\n"))?;
+    }
+    out.flush()
+}
+
+async fn test_webhooks(config: &Config, dry_run: bool) -> Result<(), String> {
+    if config.webhooks.is_empty() {
+        return Err("No webhooks configured. Add webhooks = [\"https://...\"] to config.toml".to_string());
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let payload = json!({
+        "rule": "Test",
+        "message": "Test alert from weather-searcher",
+        "test": true,
+        "timestamp": timestamp,
+    });
+
+    if dry_run {
+        println!("{}", serde_json::to_string_pretty(&payload).map_err(|e| e.to_string())?);
+        for url in &config.webhooks {
+            println!("would POST to {}", url);
+        }
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for url in &config.webhooks {
+        match api::post_webhook(url, &payload).await {
+            Ok(()) => println!("ok      {}", url),
+            Err(e) => {
+                failed += 1;
+                println!("failed  {}", e);
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} webhooks failed", failed, config.webhooks.len()));
+    }
+    Ok(())
+}
+
+async fn download_gazetteer(read_only: bool) -> Result<(), String> {
+    if read_only {
+        return Err("Not downloading anything in --read-only mode".to_string());
+    }
+    println!("Downloading from download.geonames.org...");
+    let count = gazetteer::download().await?;
+    println!("Saved {} places to {}", count, gazetteer::get_gazetteer_path().display());
+    Ok(())
+}
+
+fn refuse_if_read_only(read_only: bool) -> Result<(), String> {
+    if read_only {
+        Err("Not changing saved locations in --read-only mode".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn place_name(location: &GeoLocation) -> String {
+    let region = location.admin1.as_ref().map(|r| format!(", {}", r)).unwrap_or_default();
+    // Imported waypoints have no country
+    if location.country.is_empty() {
+        return format!("{}{}", location.label(), region);
+    }
+    format!("{}{} ({})", location.label(), region, location.country)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_batch_skips_blanks_and_comments() {
+        let input = "Berlin\n\n  # the coast\n  Bergen @NO \n#Oslo\n";
+        assert_eq!(read_cities(input.as_bytes()).unwrap(), ["Berlin", "Bergen @NO"]);
+    }
+
+    #[test]
+    fn an_unreadable_line_stops_the_batch() {
+        let e = read_cities(&b"Berlin\n\xff\xfe\nBergen\n"[..]).unwrap_err();
+        assert!(e.starts_with("Couldn't read line 2 of the city list"), "{}", e);
+    }
+
+    #[test]
+    fn batch_takes_no_city_and_no_oneline() {
+        assert!(Cli::try_parse_from(["weather-searcher", "get", "--batch", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["weather-searcher", "get", "--batch", "Berlin"]).is_err());
+        assert!(Cli::try_parse_from(["weather-searcher", "get", "--batch", "--oneline"]).is_err());
+    }
+
+    #[test]
+    fn units_go_anywhere_and_split_on_commas() {
+        let cli = Cli::try_parse_from(["weather-searcher", "forecast", "denver", "--units", "f,mph"]).unwrap();
+        assert_eq!(cli.units, ["f", "mph"]);
+        let cli = Cli::try_parse_from(["weather-searcher", "--units", "imperial", "denver"]).unwrap();
+        assert_eq!((cli.units, cli.city), (vec!["imperial".to_string()], vec!["denver".to_string()]));
+    }
+}
@@ -0,0 +1,143 @@
+// Not every terminal takes 24-bit colour. A hex colour sent to one that doesn't comes out as whatever
+// the terminal makes of the escape, often black on black, so the finished frame is brought down to
+// the nearest colours the terminal does have, the same way glyphs.rs rewrites its characters
+use ratatui::{buffer::Buffer, style::Color};
+
+use crate::config::ColorDepth;
+
+// COLORTERM is the only thing that promises 24-bit; a 256color TERM promises the xterm palette
+pub fn resolve(setting: ColorDepth, colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+    if setting != ColorDepth::Auto {
+        return setting;
+    }
+    if matches!(colorterm, Some("truecolor" | "24bit")) {
+        ColorDepth::TrueColor
+    } else if term.is_some_and(|t| t.contains("256color")) {
+        ColorDepth::Ansi256
+    } else {
+        ColorDepth::Ansi16
+    }
+}
+
+pub fn degrade(buf: &mut Buffer, depth: ColorDepth) {
+    if matches!(depth, ColorDepth::Auto | ColorDepth::TrueColor) {
+        return;
+    }
+    for cell in buf.content.iter_mut() {
+        let (fg, bg) = (down(cell.fg, depth), down(cell.bg, depth));
+        // Two colours that were told apart in 24 bits can land on the same one here
+        let fg = if fg == bg && cell.fg != cell.bg && bg != Color::Reset {
+            if luma(rgb(bg)) > 128.0 { Color::Black } else { Color::White }
+        } else {
+            fg
+        };
+        cell.fg = fg;
+        cell.bg = bg;
+    }
+}
+
+// As CSS, for the HTML snapshot; None for the terminal's own default
+pub fn hex(color: Color) -> Option<String> {
+    if color == Color::Reset {
+        return None;
+    }
+    let (r, g, b) = rgb(color);
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+fn down(color: Color, depth: ColorDepth) -> Color {
+    match (color, depth) {
+        (Color::Rgb(..), ColorDepth::Ansi256) => Color::Indexed(nearest_indexed(rgb(color))),
+        (Color::Rgb(..) | Color::Indexed(16..), ColorDepth::Ansi16) => nearest_named(rgb(color)),
+        (Color::Indexed(n), ColorDepth::Ansi16) => NAMED[n as usize].0,
+        _ => color,
+    }
+}
+
+// xterm's defaults for the first sixteen; every terminal tweaks them a little, which is close enough
+const NAMED: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// The six steps of each channel in the 6x6x6 cube at 16..=231
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(n @ 0..=15) => NAMED[n as usize].1,
+        Color::Indexed(n @ 16..=231) => {
+            let n = n - 16;
+            (CUBE[(n / 36) as usize], CUBE[(n / 6 % 6) as usize], CUBE[(n % 6) as usize])
+        }
+        Color::Indexed(n) => {
+            let grey = 8 + 10 * (n - 232);
+            (grey, grey, grey)
+        }
+        Color::Reset => (0, 0, 0),
+        named => NAMED.iter().find(|(c, _)| *c == named).map_or((0, 0, 0), |&(_, rgb)| rgb),
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn luma((r, g, b): (u8, u8, u8)) -> f64 {
+    0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64
+}
+
+// Only the cube and the grey ramp: the first sixteen are whatever the user's terminal theme says
+fn nearest_indexed(color: (u8, u8, u8)) -> u8 {
+    (16..=255).min_by_key(|&n| distance(color, rgb(Color::Indexed(n)))).unwrap_or(16)
+}
+
+fn nearest_named(color: (u8, u8, u8)) -> Color {
+    NAMED.iter().min_by_key(|(_, rgb)| distance(color, *rgb)).map_or(Color::White, |&(c, _)| c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn depth_comes_from_colorterm_then_term() {
+        assert_eq!(resolve(ColorDepth::Auto, Some("truecolor"), Some("xterm-256color")), ColorDepth::TrueColor);
+        assert_eq!(resolve(ColorDepth::Auto, None, Some("screen-256color")), ColorDepth::Ansi256);
+        assert_eq!(resolve(ColorDepth::Auto, None, Some("linux")), ColorDepth::Ansi16);
+        assert_eq!(resolve(ColorDepth::Ansi16, Some("truecolor"), None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn hex_colours_land_on_their_neighbours_and_stay_readable() {
+        assert_eq!(down(Color::Rgb(0xff, 0x87, 0x00), ColorDepth::Ansi256), Color::Indexed(208));
+        assert_eq!(down(Color::Rgb(0x30, 0x30, 0x30), ColorDepth::Ansi256), Color::Indexed(236));
+        assert_eq!(down(Color::Rgb(0xe0, 0x10, 0x10), ColorDepth::Ansi16), Color::Red);
+        assert_eq!(down(Color::Indexed(4), ColorDepth::Ansi16), Color::Blue);
+        assert_eq!(down(Color::Cyan, ColorDepth::Ansi16), Color::Cyan);
+
+        // Dark grey on near-black: one colour in sixteen, so the text goes white
+        let area = Rect::new(0, 0, 1, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_style(area, Style::default().fg(Color::Rgb(0x20, 0x20, 0x28)).bg(Color::Rgb(0x10, 0x10, 0x10)));
+        degrade(&mut buf, ColorDepth::Ansi16);
+        assert_eq!((buf[(0, 0)].fg, buf[(0, 0)].bg), (Color::White, Color::Black));
+    }
+}
@@ -0,0 +1,46 @@
+// How sticky the air feels. Relative humidity depends on the temperature; the dew point (°C) doesn't,
+// which makes it the better guide to whether you'll be mopping your brow
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comfort {
+    Dry,
+    Comfortable,
+    Muggy,
+    Oppressive,
+}
+
+// Where each band starts, driest first
+pub const BANDS: [(f64, Comfort); 4] = [
+    (f64::NEG_INFINITY, Comfort::Dry),
+    (10.0, Comfort::Comfortable),
+    (16.0, Comfort::Muggy),
+    (21.0, Comfort::Oppressive),
+];
+
+impl Comfort {
+    pub fn from_dew_point(dew_point: f64) -> Comfort {
+        BANDS.iter().rev().find(|(from, _)| dew_point >= *from).map_or(Comfort::Dry, |&(_, comfort)| comfort)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Comfort::Dry => "dry",
+            Comfort::Comfortable => "comfortable",
+            Comfort::Muggy => "muggy",
+            Comfort::Oppressive => "oppressive",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bands_start_at_their_edges() {
+        assert_eq!(Comfort::from_dew_point(-5.0), Comfort::Dry);
+        assert_eq!(Comfort::from_dew_point(9.9), Comfort::Dry);
+        assert_eq!(Comfort::from_dew_point(10.0), Comfort::Comfortable);
+        assert_eq!(Comfort::from_dew_point(16.0), Comfort::Muggy);
+        assert_eq!(Comfort::from_dew_point(24.0), Comfort::Oppressive);
+    }
+}
@@ -0,0 +1,497 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{fs, path::PathBuf, sync::OnceLock};
+
+use crate::theme::Theme;
+
+use crate::units::Units;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    // ISO 3166-1 alpha-2 code applied to searches that don't name a country
+    pub default_country: Option<String>,
+    // Re-fetch the displayed location this often while it's on screen
+    pub auto_refresh_minutes: Option<u64>,
+    // Show the current temperature in large block digits above the details
+    pub big_temperature: bool,
+    // Locations shown side by side with --kiosk
+    pub kiosk_locations: Vec<String>,
+    // Append every fetched reading to a local SQLite archive
+    pub record_observations: bool,
+    // default, nord, gruvbox or mono; --theme beats it for one run
+    pub theme: Theme,
+    // List USGS earthquakes from the past week within this many km of the location
+    pub earthquake_radius_km: Option<f64>,
+    // Airports offered for the pre-flight brief
+    pub airports: Vec<Airport>,
+    // Ring the terminal bell when severe weather shows up for the displayed location
+    pub severe_bell: bool,
+    // Show severe weather as a steady high-contrast banner instead of flashing it
+    pub static_alert_banner: bool,
+    // Background toasts and bells are logged instead of shown during these hours
+    pub quiet_hours: Option<QuietHours>,
+    // Conditions that raise an alert when a reading crosses them
+    pub rules: Vec<Rule>,
+    // URLs that get a JSON POST whenever a rule fires
+    pub webhooks: Vec<String>,
+    // Push alerts to a phone through ntfy
+    pub ntfy: Option<NtfyConfig>,
+    // Shell commands run in the background on events
+    pub hooks: Hooks,
+    // External programs that add panels to the weather view
+    pub plugins: Vec<Plugin>,
+    // Run by `o` on the weather view with {lat} and {lon} filled in; OpenStreetMap in the browser if unset
+    pub map_command: Option<String>,
+    // The page `O` opens, with {lat}, {lon} and {name} filled in; Open-Meteo's for the place if unset
+    pub forecast_page: Option<String>,
+    // Sent with every request; put a way to reach you in here if you fetch a lot
+    pub user_agent: Option<String>,
+    // A place name or "lat, lon"; the header shows how far away the displayed place is
+    pub home: Option<String>,
+    // How many requests may be in flight at once (4 if unset); the rest wait their turn
+    pub max_concurrent_fetches: Option<usize>,
+    // Fetch forecasts in Open-Meteo's FlatBuffers format instead of JSON
+    pub flatbuffers: bool,
+    // What to wear or bring, shown under the summary; replaces the built-in table when set
+    pub gear: Option<Vec<Gear>>,
+    // Hourly 0-10 scores shown under the hourly table, by built-in name or spelled out; bike and run if unset
+    #[serde(deserialize_with = "activities_by_name")]
+    pub activities: Option<Vec<Activity>>,
+    // Heights in metres for the mountain view's snowfall table; 1000, 2000 and 3000 if unset
+    pub mountain_elevations: Vec<f64>,
+    // Tides from worldtides.info where NOAA has no station nearby, i.e. outside the US
+    pub worldtides_key: Option<String>,
+    // Sea temperature in °C you'd swim in without a wetsuit, drawn across the marine view's chart; 16 if unset
+    pub swim_temperature: Option<f64>,
+    // How far out the hyperlocal view looks to each side, in km; 5 if unset
+    pub nearby_offset_km: Option<f64>,
+    // Ask GitHub once at startup whether there's a newer release
+    pub check_for_updates: bool,
+    // Rain and snow falling through the weather view and kiosk tiles when it's coming down there
+    pub weather_effects: bool,
+    // "compact" shows the weather as one small card, e.g. for a tmux popup; "large" in large print
+    pub view: ViewStyle,
+    // "ascii" draws charts, borders and icons with plain ASCII for consoles whose fonts lack them;
+    // "no_emoji" keeps the lines and braille and only swaps the weather icons
+    pub glyphs: Glyphs,
+    // "true_color", "ansi256" or "ansi16": what the terminal can show, so colours are brought down to it
+    pub colors: ColorDepth,
+    // °C and km/h unless set, e.g. units = { temperature = "fahrenheit", wind_speed = "mph" };
+    // a favorite can have its own with `:units`
+    pub units: Units,
+}
+
+impl Config {
+    pub fn gear(&self) -> &[Gear] {
+        static BUILT_IN: OnceLock<Vec<Gear>> = OnceLock::new();
+        self.gear.as_deref().unwrap_or_else(|| BUILT_IN.get_or_init(built_in_gear))
+    }
+
+    pub fn activities(&self) -> &[Activity] {
+        static BUILT_IN: OnceLock<Vec<Activity>> = OnceLock::new();
+        self.activities.as_deref().unwrap_or_else(|| {
+            BUILT_IN.get_or_init(|| DEFAULT_ACTIVITIES.iter().filter_map(|name| built_in_activity(name)).collect())
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    // Run through the shell; gets the weather as JSON on stdin, prints a panel as JSON
+    pub command: String,
+}
+
+// Each hook gets WEATHER_* environment variables and the full event as JSON on stdin
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Hooks {
+    pub on_fetch: Option<String>,
+    pub on_alert: Option<String>,
+    pub on_error: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NtfyConfig {
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    pub topic: String,
+    // Access token for protected topics
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewStyle {
+    #[default]
+    Full,
+    // Icon, temperature, feels-like, wind and the next rain, and nothing else
+    Compact,
+    // Big digits, bold short labels with a blank line between them, and no tables
+    Large,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Glyphs {
+    // Worked out from TERM and the locale at startup
+    #[default]
+    Auto,
+    Unicode,
+    NoEmoji,
+    Ascii,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorDepth {
+    // Worked out from COLORTERM and TERM at startup
+    #[default]
+    Auto,
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub metric: Metric,
+    #[serde(default)]
+    pub below: Option<f64>,
+    #[serde(default)]
+    pub above: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    Temperature,
+    ApparentTemperature,
+    WindSpeed,
+    Precipitation,
+    Humidity,
+    Pressure,
+    UvIndex,
+    // Millimetres forecast over the next 12 hours
+    RainAhead,
+    // 0 none, 1 possible, 2 likely, over the next 18 hours
+    BlackIce,
+}
+
+// Suggested while the metric is above `above` and below `below`; either bound can be left out
+#[derive(Deserialize, Debug, Clone)]
+pub struct Gear {
+    pub item: String,
+    pub metric: Metric,
+    #[serde(default)]
+    pub below: Option<f64>,
+    #[serde(default)]
+    pub above: Option<f64>,
+}
+
+// Something scored hour by hour; every factor that's off its ideal range pulls the score down
+#[derive(Deserialize, Debug, Clone)]
+pub struct Activity {
+    pub name: String,
+    pub factors: Vec<Factor>,
+}
+
+// Perfect between `above` and `below`, worthless `tolerance` units beyond them. A weight above 1
+// makes the factor count for more, e.g. rain for a bike ride
+#[derive(Deserialize, Debug, Clone)]
+pub struct Factor {
+    pub metric: HourlyMetric,
+    #[serde(default)]
+    pub above: Option<f64>,
+    #[serde(default)]
+    pub below: Option<f64>,
+    pub tolerance: f64,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum HourlyMetric {
+    Temperature,
+    WindSpeed,
+    Precipitation,
+    // Percent
+    RainChance,
+    // European AQI
+    AirQuality,
+    // Percent
+    Humidity,
+    // Percent of the sky
+    CloudCover,
+    // Minutes of sun in the hour
+    Sunshine,
+    // 1 while the sun is up, 0 after dark
+    Daylight,
+    // Percent of the moon lit, whether or not it's up
+    Moon,
+}
+
+// Shown when the config doesn't list any
+const DEFAULT_ACTIVITIES: [&str; 2] = ["bike", "run"];
+
+// The activities a config can name instead of spelling out; a new one is a name here and an arm below
+pub const BUILT_IN_ACTIVITIES: [&str; 4] = ["bike", "run", "laundry", "stargazing"];
+
+fn built_in_activity(name: &str) -> Option<Activity> {
+    let factor = |metric, above, below, tolerance, weight| Factor { metric, above, below, tolerance, weight };
+    let (name, factors) = match name {
+        "bike" => (
+            "Bike",
+            vec![
+                factor(HourlyMetric::Temperature, Some(12.0), Some(24.0), 15.0, 1.0),
+                factor(HourlyMetric::WindSpeed, None, Some(15.0), 30.0, 1.5),
+                factor(HourlyMetric::RainChance, None, Some(10.0), 60.0, 1.5),
+                factor(HourlyMetric::AirQuality, None, Some(40.0), 60.0, 1.0),
+            ],
+        ),
+        "run" => (
+            "Run",
+            vec![
+                factor(HourlyMetric::Temperature, Some(5.0), Some(18.0), 15.0, 1.0),
+                factor(HourlyMetric::WindSpeed, None, Some(20.0), 35.0, 1.0),
+                factor(HourlyMetric::RainChance, None, Some(20.0), 70.0, 1.0),
+                factor(HourlyMetric::AirQuality, None, Some(40.0), 60.0, 1.5),
+            ],
+        ),
+        // Washing on the line: dry air, a breeze that doesn't blow it away, sun, and no rain
+        "laundry" => (
+            "Laundry",
+            vec![
+                factor(HourlyMetric::Humidity, None, Some(55.0), 40.0, 1.0),
+                factor(HourlyMetric::WindSpeed, Some(8.0), Some(35.0), 20.0, 0.5),
+                factor(HourlyMetric::Sunshine, Some(40.0), None, 40.0, 0.5),
+                factor(HourlyMetric::RainChance, None, Some(10.0), 40.0, 2.0),
+            ],
+        ),
+        // Clear, moonless, clean air, and dark at all
+        "stargazing" => (
+            "Stars",
+            vec![
+                factor(HourlyMetric::Daylight, None, Some(0.0), 1.0, 1.0),
+                factor(HourlyMetric::CloudCover, None, Some(15.0), 60.0, 2.0),
+                factor(HourlyMetric::Moon, None, Some(25.0), 75.0, 1.0),
+                factor(HourlyMetric::AirQuality, None, Some(30.0), 70.0, 0.5),
+            ],
+        ),
+        _ => return None,
+    };
+    Some(Activity { name: name.to_string(), factors })
+}
+
+// Each entry is either a built-in by name or a whole activity
+fn activities_by_name<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<Activity>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Name(String),
+        Custom(Activity),
+    }
+    Vec::<Entry>::deserialize(deserializer)?
+        .into_iter()
+        .map(|entry| match entry {
+            Entry::Custom(activity) => Ok(activity),
+            Entry::Name(name) => built_in_activity(&name).ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "unknown activity '{}', the built-in ones are {}",
+                    name,
+                    BUILT_IN_ACTIVITIES.join(", ")
+                ))
+            }),
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn built_in_gear() -> Vec<Gear> {
+    let gear = |item: &str, metric, above, below| Gear { item: item.to_string(), metric, above, below };
+    vec![
+        gear("warm coat", Metric::ApparentTemperature, None, Some(5.0)),
+        gear("hat and gloves", Metric::ApparentTemperature, None, Some(0.0)),
+        gear("jacket", Metric::ApparentTemperature, Some(5.0), Some(14.0)),
+        gear("umbrella", Metric::RainAhead, Some(1.0), None),
+        gear("windproof layer", Metric::WindSpeed, Some(30.0), None),
+        gear("sunscreen", Metric::UvIndex, Some(5.0), None),
+    ]
+}
+
+// Local times as "HH:MM", kept as minutes past midnight; a start later than the end wraps past midnight
+#[derive(Deserialize, Debug, Clone)]
+pub struct QuietHours {
+    #[serde(deserialize_with = "clock")]
+    pub start: u32,
+    #[serde(deserialize_with = "clock")]
+    pub end: u32,
+}
+
+impl QuietHours {
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+// A typo here is refused at startup rather than quietly never being quiet
+fn clock<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_clock(&s).ok_or_else(|| de::Error::custom(format!("'{}' isn't a time like \"22:30\"", s)))
+}
+
+fn parse_clock(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Airport {
+    // ICAO code, e.g. "EDDB"
+    pub icao: String,
+    // True heading of the runway in use (METAR winds are true too), for the crosswind component
+    #[serde(default)]
+    pub runway_heading: Option<f64>,
+}
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+// Selects a separate set of config and history files; called once at startup
+pub fn set_profile(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid profile name '{}': use letters, digits, '-' and '_' only",
+            name
+        ));
+    }
+    PROFILE
+        .set(name.to_string())
+        .map_err(|_| "Profile already set".to_string())
+}
+
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+// Where a profile keeps its files; None for the default profile
+pub fn profile_dir() -> Option<PathBuf> {
+    let name = profile()?;
+    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("profiles");
+    path.push(name);
+    Some(path)
+}
+
+pub fn get_config_path() -> PathBuf {
+    if let Some(mut path) = profile_dir() {
+        path.push("config.toml");
+        return path;
+    }
+    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("config.toml");
+    path
+}
+
+// A missing file is the defaults; a broken one is an error, so a typo doesn't quietly switch everything off
+pub fn load_config() -> Result<Config, String> {
+    let path = get_config_path();
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    parse(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// toml's own message quotes the offending line; this names where it is instead
+pub fn parse(content: &str) -> Result<Config, String> {
+    toml::from_str(content).map_err(|e: toml::de::Error| match e.span() {
+        Some(span) => {
+            let before = &content[..span.start.min(content.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+            format!("line {}, column {}: {}", line, column, e.message())
+        }
+        None => e.message().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet(start: &str, end: &str) -> QuietHours {
+        let config = parse(&format!("[quiet_hours]\nstart = \"{}\"\nend = \"{}\"\n", start, end)).unwrap();
+        config.quiet_hours.unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let lunch = quiet("12:00", "13:30");
+        assert!(!lunch.contains(11 * 60 + 59));
+        assert!(lunch.contains(12 * 60));
+        assert!(lunch.contains(13 * 60 + 29));
+        assert!(!lunch.contains(13 * 60 + 30));
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let night = quiet("22:00", "07:00");
+        assert!(night.contains(23 * 60));
+        assert!(night.contains(0));
+        assert!(night.contains(6 * 60 + 59));
+        assert!(!night.contains(7 * 60));
+        assert!(!night.contains(12 * 60));
+    }
+
+    #[test]
+    fn a_bad_time_is_an_error_at_load() {
+        let e = parse("big_temperature = true\n[quiet_hours]\nstart = \"22:00\"\nend = \"7am\"\n").unwrap_err();
+        assert!(e.starts_with("line 4, column 7:"), "{}", e);
+        assert!(e.contains("'7am'"), "{}", e);
+        assert!(parse("[quiet_hours]\nstart = \"24:00\"\nend = \"07:00\"\n").is_err());
+    }
+
+    #[test]
+    fn activities_by_name_or_spelled_out() {
+        let config = parse(
+            r#"
+            [[activities]]
+            name = "kite"
+            factors = [{ metric = "wind_speed", above = 20, tolerance = 15 }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.activities()[0].name, "kite");
+        let config = parse("activities = [\"laundry\", \"stargazing\"]\n").unwrap();
+        let names: Vec<&str> = config.activities().iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Laundry", "Stars"]);
+        assert_eq!(Config::default().activities().len(), DEFAULT_ACTIVITIES.len());
+    }
+
+    #[test]
+    fn an_unknown_activity_name_is_an_error_at_load() {
+        let e = parse("activities = [\"bike\", \"skydiving\"]\n").unwrap_err();
+        assert!(e.starts_with("line 1,"), "{}", e);
+        assert!(e.contains("unknown activity 'skydiving'"), "{}", e);
+        assert!(e.contains("bike, run, laundry, stargazing"), "{}", e);
+    }
+}
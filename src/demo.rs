@@ -0,0 +1,298 @@
+use chrono::{Duration as ChronoDuration, Timelike, Utc};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use futures::{stream, Stream, StreamExt};
+use std::{
+    f64::consts::PI,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::api::{
+    CurrentUnits, CurrentWeather, Cyclone, DailyForecast, Earthquake, FetchError, FetchTimings, FlightBrief,
+    FreeFlightForecast, GeoLocation, HourlyForecast, MountainForecast, NearbyPoint, Release, SearchQuery, Source, Tides,
+    WeatherData, WeatherResponse,
+};
+use crate::config::{Config, NtfyConfig};
+use crate::series::TimeSeries;
+use crate::units::Units;
+
+// One place on the tour, picked so every kind of weather screen gets shown at least once
+struct Scene {
+    name: &'static str,
+    country: &'static str,
+    latitude: f64,
+    longitude: f64,
+    // Hours ahead of UTC, near enough; it decides whether it's day or night there
+    utc_offset: i64,
+    // Daily mean and how far the afternoon rises above it
+    temperature: f64,
+    swing: f64,
+    weather_code: u32,
+    wind_speed: f64,
+    // mm per hour while it's raining (or snowing), which is from now for the next six hours
+    precipitation: f64,
+    humidity: f64,
+    pressure: f64,
+}
+
+const SCENES: [Scene; 5] = [
+    Scene {
+        name: "Lisbon", country: "Portugal", latitude: 38.72, longitude: -9.14, utc_offset: 1,
+        temperature: 24.0, swing: 6.0, weather_code: 0, wind_speed: 14.0, precipitation: 0.0, humidity: 45.0, pressure: 1021.0,
+    },
+    Scene {
+        name: "Bergen", country: "Norway", latitude: 60.39, longitude: 5.32, utc_offset: 1,
+        temperature: 9.0, swing: 2.0, weather_code: 63, wind_speed: 22.0, precipitation: 3.5, humidity: 92.0, pressure: 998.0,
+    },
+    Scene {
+        name: "Chicago", country: "United States", latitude: 41.88, longitude: -87.63, utc_offset: -6,
+        temperature: 27.0, swing: 5.0, weather_code: 95, wind_speed: 38.0, precipitation: 12.0, humidity: 78.0, pressure: 1002.0,
+    },
+    Scene {
+        name: "Reykjavik", country: "Iceland", latitude: 64.15, longitude: -21.94, utc_offset: 0,
+        temperature: -4.0, swing: 1.5, weather_code: 73, wind_speed: 45.0, precipitation: 1.2, humidity: 85.0, pressure: 987.0,
+    },
+    Scene {
+        name: "Singapore", country: "Singapore", latitude: 1.29, longitude: 103.85, utc_offset: 8,
+        temperature: 29.0, swing: 3.0, weather_code: 2, wind_speed: 9.0, precipitation: 0.0, humidity: 80.0, pressure: 1009.0,
+    },
+];
+
+impl Scene {
+    fn location(&self) -> GeoLocation {
+        GeoLocation {
+            name: self.name.to_string(),
+            latitude: self.latitude,
+            longitude: self.longitude,
+            country: self.country.to_string(),
+            admin1: None,
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        }
+    }
+
+    // Made up fresh on every fetch so "now" is always now, in the place's own time
+    fn weather(&self) -> WeatherData {
+        let local = Utc::now().naive_utc() + ChronoDuration::hours(self.utc_offset);
+        let midnight = local.date().and_hms_opt(0, 0, 0).unwrap_or(local);
+        let hour_now = local.hour() as i64;
+        let hours: Vec<i64> = (0..48).collect();
+        let time: Vec<String> =
+            hours.iter().map(|&h| (midnight + ChronoDuration::hours(h)).format("%Y-%m-%dT%H:%M").to_string()).collect();
+
+        let day = |h: i64| (6..20).contains(&(h % 24));
+        let temperature = |h: i64| self.temperature + self.swing * ((h % 24 - 9) as f64 / 24.0 * 2.0 * PI).sin();
+        let raining = |h: i64| self.precipitation > 0.0 && (hour_now..hour_now + 6).contains(&h);
+        let series = |f: &dyn Fn(i64) -> f64, unit: &str| TimeSeries::new(time.clone(), hours.iter().map(|&h| f(h)).collect(), unit);
+        let hourly = HourlyForecast {
+            temperature: series(&temperature, "°C"),
+            precipitation: series(&|h| if raining(h) { self.precipitation } else { 0.0 }, "mm"),
+            wind_speed: series(&|h| self.wind_speed * if raining(h) { 1.0 } else { 0.7 }, "km/h"),
+            precipitation_probability: series(&|h| if raining(h) { 90.0 } else { 10.0 }, "%"),
+            air_quality: TimeSeries::default(),
+            humidity: series(&|_| self.humidity, "%"),
+            cloud_cover: series(&|h| if raining(h) || self.weather_code > 2 { 95.0 } else { self.weather_code as f64 * 20.0 }, "%"),
+            sunshine: series(&|h| if day(h) && !raining(h) && self.weather_code <= 2 { 3600.0 } else { 0.0 }, "s"),
+            is_day: series(&|h| if day(h) { 1.0 } else { 0.0 }, ""),
+            dew_point: series(&|h| temperature(h) - (100.0 - self.humidity) / 5.0, "°C"),
+            surface_temperature: series(&|h| temperature(h) - if day(h) { -1.0 } else { 1.5 }, "°C"),
+        };
+
+        let now = temperature(hour_now);
+        let current = CurrentWeather {
+            time: local.format("%Y-%m-%dT%H:%M").to_string(),
+            temperature_2m: now,
+            relative_humidity_2m: self.humidity as u32,
+            apparent_temperature: now - self.wind_speed / 10.0,
+            precipitation: self.precipitation,
+            weather_code: self.weather_code,
+            wind_speed_10m: self.wind_speed,
+            pressure_msl: self.pressure,
+            uv_index: if day(hour_now) && self.weather_code <= 2 { 7.0 } else { 0.0 },
+        };
+        let units = CurrentUnits {
+            temperature_2m: "°C".to_string(),
+            wind_speed_10m: "km/h".to_string(),
+            pressure_msl: "hPa".to_string(),
+        };
+        WeatherData {
+            location: self.location(),
+            weather: WeatherResponse { current, current_units: units, hourly: Some(hourly) },
+            timings: FetchTimings { geocode: None, forecast: Duration::from_millis(3), json_fallback: None },
+            fetched_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            units: Units::default(),
+        }
+    }
+}
+
+fn find(name: &str, coordinates: Option<(f64, f64)>) -> Result<&'static Scene, String> {
+    let nearest = |(latitude, longitude): (f64, f64)| {
+        SCENES.iter().min_by(|a, b| {
+            let distance = |s: &Scene| (s.latitude - latitude).powi(2) + (s.longitude - longitude).powi(2);
+            distance(a).total_cmp(&distance(b))
+        })
+    };
+    let scene = match coordinates {
+        Some(coordinates) => nearest(coordinates),
+        None => SCENES.iter().find(|s| s.name.eq_ignore_ascii_case(name)),
+    };
+    scene.ok_or_else(|| {
+        let names: Vec<&str> = SCENES.iter().map(|s| s.name).collect();
+        format!("'{}' isn't in the demo. Try {}.", name, names.join(", "))
+    })
+}
+
+// --demo starts from a clean slate instead of your config, so nothing of yours ends up in a screenshot
+pub fn config() -> Config {
+    Config {
+        kiosk_locations: SCENES.iter().map(|s| s.name.to_string()).collect(),
+        weather_effects: true,
+        ..Config::default()
+    }
+}
+
+// Answers everything from the scenes above; what they don't cover says so instead of going online
+pub struct Demo;
+
+impl Source for Demo {
+    async fn fetch_weather(&self, query: &SearchQuery, cached: Option<GeoLocation>) -> Result<WeatherData, FetchError> {
+        let coordinates = cached.map(|location| (location.latitude, location.longitude)).or(query.coordinates);
+        find(&query.name, coordinates).map(Scene::weather).map_err(FetchError::NotFound)
+    }
+
+    async fn fetch_autocomplete(&self, query: &SearchQuery) -> Result<Vec<GeoLocation>, String> {
+        let prefix = query.name.to_lowercase();
+        Ok(SCENES.iter().filter(|s| s.name.to_lowercase().starts_with(&prefix)).map(Scene::location).collect())
+    }
+
+    async fn geocode(&self, query: &SearchQuery) -> Result<GeoLocation, String> {
+        find(&query.name, query.coordinates).map(Scene::location)
+    }
+
+    async fn fetch_earthquakes(&self, _: f64, _: f64, _: f64) -> Result<Vec<Earthquake>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_brief(&self, icao: &str) -> Result<FlightBrief, String> {
+        Err(format!("No brief for {} in the demo", icao))
+    }
+
+    async fn fetch_cyclones(&self) -> Result<Vec<Cyclone>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_mountain(&self, _: f64, _: f64, _: &[f64]) -> Result<MountainForecast, String> {
+        Err("No mountain forecast in the demo".to_string())
+    }
+
+    async fn fetch_free_flight(&self, _: f64, _: f64) -> Result<FreeFlightForecast, String> {
+        Err("No upper-air forecast in the demo".to_string())
+    }
+
+    async fn fetch_daily(&self, _: &GeoLocation, _: u8) -> Result<DailyForecast, String> {
+        Err("No week ahead in the demo".to_string())
+    }
+
+    async fn fetch_tides(&self, _: f64, _: f64, _: Option<&str>) -> Result<Tides, String> {
+        Err("No tides in the demo".to_string())
+    }
+
+    async fn fetch_sea_temperature(&self, _: f64, _: f64) -> Result<TimeSeries<f64>, String> {
+        Err("No sea temperature in the demo".to_string())
+    }
+
+    async fn fetch_pressure(&self, _: f64, _: f64) -> Result<TimeSeries<f64>, String> {
+        Err("No barograph in the demo".to_string())
+    }
+
+    async fn fetch_nearby(&self, _: f64, _: f64, _: f64) -> Result<Vec<NearbyPoint>, String> {
+        Err("No nearby forecasts in the demo".to_string())
+    }
+
+    async fn fetch_latest_release(&self) -> Result<Release, String> {
+        Err("No update check in the demo".to_string())
+    }
+
+    async fn post_webhook(&self, _: &str, _: &serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn publish_ntfy(&self, _: &NtfyConfig, _: &str, _: &str, _: u8) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Types each place into the search box in turn, forever, at about the pace of someone showing it off.
+// The first real key press ends the tour and leaves the keyboard to whoever pressed it
+pub fn with_tour<S>(keyboard: S) -> impl Stream<Item = io::Result<Event>>
+where
+    S: Stream<Item = io::Result<Event>>,
+{
+    let press = |code| Some(Event::Key(KeyEvent::from(code)));
+    let mut lap = Vec::new();
+    for scene in &SCENES {
+        lap.push((Duration::from_millis(800), press(KeyCode::Char('i'))));
+        lap.extend(scene.name.chars().map(|c| (Duration::from_millis(120), press(KeyCode::Char(c)))));
+        // Once to take the suggestion, once to search
+        lap.push((Duration::from_millis(600), press(KeyCode::Enter)));
+        lap.push((Duration::from_millis(500), press(KeyCode::Enter)));
+        // Long enough to take in, or take a screenshot of
+        lap.push((Duration::from_secs(8), None));
+    }
+
+    let taken_over = Arc::new(AtomicBool::new(false));
+    let stop = taken_over.clone();
+    let tour = stream::iter(lap)
+        .cycle()
+        .then(|(delay, event)| async move {
+            tokio::time::sleep(delay).await;
+            event
+        })
+        .take_while(move |_| std::future::ready(!stop.load(Ordering::Relaxed)))
+        .filter_map(|event| std::future::ready(event.map(Ok)));
+    let keyboard = keyboard.inspect(move |event| {
+        if matches!(event, Ok(Event::Key(_))) {
+            taken_over.store(true, Ordering::Relaxed);
+        }
+    });
+    stream::select(tour, keyboard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn every_scene_has_a_full_day_ahead() {
+        for scene in &SCENES {
+            let query = SearchQuery::parse(&scene.name.to_uppercase(), None);
+            let data = Demo.fetch_weather(&query, None).await.expect("scene should be found");
+            let hourly = data.weather.hourly.expect("scene should have an hourly forecast");
+            let now = hourly.temperature.slot_of(&data.weather.current.time).expect("now should be in the forecast");
+            assert!(hourly.temperature.values.len() - now >= 24, "{} runs out too soon", scene.name);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_key_press_ends_the_tour() {
+        let keyboard = stream::once(async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(Event::Key(KeyEvent::from(KeyCode::Esc)))
+        });
+        let keys: Vec<KeyCode> = with_tour(keyboard)
+            .filter_map(|event| std::future::ready(match event {
+                Ok(Event::Key(key)) => Some(key.code),
+                _ => None,
+            }))
+            .collect()
+            .await;
+        let esc = keys.iter().position(|&code| code == KeyCode::Esc).expect("the real key should come through");
+        assert!(esc > 10 && esc + 1 == keys.len(), "{:?}", keys);
+        assert_eq!(&keys[..2], &[KeyCode::Char('i'), KeyCode::Char('L')]);
+    }
+}
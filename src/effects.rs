@@ -0,0 +1,94 @@
+// Rain or snow falling through the empty parts of a pane, one step per tick. Drops are worked out
+// from the tick and the column alone, so there's no particle state to keep between frames.
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Precipitation {
+    Rain,
+    Snow,
+}
+
+impl Precipitation {
+    // WMO weather codes: drizzle, rain, showers and thunderstorms fall as rain; the snow codes as snow
+    pub fn from_code(code: u32) -> Option<Precipitation> {
+        match code {
+            51..=67 | 80..=82 | 95..=99 => Some(Precipitation::Rain),
+            71..=77 | 85..=86 => Some(Precipitation::Snow),
+            _ => None,
+        }
+    }
+}
+
+pub struct Falling {
+    pub kind: Precipitation,
+    pub tick: u64,
+}
+
+// Cheap, stable scatter so each column gets its own phase
+fn scatter(n: u64) -> u64 {
+    let mut x = n.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    x ^= x >> 29;
+    x.wrapping_mul(0xBF58_476D_1CE4_E5B9) >> 32
+}
+
+impl Widget for Falling {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let (symbol, color, percent, ticks_per_row) = match self.kind {
+            Precipitation::Rain => ("╎", Color::Blue, 30, 1),
+            Precipitation::Snow => ("*", Color::White, 20, 3),
+        };
+        let height = area.height as u64;
+        // Two drops per column at most, half a screen apart, so the sky doesn't look gappy
+        for layer in 0..2u64 {
+            for column in 0..area.width as u64 {
+                let seed = scatter(column * 2 + layer);
+                if seed % 100 >= percent {
+                    continue;
+                }
+                let fallen = self.tick / ticks_per_row + seed / 100;
+                let y = (fallen + layer * height / 2) % height;
+                // Snow wanders a column either way as it falls
+                let x = match self.kind {
+                    Precipitation::Rain => column,
+                    Precipitation::Snow => (column + area.width as u64 + fallen / 4 % 3 - 1) % area.width as u64,
+                };
+                let cell = &mut buf[(area.x + x as u16, area.y + y as u16)];
+                // Only in the gaps: text always stays readable
+                if cell.symbol() == " " {
+                    cell.set_symbol(symbol).set_style(Style::default().fg(color));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::widgets::Paragraph;
+
+    #[test]
+    fn drops_fall_around_the_text_and_move_on() {
+        let area = Rect::new(0, 0, 40, 10);
+        let frame = |tick| {
+            let mut buf = Buffer::empty(area);
+            Paragraph::new("Rain, heavy at times").render(area, &mut buf);
+            Falling { kind: Precipitation::Rain, tick }.render(area, &mut buf);
+            buf
+        };
+        let first = frame(0);
+        let text: String = (0..20).map(|x| first[(x, 0)].symbol()).collect();
+        assert_eq!(text, "Rain, heavy at times");
+        let drops = first.content.iter().filter(|c| c.symbol() == "╎").count();
+        assert!(drops > 5, "only {} drops", drops);
+        assert_ne!(first, frame(1));
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use crate::config;
+
+// How many of the most recent events are loaded at startup
+const KEEP: usize = 500;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Alert,
+    ForecastChange,
+    RefreshFailed,
+    Threshold,
+    DeliveryFailed,
+    UpdateAvailable,
+}
+
+// Something that happened in the background, kept so it can be read later
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Event {
+    // Unix seconds
+    pub timestamp: u64,
+    pub kind: EventKind,
+    pub message: String,
+}
+
+pub fn get_events_path() -> PathBuf {
+    if let Some(mut path) = config::profile_dir() {
+        path.push("events.jsonl");
+        return path;
+    }
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("events.jsonl");
+    path
+}
+
+// Oldest first; lines that don't parse are skipped rather than failing the whole log
+pub fn load_events() -> Result<Vec<Event>, Box<dyn Error>> {
+    let path = get_events_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    let mut events: Vec<Event> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let excess = events.len().saturating_sub(KEEP);
+    events.drain(..excess);
+    Ok(events)
+}
+
+// One JSON object per line, so appending never rewrites the file
+pub fn append_event(event: &Event) -> Result<(), Box<dyn Error>> {
+    let path = get_events_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
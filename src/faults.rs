@@ -0,0 +1,198 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::api::{
+    Cyclone, DailyForecast, Earthquake, FetchError, FlightBrief, FreeFlightForecast, GeoLocation, MountainForecast,
+    NearbyPoint, Release, SearchQuery, Source, Tides, WeatherData,
+};
+use crate::config::NtfyConfig;
+use crate::series::TimeSeries;
+
+// How the requests that don't get through fail
+#[derive(Clone, Copy)]
+enum Fault {
+    Timeout,
+    RateLimited,
+    Malformed,
+    Empty,
+}
+
+impl Fault {
+    // The same wording the real failures use, so the UI can't tell the difference
+    async fn error(self, what: &str) -> String {
+        match self {
+            Fault::Timeout => {
+                // Long enough to watch the spinner, short enough not to be annoying
+                tokio::time::sleep(Duration::from_secs(3)).await;
+                format!("Connection timeout while fetching {}.", what)
+            }
+            Fault::RateLimited => {
+                "Network error: HTTP status client error (429 Too Many Requests) for url (simulated)".to_string()
+            }
+            Fault::Malformed => format!("Failed to parse {} from service.", what),
+            Fault::Empty => format!("No {} in the response.", what),
+        }
+    }
+}
+
+// Wraps a source and makes about half of its requests fail, for poking at error paths by hand
+pub struct Faulty<S> {
+    inner: S,
+    seed: AtomicU64,
+}
+
+impl<S: Source> Faulty<S> {
+    pub fn new(inner: S) -> Faulty<S> {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(1);
+        Faulty { inner, seed: AtomicU64::new(seed | 1) }
+    }
+
+    // xorshift; good enough to pick which request breaks
+    fn roll(&self) -> Option<Fault> {
+        let mut x = self.seed.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed.store(x, Ordering::Relaxed);
+        match x % 8 {
+            0 => Some(Fault::Timeout),
+            1 => Some(Fault::RateLimited),
+            2 => Some(Fault::Malformed),
+            3 => Some(Fault::Empty),
+            _ => None,
+        }
+    }
+}
+
+impl<S: Source> Source for Faulty<S> {
+    async fn fetch_weather(&self, query: &SearchQuery, cached: Option<GeoLocation>) -> Result<WeatherData, FetchError> {
+        match self.roll() {
+            Some(Fault::Empty) => Err(FetchError::NotFound(format!("'{}' not found. Try a different city name.", query.name))),
+            Some(fault) => Err(fault.error("weather data").await.into()),
+            None => self.inner.fetch_weather(query, cached).await,
+        }
+    }
+
+    async fn fetch_autocomplete(&self, query: &SearchQuery) -> Result<Vec<GeoLocation>, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Ok(Vec::new()),
+            Some(fault) => Err(fault.error("location data").await),
+            None => self.inner.fetch_autocomplete(query).await,
+        }
+    }
+
+    async fn geocode(&self, query: &SearchQuery) -> Result<GeoLocation, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err(format!("'{}' not found. Try a different city name.", query.name)),
+            Some(fault) => Err(fault.error("location data").await),
+            None => self.inner.geocode(query).await,
+        }
+    }
+
+    async fn fetch_earthquakes(&self, latitude: f64, longitude: f64, radius_km: f64) -> Result<Vec<Earthquake>, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Ok(Vec::new()),
+            Some(fault) => Err(fault.error("earthquakes").await),
+            None => self.inner.fetch_earthquakes(latitude, longitude, radius_km).await,
+        }
+    }
+
+    async fn fetch_brief(&self, icao: &str) -> Result<FlightBrief, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err(format!("No METAR for {}. Is that an ICAO code?", icao)),
+            Some(fault) => Err(fault.error("the brief").await),
+            None => self.inner.fetch_brief(icao).await,
+        }
+    }
+
+    async fn fetch_cyclones(&self) -> Result<Vec<Cyclone>, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Ok(Vec::new()),
+            Some(fault) => Err(fault.error("storm advisories").await),
+            None => self.inner.fetch_cyclones().await,
+        }
+    }
+
+    async fn fetch_mountain(&self, latitude: f64, longitude: f64, elevations: &[f64]) -> Result<MountainForecast, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("No mountain forecast for this place".to_string()),
+            Some(fault) => Err(fault.error("the mountain forecast").await),
+            None => self.inner.fetch_mountain(latitude, longitude, elevations).await,
+        }
+    }
+
+    async fn fetch_free_flight(&self, latitude: f64, longitude: f64) -> Result<FreeFlightForecast, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("No upper-air forecast for this place".to_string()),
+            Some(fault) => Err(fault.error("the upper-air forecast").await),
+            None => self.inner.fetch_free_flight(latitude, longitude).await,
+        }
+    }
+
+    async fn fetch_daily(&self, location: &GeoLocation, days: u8) -> Result<DailyForecast, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("No daily forecast for this place".to_string()),
+            Some(fault) => Err(fault.error("the daily forecast").await),
+            None => self.inner.fetch_daily(location, days).await,
+        }
+    }
+
+    async fn fetch_tides(&self, latitude: f64, longitude: f64, worldtides_key: Option<&str>) -> Result<Tides, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("No tide station nearby".to_string()),
+            Some(fault) => Err(fault.error("tide predictions").await),
+            None => self.inner.fetch_tides(latitude, longitude, worldtides_key).await,
+        }
+    }
+
+    async fn fetch_sea_temperature(&self, latitude: f64, longitude: f64) -> Result<TimeSeries<f64>, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("No sea temperature for this place. Is it on the coast?".to_string()),
+            Some(fault) => Err(fault.error("the sea temperature").await),
+            None => self.inner.fetch_sea_temperature(latitude, longitude).await,
+        }
+    }
+
+    async fn fetch_pressure(&self, latitude: f64, longitude: f64) -> Result<TimeSeries<f64>, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("No pressure forecast for this place".to_string()),
+            Some(fault) => Err(fault.error("the pressure forecast").await),
+            None => self.inner.fetch_pressure(latitude, longitude).await,
+        }
+    }
+
+    async fn fetch_nearby(&self, latitude: f64, longitude: f64, offset_km: f64) -> Result<Vec<NearbyPoint>, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("Incomplete forecast for the nearby points".to_string()),
+            Some(fault) => Err(fault.error("the nearby forecasts").await),
+            None => self.inner.fetch_nearby(latitude, longitude, offset_km).await,
+        }
+    }
+
+    async fn fetch_latest_release(&self) -> Result<Release, String> {
+        match self.roll() {
+            Some(Fault::Empty) => Err("Couldn't make sense of the latest release.".to_string()),
+            Some(fault) => Err(fault.error("the latest release").await),
+            None => self.inner.fetch_latest_release().await,
+        }
+    }
+
+    async fn post_webhook(&self, url: &str, payload: &serde_json::Value) -> Result<(), String> {
+        match self.roll() {
+            Some(Fault::Timeout) | Some(Fault::RateLimited) => Err(format!("{}: simulated failure", url)),
+            _ => self.inner.post_webhook(url, payload).await,
+        }
+    }
+
+    async fn publish_ntfy(&self, ntfy: &NtfyConfig, title: &str, message: &str, priority: u8) -> Result<(), String> {
+        match self.roll() {
+            Some(Fault::Timeout) | Some(Fault::RateLimited) => Err(format!("ntfy {}: simulated failure", ntfy.topic)),
+            _ => self.inner.publish_ntfy(ntfy, title, message, priority).await,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// Just enough of a FlatBuffers reader for Open-Meteo's binary format (the openmeteo_sdk
+// schema). Every read is bounds-checked, so a malformed buffer gives None rather than a panic
+
+use crate::api::{CurrentUnits, CurrentWeather, HourlyForecast, WeatherResponse};
+use crate::series::TimeSeries;
+
+// Field slots from weather_api.fbs
+const RESPONSE_UTC_OFFSET: usize = 6;
+const RESPONSE_CURRENT: usize = 9;
+const RESPONSE_HOURLY: usize = 11;
+const SERIES_TIME: usize = 0;
+const SERIES_TIME_END: usize = 1;
+const SERIES_INTERVAL: usize = 2;
+const SERIES_VARIABLES: usize = 3;
+const VARIABLE_VALUE: usize = 2;
+const VARIABLE_VALUES: usize = 3;
+
+fn read<const N: usize>(buf: &[u8], pos: usize) -> Option<[u8; N]> {
+    buf.get(pos..pos.checked_add(N)?)?.try_into().ok()
+}
+
+// f32 -> f64 without dragging along noise like 12.300000190734863; the API rounds to 0.1 anyway
+fn widen(value: f32) -> f64 {
+    (value as f64 * 100.0).round() / 100.0
+}
+
+fn offset_at(buf: &[u8], pos: usize) -> Option<usize> {
+    Some(u32::from_le_bytes(read(buf, pos)?) as usize)
+}
+
+#[derive(Clone, Copy)]
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Table<'a> {
+    fn root(buf: &'a [u8]) -> Option<Table<'a>> {
+        Some(Table { buf, pos: offset_at(buf, 0)? })
+    }
+
+    // Where a field's data starts, or None when the writer left it at its default
+    fn field(&self, index: usize) -> Option<usize> {
+        let soffset = i32::from_le_bytes(read(self.buf, self.pos)?) as i64;
+        let vtable = usize::try_from(self.pos as i64 - soffset).ok()?;
+        let vtable_len = u16::from_le_bytes(read(self.buf, vtable)?) as usize;
+        let slot = 4 + 2 * index;
+        if slot + 2 > vtable_len {
+            return None;
+        }
+        let offset = u16::from_le_bytes(read(self.buf, vtable + slot)?) as usize;
+        (offset != 0).then_some(self.pos + offset)
+    }
+
+    // Scalars left out of the buffer are zero, which is also the schema default
+    fn f32(&self, index: usize) -> f32 {
+        self.field(index).and_then(|p| read(self.buf, p)).map_or(0.0, f32::from_le_bytes)
+    }
+
+    fn i32(&self, index: usize) -> i32 {
+        self.field(index).and_then(|p| read(self.buf, p)).map_or(0, i32::from_le_bytes)
+    }
+
+    fn i64(&self, index: usize) -> i64 {
+        self.field(index).and_then(|p| read(self.buf, p)).map_or(0, i64::from_le_bytes)
+    }
+
+    fn indirect(&self, index: usize) -> Option<usize> {
+        let pos = self.field(index)?;
+        pos.checked_add(offset_at(self.buf, pos)?)
+    }
+
+    fn table(&self, index: usize) -> Option<Table<'a>> {
+        Some(Table { buf: self.buf, pos: self.indirect(index)? })
+    }
+
+    fn tables(&self, index: usize) -> Option<Vec<Table<'a>>> {
+        let vector = self.indirect(index)?;
+        (0..offset_at(self.buf, vector)?)
+            .map(|i| {
+                let pos = vector + 4 + 4 * i;
+                Some(Table { buf: self.buf, pos: pos.checked_add(offset_at(self.buf, pos)?)? })
+            })
+            .collect()
+    }
+
+    fn f32s(&self, index: usize) -> Option<Vec<f64>> {
+        let vector = self.indirect(index)?;
+        (0..offset_at(self.buf, vector)?)
+            .map(|i| read(self.buf, vector + 4 + 4 * i).map(|b| widen(f32::from_le_bytes(b))))
+            .collect()
+    }
+}
+
+// Same shape as the JSON: local time without seconds, e.g. "2024-05-01T14:15"
+fn local_time(unix: i64, utc_offset: i64) -> Option<String> {
+    Some(chrono::DateTime::from_timestamp(unix + utc_offset, 0)?.format("%Y-%m-%dT%H:%M").to_string())
+}
+
+// Decodes the answer to the forecast request in api::forecast. Variables come back in
+// the order they were asked for, so they're read by position rather than by their enum value
+pub fn decode_forecast(body: &[u8]) -> Option<WeatherResponse> {
+    // Each location is a size-prefixed message; we only ever ask for one
+    let len = offset_at(body, 0)?;
+    let message = body.get(4..4usize.checked_add(len)?)?;
+    let response = Table::root(message)?;
+    let utc_offset = response.i32(RESPONSE_UTC_OFFSET) as i64;
+
+    let current = response.table(RESPONSE_CURRENT)?;
+    let values: Vec<f64> = current.tables(SERIES_VARIABLES)?.iter().map(|v| widen(v.f32(VARIABLE_VALUE))).collect();
+    let [temperature, humidity, apparent, precipitation, code, wind, pressure, ref rest @ ..] = values[..] else {
+        return None;
+    };
+    let current = CurrentWeather {
+        time: local_time(current.i64(SERIES_TIME), utc_offset)?,
+        temperature_2m: temperature,
+        relative_humidity_2m: humidity.round() as u32,
+        apparent_temperature: apparent,
+        precipitation,
+        weather_code: code.round() as u32,
+        wind_speed_10m: wind,
+        pressure_msl: pressure,
+        uv_index: rest.first().copied().unwrap_or(0.0),
+    };
+
+    let hourly = response.table(RESPONSE_HOURLY).and_then(|hourly| {
+        let variables = hourly.tables(SERIES_VARIABLES)?;
+        let temperature = variables.first()?.f32s(VARIABLE_VALUES)?;
+        let interval = hourly.i32(SERIES_INTERVAL).max(1) as usize;
+        // No more times than readings, so a bad time_end can't have us counting for hours
+        let time = (hourly.i64(SERIES_TIME)..hourly.i64(SERIES_TIME_END))
+            .step_by(interval)
+            .take(temperature.len())
+            .map(|t| local_time(t, utc_offset))
+            .collect::<Option<Vec<_>>>()?;
+        // Later variables are optional, so an older cached answer still decodes
+        let optional = |i: usize| variables.get(i).and_then(|v| v.f32s(VARIABLE_VALUES)).unwrap_or_default();
+        Some(HourlyForecast {
+            temperature: TimeSeries::new(time.clone(), temperature, "°C"),
+            precipitation: TimeSeries::new(time.clone(), variables.get(1)?.f32s(VARIABLE_VALUES)?, "mm"),
+            wind_speed: TimeSeries::new(time.clone(), optional(2), "km/h"),
+            precipitation_probability: TimeSeries::new(time.clone(), optional(3), "%"),
+            humidity: TimeSeries::new(time.clone(), optional(4), "%"),
+            cloud_cover: TimeSeries::new(time.clone(), optional(5), "%"),
+            sunshine: TimeSeries::new(time.clone(), optional(6), "s"),
+            is_day: TimeSeries::new(time.clone(), optional(7), ""),
+            dew_point: TimeSeries::new(time.clone(), optional(8), "°C"),
+            surface_temperature: TimeSeries::new(time, optional(9), "°C"),
+            air_quality: TimeSeries::default(),
+        })
+    });
+
+    // The request pins the units, so there's nothing to decode here
+    let current_units = CurrentUnits {
+        temperature_2m: "°C".to_string(),
+        wind_speed_10m: "km/h".to_string(),
+        pressure_msl: "hPa".to_string(),
+    };
+    Some(WeatherResponse { current, current_units, hourly })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // The request in api::forecast for Berlin, in both formats. The binary one was written with
+    // the flatbuffers crate to the openmeteo_sdk layout, so it checks this reader against another
+    const FLATBUFFERS: &[u8] = include_bytes!("../tests/fixtures/forecast-berlin.flatbuffers");
+    const JSON: &str = include_str!("../tests/fixtures/forecast-berlin.json");
+
+    #[test]
+    fn decodes_the_same_forecast_as_the_json() {
+        let decoded = decode_forecast(FLATBUFFERS).expect("fixture should decode");
+        let json: WeatherResponse = serde_json::from_str(JSON).expect("fixture should parse");
+        assert_eq!(decoded.current.time, "2024-05-01T14:15");
+        assert_eq!(decoded.hourly.as_ref().map(|h| h.temperature.len()), Some(72));
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", json));
+    }
+
+    #[test]
+    fn a_truncated_or_json_answer_is_none() {
+        for len in 0..FLATBUFFERS.len() {
+            assert!(decode_forecast(&FLATBUFFERS[..len]).is_none(), "cut at {}", len);
+        }
+        assert!(decode_forecast(JSON.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn a_series_ending_in_the_far_future_stops_with_its_readings() {
+        let mut body = FLATBUFFERS.to_vec();
+        let hourly = Table::root(&FLATBUFFERS[4..]).and_then(|r| r.table(RESPONSE_HOURLY)).unwrap();
+        let at = 4 + hourly.field(SERIES_TIME_END).unwrap();
+        body[at..at + 8].copy_from_slice(&i64::MAX.to_le_bytes());
+        let decoded = decode_forecast(&body).expect("still decodes");
+        assert_eq!(decoded.hourly.map(|h| h.temperature.len()), Some(72));
+    }
+
+    proptest! {
+        #[test]
+        fn garbage_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+            let _ = decode_forecast(&bytes);
+        }
+
+        // The size prefix still fits, so the damage is only found by following offsets into it
+        #[test]
+        fn a_corrupted_answer_never_panics(at in 4..FLATBUFFERS.len(), byte in any::<u8>()) {
+            let mut body = FLATBUFFERS.to_vec();
+            body[at] = byte;
+            let _ = decode_forecast(&body);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::action::Action;
+use crate::app::App;
+
+// Everything on the search screen that can hold keyboard focus.
+// A new pane is a variant here, a place in ORDER and an arm in its Focusable impl.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pane {
+    Search,
+    History,
+}
+
+impl Pane {
+    // Tab order; Alt plus the position jumps straight to a pane
+    pub const ORDER: [Pane; 2] = [Pane::Search, Pane::History];
+
+    // Alt plus this letter focuses the pane in any mode
+    pub fn hotkey(self) -> char {
+        match self {
+            Pane::Search => 's',
+            Pane::History => 'h',
+        }
+    }
+
+    pub fn from_hotkey(c: char) -> Option<Pane> {
+        if let Some(n) = c.to_digit(10) {
+            return (n as usize).checked_sub(1).and_then(|i| Pane::ORDER.get(i)).copied();
+        }
+        Pane::ORDER.into_iter().find(|p| p.hotkey() == c)
+    }
+
+    // The next pane after this one that will take focus, wrapping around
+    pub fn next(self, can_focus: impl Fn(Pane) -> bool) -> Pane {
+        self.step(1, can_focus)
+    }
+
+    pub fn prev(self, can_focus: impl Fn(Pane) -> bool) -> Pane {
+        self.step(Pane::ORDER.len() - 1, can_focus)
+    }
+
+    fn step(self, by: usize, can_focus: impl Fn(Pane) -> bool) -> Pane {
+        let len = Pane::ORDER.len();
+        let start = Pane::ORDER.iter().position(|&p| p == self).unwrap_or(0);
+        (1..=len)
+            .map(|i| Pane::ORDER[(start + i * by) % len])
+            .find(|&p| can_focus(p))
+            .unwrap_or(self)
+    }
+}
+
+// A pane that sees actions before the rest of the app while it has focus
+pub trait Focusable {
+    // False while the pane has nothing to offer, so Tab skips it
+    fn can_focus(&self, app: &App) -> bool;
+    // Hands back the actions it doesn't handle
+    fn handle_action(&self, app: &mut App, action: Action) -> Option<Action>;
+}
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+
+use crate::api::HourlyForecast;
+
+// How far ahead a refresh is compared against the previous one
+const HORIZON_HOURS: usize = 24;
+const TEMPERATURE_THRESHOLD: f64 = 1.5;
+// Anything below this counts as dry
+pub const WET_MM: f64 = 0.1;
+
+// Hours (as "2024-05-01T14:00") whose forecast moved noticeably between two refreshes
+#[derive(Default)]
+pub struct ForecastDiff {
+    pub temperature: HashSet<String>,
+    pub precipitation: HashSet<String>,
+    // Short description for the toast, None when nothing material changed
+    pub summary: Option<String>,
+}
+
+pub fn diff(old: &HourlyForecast, new: &HourlyForecast, current_time: &str) -> ForecastDiff {
+    let mut result = ForecastDiff::default();
+    let Some(start) = new.temperature.slot_of(current_time) else {
+        return result;
+    };
+
+    let mut biggest: Option<(f64, &str)> = None;
+    let mut old_rain = None;
+    let mut new_rain = None;
+    for (time, temperature) in new.temperature.iter().skip(start).take(HORIZON_HOURS) {
+        let Some(previous) = old.temperature.at(time) else {
+            continue;
+        };
+
+        let change = temperature - previous;
+        if change.abs() >= TEMPERATURE_THRESHOLD {
+            result.temperature.insert(time.to_string());
+            if biggest.is_none_or(|(b, _)| change.abs() > b.abs()) {
+                biggest = Some((change, time));
+            }
+        }
+
+        let (was, now) = (rain_at(old, time), rain_at(new, time));
+        if (was >= WET_MM) != (now >= WET_MM) || (now - was).abs() >= 1.0 {
+            result.precipitation.insert(time.to_string());
+        }
+        if old_rain.is_none() && was >= WET_MM {
+            old_rain = Some(time);
+        }
+        if new_rain.is_none() && now >= WET_MM {
+            new_rain = Some(time);
+        }
+    }
+
+    let mut parts = Vec::new();
+    match (old_rain, new_rain) {
+        (Some(was), Some(now)) if was != now => {
+            parts.push(format!("rain now from {} (was {})", hour_of(now), hour_of(was)))
+        }
+        (None, Some(now)) => parts.push(format!("rain now expected from {}", hour_of(now))),
+        (Some(_), None) => parts.push("no rain expected any more".to_string()),
+        _ => {}
+    }
+    if let Some((change, time)) = biggest {
+        let direction = if change > 0.0 { "warmer" } else { "colder" };
+        parts.push(format!("up to {:.1}° {} around {}", change.abs(), direction, hour_of(time)));
+    }
+    if !parts.is_empty() {
+        result.summary = Some(format!("Forecast updated: {}", parts.join(", ")));
+    }
+    result
+}
+
+fn rain_at(forecast: &HourlyForecast, time: &str) -> f64 {
+    forecast.precipitation.at(time).copied().unwrap_or(0.0)
+}
+
+// "2024-05-01T14:00" -> "14:00"
+pub fn hour_of(time: &str) -> &str {
+    time.split('T').nth(1).unwrap_or(time)
+}
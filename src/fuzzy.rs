@@ -0,0 +1,183 @@
+// Small subsequence matcher used to rank and highlight place names locally
+
+// Returns a score (higher is better) and the char indices in `candidate` that matched.
+// Every char of `query` must appear in order; whitespace in the query is ignored. Both sides are
+// compared as `normalize` sees them, so "zurich" matches (and highlights) "Zürich".
+pub fn fuzzy_match(candidate: &str, query: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = normalize(query).chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    // Each folded char remembers which char of the candidate it came from
+    let mut folded: Vec<(char, usize)> = Vec::with_capacity(chars.len());
+    let mut buf = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        buf.clear();
+        fold(c, &mut buf);
+        folded.extend(buf.chars().map(|f| (f, i)));
+    }
+
+    let mut positions: Vec<usize> = Vec::with_capacity(query.len());
+    let mut score: i64 = 0;
+    let mut pos = 0;
+    let mut last = None;
+
+    for &q in &query {
+        while pos < folded.len() && folded[pos].0 != q {
+            pos += 1;
+        }
+        if pos == folded.len() {
+            return None;
+        }
+
+        let index = folded[pos].1;
+        score += 1;
+        let word_start = index == 0 || !chars[index - 1].is_alphanumeric();
+        if word_start && (pos == 0 || folded[pos - 1].1 != index) {
+            score += 3;
+        }
+        if last.is_some_and(|last| last + 1 == pos) {
+            score += 5;
+        }
+        if positions.last() != Some(&index) {
+            positions.push(index);
+        }
+        last = Some(pos);
+        pos += 1;
+    }
+
+    // Prefer matches that start early and names that aren't much longer than the query
+    score -= positions[0] as i64;
+    score -= (folded.len() as i64 - query.len() as i64) / 4;
+
+    Some((score, positions))
+}
+
+// Lowercased, trimmed, whitespace collapsed and accents stripped, so "  Zürich" and "zurich" are the same place
+pub fn normalize(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        fold(c, &mut folded);
+    }
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// One char as `normalize` spells it; usually one char, nothing for a lone accent, two for 'ß'
+fn fold(c: char, out: &mut String) {
+    use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+    for c in std::iter::once(c).nfd().filter(|c| !is_combining_mark(*c)).flat_map(char::to_lowercase) {
+        // Letters that don't decompose into a base letter plus an accent
+        match c {
+            'ø' => out.push('o'),
+            'ł' => out.push('l'),
+            'đ' => out.push('d'),
+            'ß' => out.push_str("ss"),
+            'æ' => out.push_str("ae"),
+            'œ' => out.push_str("oe"),
+            c => out.push(c),
+        }
+    }
+}
+
+// Edits (insert, delete, substitute, swap neighbours) needed to turn one string into the other
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+// How many typos a name of this length can have before it's really a different name
+pub fn typo_allowance(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=5 => 1,
+        6..=9 => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_in_order_ignoring_case_and_query_spaces() {
+        assert_eq!(fuzzy_match("Berlin", "brl").map(|(_, p)| p), Some(vec![0, 2, 3]));
+        assert_eq!(fuzzy_match("New York", "new york").map(|(_, p)| p), Some(vec![0, 1, 2, 4, 5, 6, 7]));
+        assert_eq!(fuzzy_match("Berlin", "nilreb"), None);
+        assert_eq!(fuzzy_match("Berlin", "  "), Some((0, Vec::new())));
+    }
+
+    #[test]
+    fn prefixes_and_runs_score_higher() {
+        let score = |candidate| fuzzy_match(candidate, "ber").unwrap().0;
+        assert!(score("Bern") > score("Oberhausen"));
+        assert!(score("Bern") > score("Bad Ems Rhein"));
+        assert!(score("Bern") > score("Bernkastel-Kues"));
+    }
+
+    #[test]
+    fn accents_and_folded_letters_match_their_plain_spelling() {
+        assert_eq!(fuzzy_match("Zürich", "zurich").map(|(_, p)| p), Some(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(fuzzy_match("Straße", "strasse").map(|(_, p)| p), Some(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(fuzzy_match("Zurich", "Zür").map(|(_, p)| p), Some(vec![0, 1, 2]));
+        assert_eq!(fuzzy_match("København", "kobn").map(|(_, p)| p), Some(vec![0, 1, 2, 4]));
+        assert_eq!(
+            fuzzy_match("Zürich", "zurich").map(|(score, _)| score),
+            fuzzy_match("Zurich", "zurich").map(|(score, _)| score)
+        );
+    }
+
+    #[test]
+    fn normalize_folds_case_accents_and_spacing() {
+        assert_eq!(normalize("  Zürich "), "zurich");
+        assert_eq!(normalize("São   Paulo"), "sao paulo");
+        assert_eq!(normalize("Łódź"), "lodz");
+        assert_eq!(normalize("Gießen"), "giessen");
+        assert_eq!(normalize("Ærøskøbing"), "aeroskobing");
+        // Already decomposed: a base letter followed by a combining accent
+        assert_eq!(normalize("Zu\u{308}rich"), "zurich");
+    }
+
+    #[test]
+    fn edit_distance_counts_each_kind_of_typo_once() {
+        assert_eq!(edit_distance("berlin", "berlin"), 0);
+        assert_eq!(edit_distance("berlin", "berln"), 1);
+        assert_eq!(edit_distance("berlin", "berlinn"), 1);
+        assert_eq!(edit_distance("berlin", "burlin"), 1);
+        // Swapped neighbours are one slip of the finger, not two substitutions
+        assert_eq!(edit_distance("berlin", "beriln"), 1);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+        assert_eq!(edit_distance("", "oslo"), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn longer_names_allow_more_typos() {
+        assert_eq!(typo_allowance(3), 0);
+        assert_eq!(typo_allowance(4), 1);
+        assert_eq!(typo_allowance(5), 1);
+        assert_eq!(typo_allowance(6), 2);
+        assert_eq!(typo_allowance(9), 2);
+        assert_eq!(typo_allowance(10), 3);
+        assert_eq!(typo_allowance(40), 3);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Cursor, Read},
+    path::PathBuf,
+};
+
+use crate::api::{self, GeoLocation, SearchQuery};
+use crate::fuzzy;
+use crate::geo;
+
+// Beyond this the closest city says little about where typed coordinates are
+pub const NEAREST_PLACE_KM: f64 = 50.0;
+
+// Every city with 15k+ people, about 30k places; small enough to keep in memory
+const CITIES_URL: &str = "https://download.geonames.org/export/dump/cities15000.zip";
+const COUNTRIES_URL: &str = "https://download.geonames.org/export/dump/countryInfo.txt";
+const ADMIN1_URL: &str = "https://download.geonames.org/export/dump/admin1CodesASCII.txt";
+
+pub struct Place {
+    pub location: GeoLocation,
+    pub country_code: String,
+    pub population: u64,
+    // Folded once up front so matching per keystroke stays cheap
+    key: String,
+}
+
+pub struct Gazetteer {
+    places: Vec<Place>,
+}
+
+impl Gazetteer {
+    // Best matches first: how well the name matches, then how big the place is
+    pub fn search(&self, query: &SearchQuery, limit: usize) -> Vec<GeoLocation> {
+        let needle = fuzzy::normalize(&query.name);
+        if needle.is_empty() {
+            return Vec::new();
+        }
+        let mut scored: Vec<(i64, &Place)> = self
+            .places
+            .iter()
+            .filter(|p| query.country_code.as_ref().is_none_or(|c| *c == p.country_code))
+            .filter_map(|p| {
+                // Anything that doesn't at least start with the right letter is noise at this size
+                let (score, positions) = fuzzy::fuzzy_match(&p.key, &needle)?;
+                (positions.first() == Some(&0)).then(|| (score * 100 + population_bonus(p.population), p))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().take(limit).map(|(_, p)| p.location.clone()).collect()
+    }
+
+    // Closest place to a point, if there's one within `max_km`, pinned to the exact coordinates
+    pub fn nearest(&self, latitude: f64, longitude: f64, max_km: f64) -> Option<GeoLocation> {
+        let (distance, place) = self
+            .places
+            .iter()
+            .map(|p| (geo::distance_km(latitude, longitude, p.location.latitude, p.location.longitude), p))
+            .min_by(|a, b| a.0.total_cmp(&b.0))?;
+        (distance <= max_km).then(|| GeoLocation {
+            latitude,
+            longitude,
+            near_km: Some(distance),
+            ..place.location.clone()
+        })
+    }
+
+    // Places a few typos away from a name that matched nothing, closest and biggest first
+    pub fn near_misses(&self, query: &SearchQuery, limit: usize) -> Vec<GeoLocation> {
+        let needle = fuzzy::normalize(&query.name);
+        let allowance = fuzzy::typo_allowance(needle.chars().count());
+        let mut scored: Vec<(usize, &Place)> = self
+            .places
+            .iter()
+            .filter(|p| query.country_code.as_ref().is_none_or(|c| *c == p.country_code))
+            .filter(|p| p.key.chars().count().abs_diff(needle.chars().count()) <= allowance)
+            .map(|p| (fuzzy::edit_distance(&p.key, &needle), p))
+            .filter(|(distance, _)| *distance <= allowance)
+            .collect();
+        scored.sort_by_key(|(distance, p)| (*distance, std::cmp::Reverse(p.population)));
+        scored.into_iter().take(limit).map(|(_, p)| p.location.clone()).collect()
+    }
+}
+
+// Enough to put Paris, France ahead of Paris, Texas without outweighing a better spelling match
+fn population_bonus(population: u64) -> i64 {
+    ((population.max(1) as f64).log10() * 20.0) as i64
+}
+
+pub fn get_gazetteer_path() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("cities.tsv");
+    path
+}
+
+// None until `weather-searcher gazetteer download` has been run
+// A file that's there but can't be read is an error, so autocomplete doesn't quietly go back to the network
+pub fn load() -> Result<Option<Gazetteer>, String> {
+    let path = get_gazetteer_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| {
+        format!("Couldn't read {}: {} (`weather-searcher gazetteer download` fetches it again)", path.display(), e)
+    })?;
+    let places = content.lines().filter_map(parse_line).collect();
+    Ok(Some(Gazetteer { places }))
+}
+
+// name, country code, country, admin1, latitude, longitude, population
+fn parse_line(line: &str) -> Option<Place> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let [name, country_code, country, admin1, latitude, longitude, population] = fields[..] else {
+        return None;
+    };
+    Some(Place {
+        location: GeoLocation {
+            name: name.to_string(),
+            latitude: latitude.parse().ok()?,
+            longitude: longitude.parse().ok()?,
+            country: country.to_string(),
+            admin1: (!admin1.is_empty()).then(|| admin1.to_string()),
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        },
+        country_code: country_code.to_string(),
+        population: population.parse().unwrap_or(0),
+        key: fuzzy::normalize(name),
+    })
+}
+
+// Fetches the GeoNames dumps and boils them down to the few columns we use; returns the place count
+pub async fn download() -> Result<usize, String> {
+    let countries = String::from_utf8_lossy(&api::fetch_bytes(COUNTRIES_URL).await?).into_owned();
+    let admin1 = String::from_utf8_lossy(&api::fetch_bytes(ADMIN1_URL).await?).into_owned();
+    let zipped = api::fetch_bytes(CITIES_URL).await?;
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(zipped)).map_err(|e| format!("Bad archive: {}", e))?;
+    let mut cities = String::new();
+    archive
+        .by_name("cities15000.txt")
+        .map_err(|e| format!("Bad archive: {}", e))?
+        .read_to_string(&mut cities)
+        .map_err(|e| format!("Bad archive: {}", e))?;
+
+    let compact = compact(&cities, &countries, &admin1);
+    let count = compact.lines().count();
+    let path = get_gazetteer_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, compact).map_err(|e| format!("Couldn't write {}: {}", path.display(), e))?;
+    Ok(count)
+}
+
+fn compact(cities: &str, countries: &str, admin1: &str) -> String {
+    let country_names: HashMap<&str, &str> = countries
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| {
+            let fields: Vec<&str> = l.split('\t').collect();
+            Some((*fields.first()?, *fields.get(4)?))
+        })
+        .collect();
+    // Keyed "DE.16"
+    let admin1_names: HashMap<&str, &str> = admin1
+        .lines()
+        .filter_map(|l| {
+            let mut fields = l.split('\t');
+            Some((fields.next()?, fields.next()?))
+        })
+        .collect();
+
+    let mut out = String::new();
+    for line in cities.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 15 {
+            continue;
+        }
+        let (name, latitude, longitude, code, population) = (fields[1], fields[4], fields[5], fields[8], fields[14]);
+        let country = country_names.get(code).copied().unwrap_or(code);
+        let region = admin1_names.get(format!("{}.{}", code, fields[10]).as_str()).copied().unwrap_or("");
+        out.push_str(&[name, code, country, region, latitude, longitude, population].join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates_are_named_after_the_closest_city_within_reach() {
+        let lines = ["London\tGB\tUnited Kingdom\tEngland\t51.5085\t-0.1257\t8961989", "Tromsø\tNO\tNorway\tTroms\t69.6496\t18.957\t38980"];
+        let gazetteer = Gazetteer { places: lines.iter().filter_map(|l| parse_line(l)).collect() };
+        let near = gazetteer.nearest(51.5, -0.12, NEAREST_PLACE_KM).unwrap();
+        assert_eq!((near.name.as_str(), near.latitude, near.longitude), ("London", 51.5, -0.12));
+        assert!(near.near_km.is_some_and(|km| km < 2.0));
+        // The middle of the North Sea is nowhere near either
+        assert!(gazetteer.nearest(56.0, 3.0, NEAREST_PLACE_KM).is_none());
+    }
+}
@@ -0,0 +1,100 @@
+// Great-circle helpers on a spherical Earth, plenty accurate for "how far away is it"
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+pub fn distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+// Where you end up after travelling `km` from a point along a compass bearing
+pub fn destination(lat: f64, lon: f64, bearing_deg: f64, km: f64) -> (f64, f64) {
+    let (phi, lambda) = (lat.to_radians(), lon.to_radians());
+    let theta = bearing_deg.to_radians();
+    let delta = km / EARTH_RADIUS_KM;
+    let phi2 = (phi.sin() * delta.cos() + phi.cos() * delta.sin() * theta.cos()).asin();
+    let lambda2 = lambda
+        + (theta.sin() * delta.sin() * phi.cos()).atan2(delta.cos() - phi.sin() * phi2.sin());
+    let lon2 = (lambda2.to_degrees() + 540.0) % 360.0 - 180.0;
+    (phi2.to_degrees(), lon2)
+}
+
+// Headwind and crosswind for a wind blowing *from* `wind_dir` along a runway heading;
+// negative headwind is a tailwind, positive crosswind comes from the right
+pub fn wind_components(wind_dir: f64, speed: f64, runway_heading: f64) -> (f64, f64) {
+    let angle = (wind_dir - runway_heading).to_radians();
+    (speed * angle.cos(), speed * angle.sin())
+}
+
+// Initial compass bearing of the great circle from the first point to the second
+pub fn bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lon2 - lon1).to_radians();
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+// Compass point for a bearing, e.g. 315 -> "NW"
+pub fn compass(bearing_deg: f64) -> &'static str {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+    ];
+    let index = ((bearing_deg.rem_euclid(360.0) / 22.5) + 0.5) as usize % POINTS.len();
+    POINTS[index]
+}
+
+// A new moon, and the average time from one to the next
+const NEW_MOON_UNIX: f64 = 947_182_440.0;
+const SYNODIC_DAYS: f64 = 29.530_588_853;
+
+// Percent of the moon's face lit at a local time like "2024-05-01T22:00". Ignoring the time zone
+// puts it off by a few hours, which the moon barely notices
+pub fn moon_illumination(time: &str) -> Option<f64> {
+    let at = chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").ok()?;
+    let days = (at.and_utc().timestamp() as f64 - NEW_MOON_UNIX) / 86400.0;
+    let phase = (days / SYNODIC_DAYS).rem_euclid(1.0) * std::f64::consts::TAU;
+    Some((1.0 - phase.cos()) / 2.0 * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    #[test]
+    fn distances_between_known_cities() {
+        // Berlin to Paris is about 878 km, London to New York about 5570 km
+        assert!(close(distance_km(52.52, 13.405, 48.8566, 2.3522), 878.0, 5.0));
+        assert!(close(distance_km(51.5074, -0.1278, 40.7128, -74.006), 5570.0, 10.0));
+        assert_eq!(distance_km(10.0, 20.0, 10.0, 20.0), 0.0);
+        // A quarter of the way round the equator, and the same going the other way across the date line
+        assert!(close(distance_km(0.0, 0.0, 0.0, 90.0), 10007.5, 1.0));
+        assert!(close(distance_km(0.0, 179.5, 0.0, -179.5), 111.2, 0.5));
+    }
+
+    #[test]
+    fn bearings_point_the_right_way() {
+        assert!(close(bearing(0.0, 0.0, 1.0, 0.0), 0.0, 1e-9));
+        assert!(close(bearing(0.0, 0.0, 0.0, 1.0), 90.0, 1e-9));
+        assert!(close(bearing(1.0, 0.0, 0.0, 0.0), 180.0, 1e-9));
+        assert!(close(bearing(0.0, 1.0, 0.0, 0.0), 270.0, 1e-9));
+        // Berlin to Paris sets off a little south of west
+        assert_eq!(compass(bearing(52.52, 13.405, 48.8566, 2.3522)), "WSW");
+    }
+
+    #[test]
+    fn compass_rounds_to_the_nearest_point() {
+        assert_eq!(compass(315.0), "NW");
+        assert_eq!(compass(359.0), "N");
+        assert_eq!(compass(-45.0), "NW");
+        assert_eq!(compass(11.0), "N");
+        assert_eq!(compass(12.0), "NNE");
+    }
+}
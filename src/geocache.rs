@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::api::{GeoLocation, SearchQuery};
+use crate::fuzzy;
+
+// Places don't move, so lookups stay good for a month
+const TTL_SECS: u64 = 30 * 86400;
+const MAX_ENTRIES: usize = 2000;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    fetched_at: u64,
+    results: Vec<GeoLocation>,
+}
+
+// One line of the on-disk log; a later line for the same key replaces an earlier one
+#[derive(Serialize, Deserialize)]
+struct LogLine {
+    key: String,
+    #[serde(flatten)]
+    entry: CacheEntry,
+}
+
+static CACHE: OnceLock<Mutex<HashMap<String, CacheEntry>>> = OnceLock::new();
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+// Lookups still get cached for the session, just never written to disk
+pub fn disable_writes() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+fn cache() -> &'static Mutex<HashMap<String, CacheEntry>> {
+    CACHE.get_or_init(|| Mutex::new(load().unwrap_or_default()))
+}
+
+fn key(query: &SearchQuery) -> String {
+    format!("{}|{}", fuzzy::normalize(&query.name), query.country_code.as_deref().unwrap_or(""))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn get(query: &SearchQuery) -> Option<Vec<GeoLocation>> {
+    let cache = cache().lock().ok()?;
+    cache
+        .get(&key(query))
+        .filter(|entry| now().saturating_sub(entry.fetched_at) < TTL_SECS)
+        .map(|entry| entry.results.clone())
+}
+
+// Nothing found isn't cached: it's usually a typo, and a place that gets added should turn up the next day
+pub fn insert(query: &SearchQuery, results: &[GeoLocation]) {
+    if results.is_empty() {
+        return;
+    }
+    let line = LogLine { key: key(query), entry: CacheEntry { fetched_at: now(), results: results.to_vec() } };
+    {
+        let Ok(mut cache) = cache().lock() else { return };
+        if cache.len() >= MAX_ENTRIES {
+            if let Some(oldest) = cache.iter().min_by_key(|(_, e)| e.fetched_at).map(|(k, _)| k.clone()) {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(line.key.clone(), line.entry.clone());
+    }
+    // One appended line per lookup, outside the lock; the whole file is only rewritten at startup
+    if !READ_ONLY.load(Ordering::Relaxed) {
+        let _ = append(&line);
+    }
+}
+
+pub fn get_cache_path() -> PathBuf {
+    let mut path = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("geocode.jsonl");
+    path
+}
+
+fn load() -> Result<HashMap<String, CacheEntry>, Box<dyn Error>> {
+    let path = get_cache_path();
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    let lines = content.lines().count();
+    let cache = replay(&content, now());
+    // Superseded and expired lines pile up; squeeze them out once there are a lot of them
+    if lines > 2 * cache.len() + 100 && !READ_ONLY.load(Ordering::Relaxed) {
+        let _ = compact(&cache);
+    }
+    Ok(cache)
+}
+
+// Live entries from the log, newest per key; lines that don't parse (a torn write) are skipped
+fn replay(content: &str, now: u64) -> HashMap<String, CacheEntry> {
+    let mut cache = HashMap::new();
+    for line in content.lines().filter_map(|l| serde_json::from_str::<LogLine>(l).ok()) {
+        if now.saturating_sub(line.entry.fetched_at) < TTL_SECS {
+            cache.insert(line.key, line.entry);
+        } else {
+            cache.remove(&line.key);
+        }
+    }
+    if cache.len() > MAX_ENTRIES {
+        let mut ages: Vec<u64> = cache.values().map(|e| e.fetched_at).collect();
+        ages.sort_unstable_by(|a, b| b.cmp(a));
+        let cutoff = ages[MAX_ENTRIES - 1];
+        cache.retain(|_, e| e.fetched_at >= cutoff);
+    }
+    cache
+}
+
+fn append(line: &LogLine) -> Result<(), Box<dyn Error>> {
+    let path = get_cache_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(line)?)?;
+    Ok(())
+}
+
+fn compact(cache: &HashMap<String, CacheEntry>) -> Result<(), Box<dyn Error>> {
+    let mut content = String::new();
+    for (key, entry) in cache {
+        let line = LogLine { key: key.clone(), entry: entry.clone() };
+        content.push_str(&serde_json::to_string(&line)?);
+        content.push('\n');
+    }
+    let path = get_cache_path();
+    let tmp = path.with_extension("jsonl.tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(input: &str) -> SearchQuery {
+        SearchQuery::parse(input, None)
+    }
+
+    fn line(key: &str, fetched_at: u64, name: &str) -> String {
+        let results = vec![GeoLocation {
+            name: name.to_string(),
+            latitude: 47.37,
+            longitude: 8.54,
+            country: "Switzerland".to_string(),
+            admin1: None,
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        }];
+        serde_json::to_string(&LogLine { key: key.to_string(), entry: CacheEntry { fetched_at, results } }).unwrap()
+    }
+
+    #[test]
+    fn spellings_of_the_same_place_share_a_key() {
+        assert_eq!(key(&query("  Zürich ")), key(&query("zurich")));
+        assert_eq!(key(&query("ZURICH @ch")), key(&query("zürich, country:CH")));
+        assert_ne!(key(&query("Zurich @CH")), key(&query("Zurich")));
+        assert_ne!(key(&query("Sao Paulo")), key(&query("SaoPaulo")));
+    }
+
+    #[test]
+    fn the_log_keeps_the_newest_live_line_per_key() {
+        let now = 100 * 86400;
+        let log = [
+            line("zurich|", now - 2 * 86400, "Old"),
+            line("zurich|", now - 86400, "Zürich"),
+            line("bern|", now - TTL_SECS, "Bern"),
+            "{\"key\": \"torn".to_string(),
+        ]
+        .join("\n");
+        let cache = replay(&log, now);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache["zurich|"].results[0].name, "Zürich");
+    }
+}
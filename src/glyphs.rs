@@ -0,0 +1,193 @@
+// Stand-ins for consoles without the fonts for braille charts, box drawing and weather emoji. Rather
+// than teach every widget two alphabets, the finished frame is rewritten cell by cell
+use ratatui::buffer::Buffer;
+
+use crate::config::Glyphs;
+
+// "auto" picks ASCII on the Linux console and other bare terminals, or under a locale that isn't UTF-8.
+// Real xterm (the only one that sets XTERM_VERSION) and rxvt draw lines fine but have no emoji font
+pub fn resolve(setting: Glyphs, term: Option<&str>, locale: Option<&str>, xterm: bool) -> Glyphs {
+    if setting != Glyphs::Auto {
+        return setting;
+    }
+    let bare_terminal = matches!(term, Some("linux" | "vt100" | "vt220" | "dumb"));
+    let utf8 = locale.is_none_or(|l| {
+        let l = l.to_ascii_lowercase();
+        l.contains("utf-8") || l.contains("utf8")
+    });
+    if bare_terminal || !utf8 {
+        Glyphs::Ascii
+    } else if xterm || term.is_some_and(|t| t.starts_with("rxvt")) {
+        Glyphs::NoEmoji
+    } else {
+        Glyphs::Unicode
+    }
+}
+
+// Every icon the UI draws comes from here, so a console without emoji never sees a literal one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Icon {
+    Title,
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Fog,
+    Rain,
+    Snow,
+    Thunder,
+    Warning,
+    Storm,
+    Done,
+    Failed,
+    Marked,
+    Favorite,
+}
+
+impl Icon {
+    // One symbol for the sky, where there's room for little else
+    pub fn sky(weather_code: u32) -> Icon {
+        match weather_code {
+            0 | 1 => Icon::Clear,
+            2 => Icon::PartlyCloudy,
+            45 | 48 => Icon::Fog,
+            51..=67 | 80..=82 => Icon::Rain,
+            71..=77 | 85 | 86 => Icon::Snow,
+            95..=99 => Icon::Thunder,
+            _ => Icon::Cloudy,
+        }
+    }
+
+    pub fn glyph(self, set: Glyphs) -> &'static str {
+        match (set, self) {
+            (Glyphs::Auto | Glyphs::Unicode, Icon::Title) => "🌤",
+            (Glyphs::Auto | Glyphs::Unicode, Icon::Clear) => "☀",
+            (Glyphs::Auto | Glyphs::Unicode, Icon::PartlyCloudy) => "⛅",
+            (Glyphs::Auto | Glyphs::Unicode, Icon::Cloudy) => "☁",
+            (Glyphs::Auto | Glyphs::Unicode, Icon::Fog) => "🌫",
+            (Glyphs::Auto | Glyphs::Unicode, Icon::Rain) => "🌧",
+            (Glyphs::Auto | Glyphs::Unicode, Icon::Snow) => "🌨",
+            (Glyphs::Auto | Glyphs::Unicode, Icon::Thunder) => "⛈",
+            // Plain text symbols that any Unicode font has, emoji or not
+            (Glyphs::Ascii, Icon::Warning) => "!",
+            (_, Icon::Warning) => "⚠",
+            (Glyphs::Ascii, Icon::Storm) => "!",
+            (_, Icon::Storm) => "⚑",
+            (Glyphs::Ascii, Icon::Done) => "v",
+            (_, Icon::Done) => "✓",
+            (Glyphs::Ascii, Icon::Failed) => "x",
+            (_, Icon::Failed) => "✗",
+            (Glyphs::Ascii, Icon::Marked) => "+",
+            (_, Icon::Marked) => "●",
+            (Glyphs::Ascii, Icon::Favorite) => "*",
+            (_, Icon::Favorite) => "★",
+            // The sky in letters
+            (_, Icon::Title) => "*",
+            (_, Icon::Clear) => "O",
+            (_, Icon::PartlyCloudy) => "o",
+            (_, Icon::Cloudy) => "~",
+            (_, Icon::Fog) => "=",
+            (_, Icon::Rain) => "/",
+            (_, Icon::Snow) => "*",
+            (_, Icon::Thunder) => "Z",
+        }
+    }
+}
+
+// The locale that decides the character set: the first of these that's set at all
+pub fn locale_from_env() -> Option<String> {
+    ["LC_ALL", "LC_CTYPE", "LANG"].iter().filter_map(|name| std::env::var(name).ok()).find(|v| !v.is_empty())
+}
+
+pub fn asciify(buf: &mut Buffer) {
+    for cell in buf.content.iter_mut() {
+        let Some(c) = cell.symbol().chars().next().filter(|c| !c.is_ascii()) else {
+            continue;
+        };
+        if let Some(ascii) = ascii(c) {
+            cell.set_char(ascii);
+        }
+    }
+}
+
+// Letters, ° and the rest of Latin-1 are left alone: any console that can show a city name can show those
+fn ascii(c: char) -> Option<char> {
+    Some(match c {
+        '\u{2800}'..='\u{28FF}' => braille(c as u32 - 0x2800),
+        '─' | '━' | '═' | '┄' | '┈' | '╌' => '-',
+        '│' | '┃' | '║' | '┆' | '┊' | '╎' => '|',
+        '\u{2500}'..='\u{257F}' => '+',
+        '\u{2580}'..='\u{259F}' => '#',
+        '←' => '<',
+        '→' => '>',
+        '↑' => '^',
+        '↓' => 'v',
+        '\u{2190}'..='\u{21FF}' => '*',
+        '•' => '*',
+        '…' => '.',
+        '✓' => 'v',
+        '✗' => 'x',
+        '⚠' | '⚑' => '!',
+        // Weather icons and everything else pictorial
+        '\u{2600}'..='\u{27BF}' | '\u{1F300}'..='\u{1FAFF}' => '*',
+        _ => return None,
+    })
+}
+
+// A braille cell is 2x4 dots; pick whatever ASCII sits in the same part of the cell
+fn braille(dots: u32) -> char {
+    const ROWS: [u32; 4] = [0x01 | 0x08, 0x02 | 0x10, 0x04 | 0x20, 0x40 | 0x80];
+    let rows: Vec<usize> = (0..4).filter(|&row| dots & ROWS[row] != 0).collect();
+    let (Some(&top), Some(&bottom)) = (rows.first(), rows.last()) else {
+        return ' ';
+    };
+    if bottom - top >= 2 {
+        return '|';
+    }
+    match top + bottom {
+        0 => '\'',
+        1..=4 => '-',
+        _ => '.',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, text::Line, widgets::{Block, Borders, Widget}};
+
+    #[test]
+    fn auto_goes_ascii_on_bare_consoles_and_other_charsets() {
+        let auto = |term, locale| resolve(Glyphs::Auto, term, locale, false);
+        assert_eq!(auto(Some("xterm-256color"), Some("en_GB.UTF-8")), Glyphs::Unicode);
+        assert_eq!(auto(Some("xterm-256color"), None), Glyphs::Unicode);
+        assert_eq!(auto(Some("linux"), Some("en_GB.UTF-8")), Glyphs::Ascii);
+        assert_eq!(auto(Some("xterm"), Some("de_DE.ISO-8859-1")), Glyphs::Ascii);
+        assert_eq!(auto(Some("rxvt-unicode-256color"), Some("en_GB.UTF-8")), Glyphs::NoEmoji);
+        assert_eq!(resolve(Glyphs::Auto, Some("xterm-256color"), Some("en_GB.UTF-8"), true), Glyphs::NoEmoji);
+        assert_eq!(resolve(Glyphs::Unicode, Some("linux"), Some("C"), true), Glyphs::Unicode);
+    }
+
+    #[test]
+    fn without_emoji_the_sky_is_spelled_out() {
+        let skies = (0..100).map(Icon::sky).chain([Icon::Title]);
+        for icon in skies {
+            assert!(icon.glyph(Glyphs::NoEmoji).is_ascii(), "{:?}", icon);
+        }
+        for icon in [Icon::Warning, Icon::Storm, Icon::Done, Icon::Failed] {
+            assert!(icon.glyph(Glyphs::Ascii).is_ascii(), "{:?}", icon);
+        }
+        assert_eq!(Icon::sky(95).glyph(Glyphs::Unicode), "⛈");
+        assert_eq!(Icon::Warning.glyph(Glyphs::NoEmoji), "⚠");
+    }
+
+    #[test]
+    fn frames_come_out_in_plain_ascii() {
+        let area = Rect::new(0, 0, 16, 3);
+        let mut buf = Buffer::empty(area);
+        Block::default().borders(Borders::ALL).render(area, &mut buf);
+        buf.set_line(1, 1, &Line::from("🌤 ⣀⠤⠒⠉ München"), 14);
+        asciify(&mut buf);
+        let rows: Vec<String> = (0..3).map(|y| (0..16).map(|x| buf[(x, y)].symbol()).collect()).collect();
+        assert_eq!(rows, ["+--------------+", "|* .--' München|", "+--------------+"]);
+    }
+}
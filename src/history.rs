@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fs, path::PathBuf};
+
+use crate::api::GeoLocation;
+use crate::config;
+use crate::units::Units;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub query: String,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub location: Option<GeoLocation>,
+    #[serde(default)]
+    pub favorite: bool,
+    // 0 in files from before searches were counted, which is read as once
+    #[serde(default)]
+    pub searches: u32,
+    // 0 in those older files too, which falls back to `timestamp`
+    #[serde(default)]
+    pub first_searched: u64,
+    // Shown in these instead of the configured units, e.g. °F for a trip to the US
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<Units>,
+}
+
+impl HistoryEntry {
+    pub fn new(query: String, timestamp: u64, location: Option<GeoLocation>) -> HistoryEntry {
+        HistoryEntry { query, timestamp, location, favorite: false, searches: 1, first_searched: timestamp, units: None }
+    }
+
+    pub fn times_searched(&self) -> u32 {
+        self.searches.max(1)
+    }
+
+    pub fn first_seen(&self) -> u64 {
+        if self.first_searched == 0 { self.timestamp } else { self.first_searched }
+    }
+}
+
+pub const MAX_ENTRIES: usize = 50;
+const WEEK: f64 = 7.0 * 24.0 * 3600.0;
+
+// Most recent first, without duplicates, capped at MAX_ENTRIES. A place searched again keeps its
+// count, first date and units, a favorite stays one, and favorites are never what falls off the end
+pub fn push_entry(history: &mut Vec<HistoryEntry>, mut entry: HistoryEntry) {
+    if let Some(previous) = history.iter().find(|e| e.query == entry.query) {
+        entry.favorite |= previous.favorite;
+        entry.units = entry.units.or(previous.units);
+        entry.searches = previous.times_searched() + 1;
+        entry.first_searched = previous.first_seen();
+    }
+    history.retain(|e| e.query != entry.query);
+    history.insert(0, entry);
+    while history.len() > MAX_ENTRIES {
+        match history.iter().rposition(|e| !e.favorite) {
+            Some(oldest) => history.remove(oldest),
+            None => history.remove(MAX_ENTRIES),
+        };
+    }
+}
+
+// What the stats popup shows, from what's still in the history
+pub struct Stats {
+    // Most searched first, at most five
+    pub top: Vec<(String, u32)>,
+    pub total: u32,
+    pub per_week: f64,
+    pub first_used: Option<u64>,
+}
+
+pub fn stats(history: &[HistoryEntry], now: u64) -> Stats {
+    let mut top: Vec<(String, u32)> = history.iter().map(|e| (e.query.clone(), e.times_searched())).collect();
+    // Stable, so a tie goes to the one searched most recently
+    top.sort_by_key(|&(_, times)| std::cmp::Reverse(times));
+    top.truncate(5);
+    let total = history.iter().map(HistoryEntry::times_searched).sum();
+    let first_used = history.iter().map(HistoryEntry::first_seen).min();
+    // Counted over a week at least, or a keen first afternoon works out at hundreds a week
+    let weeks = first_used.map_or(1.0, |first| (now.saturating_sub(first) as f64 / WEEK).max(1.0));
+    Stats { top, total, per_week: total as f64 / weeks, first_used }
+}
+
+pub fn get_history_path() -> PathBuf {
+    if let Some(mut path) = config::profile_dir() {
+        path.push("history.json");
+        return path;
+    }
+    let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push(".weather_searcher_history.json");
+    path
+}
+
+// Set with :home set; takes over from `home` in config.toml
+pub fn get_home_path() -> PathBuf {
+    if let Some(mut path) = config::profile_dir() {
+        path.push("home.json");
+        return path;
+    }
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("home.json");
+    path
+}
+
+pub fn load_home() -> Result<Option<GeoLocation>, Box<dyn Error>> {
+    let path = get_home_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+pub fn save_home(home: &GeoLocation) -> Result<(), Box<dyn Error>> {
+    let path = get_home_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(home)?)?;
+    Ok(())
+}
+
+pub fn load_history() -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+    let path = get_history_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    let history: Vec<HistoryEntry> = serde_json::from_str(&content)?;
+    Ok(history)
+}
+
+pub fn save_history(history: &[HistoryEntry]) -> Result<(), Box<dyn Error>> {
+    let path = get_history_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_string_pretty(history)?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(query: &str, favorite: bool) -> HistoryEntry {
+        HistoryEntry { favorite, ..HistoryEntry::new(query.to_string(), 0, None) }
+    }
+
+    #[test]
+    fn favorites_survive_a_full_history_and_a_repeat_search() {
+        let mut history = vec![entry("Tromsø", true)];
+        for i in 0..MAX_ENTRIES {
+            push_entry(&mut history, entry(&format!("City {}", i), false));
+        }
+        assert_eq!(history.len(), MAX_ENTRIES);
+        assert_eq!(history.last().map(|e| e.query.as_str()), Some("Tromsø"));
+
+        push_entry(&mut history, entry("Tromsø", false));
+        assert_eq!(history[0].query, "Tromsø");
+        assert!(history[0].favorite);
+    }
+
+    #[test]
+    fn stats_count_repeat_searches_since_the_first() {
+        const DAY: u64 = 24 * 3600;
+        let mut history = Vec::new();
+        for (query, day) in [("Berlin", 0), ("Bergen", 3), ("Berlin", 10), ("Oslo", 12), ("Berlin", 13)] {
+            push_entry(&mut history, HistoryEntry::new(query.to_string(), 1_000_000 + day * DAY, None));
+        }
+        // An entry saved before searches were counted
+        history.push(HistoryEntry { searches: 0, first_searched: 0, ..HistoryEntry::new("Tromsø".to_string(), 1_000_000 + DAY, None) });
+
+        let stats = stats(&history, 1_000_000 + 14 * DAY);
+        assert_eq!(stats.top[..2], [("Berlin".to_string(), 3), ("Oslo".to_string(), 1)]);
+        assert_eq!(stats.total, 6);
+        assert_eq!(stats.per_week, 3.0);
+        assert_eq!(stats.first_used, Some(1_000_000));
+    }
+}
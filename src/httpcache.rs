@@ -0,0 +1,42 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+// Bodies are a few tens of KB each; a kiosk wall plus the odd storm map fits easily
+const MAX_ENTRIES: usize = 64;
+
+// The last answer for a URL together with whatever the server gave us to revalidate it
+#[derive(Clone)]
+pub struct CachedResponse {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: Vec<u8>,
+    stored_at: Instant,
+}
+
+static CACHE: OnceLock<Mutex<HashMap<String, CachedResponse>>> = OnceLock::new();
+
+fn cache() -> &'static Mutex<HashMap<String, CachedResponse>> {
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn get(url: &str) -> Option<CachedResponse> {
+    cache().lock().ok()?.get(url).cloned()
+}
+
+// Responses without an ETag or Last-Modified can't be revalidated, so there's no point keeping them
+pub fn store(url: &str, etag: Option<String>, last_modified: Option<String>, body: &[u8]) {
+    if etag.is_none() && last_modified.is_none() {
+        return;
+    }
+    let Ok(mut cache) = cache().lock() else { return };
+    if cache.len() >= MAX_ENTRIES && !cache.contains_key(url) {
+        if let Some(oldest) = cache.iter().min_by_key(|(_, r)| r.stored_at).map(|(k, _)| k.clone()) {
+            cache.remove(&oldest);
+        }
+    }
+    let response = CachedResponse { etag, last_modified, body: body.to_vec(), stored_at: Instant::now() };
+    cache.insert(url.to_string(), response);
+}
@@ -0,0 +1,166 @@
+// The daily forecast and rule windows as an iCalendar file, for `forecast --ics`. Days are all-day
+// events that don't block time; re-importing replaces them, since each day's UID is its date and place
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::api::{self, DailyForecast, GeoLocation};
+use crate::config::Glyphs;
+use crate::glyphs::Icon;
+use crate::rules::Window;
+use crate::series::TimeSeries;
+use crate::units::Units;
+
+pub fn calendar(
+    place: &str,
+    location: &GeoLocation,
+    daily: &DailyForecast,
+    units: Units,
+    windows: &[Window],
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//weather-searcher//forecast//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(&format!("Weather in {}", place))),
+    ];
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let uid = |what: &str| format!("{}-{:.3},{:.3}@weather-searcher", what, location.latitude, location.longitude);
+
+    let value_at = |series: &TimeSeries<f64>, date: &str| series.at(date).copied().unwrap_or(f64::NAN);
+    for (date, &code) in daily.weather_code.iter() {
+        let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+            continue;
+        };
+        let (min, max) = (value_at(&daily.temperature_min, date), value_at(&daily.temperature_max, date));
+        let (min, max) = (units.temperature(min), units.temperature(max));
+        let sky = match api::severe_weather(code) {
+            Some(severe) => format!("{} {}", Icon::Warning.glyph(Glyphs::Unicode), severe),
+            None => format!("{} {}", Icon::sky(code).glyph(Glyphs::Unicode), api::weather_code_to_description(code)),
+        };
+        let summary = format!("{} {:.0}–{:.0}{}", sky, min, max, units.temperature_unit());
+        let description = format!(
+            "Rain {:.1} {}, wind up to {:.0} {}",
+            value_at(&daily.precipitation, date),
+            daily.precipitation.unit,
+            units.wind_speed(value_at(&daily.wind_speed_max, date)),
+            units.wind_speed_unit()
+        );
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", uid(&day.format("%Y%m%d").to_string())),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", day.succ_opt().unwrap_or(day).format("%Y%m%d")),
+            format!("SUMMARY:{}", escape(&summary)),
+            format!("DESCRIPTION:{}", escape(&description)),
+            format!("LOCATION:{}", escape(place)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    // Forecast times are the place's own clock, so they're written without a zone ("floating")
+    // and show at the same wall-clock time wherever the calendar is
+    let local = |time: &NaiveDateTime| time.format("%Y%m%dT%H%M%S").to_string();
+    for (i, window) in windows.iter().enumerate() {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", uid(&format!("alert-{}-{}", local(&window.start), i))),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART:{}", local(&window.start)),
+            format!("DTEND:{}", local(&window.end)),
+            format!("SUMMARY:{}", escape(&format!("{} {}", Icon::Warning.glyph(Glyphs::Unicode), window.message))),
+            format!("LOCATION:{}", escape(place)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+// Lines end in CRLF and go no longer than 75 bytes; the rest continues after a space on the next
+fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_day_and_an_alert_window() {
+        let days = |values: Vec<f64>, unit| TimeSeries::new(vec!["2024-05-01".to_string()], values, unit);
+        let daily = DailyForecast {
+            weather_code: TimeSeries::new(vec!["2024-05-01".to_string()], vec![95], ""),
+            temperature_max: days(vec![17.2], "°C"),
+            temperature_min: days(vec![8.6], "°C"),
+            precipitation: days(vec![12.4], "mm"),
+            wind_speed_max: days(vec![41.0], "km/h"),
+        };
+        let location = GeoLocation {
+            name: "Bern".to_string(),
+            latitude: 46.948,
+            longitude: 7.447,
+            country: "Switzerland".to_string(),
+            admin1: None,
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        };
+        let at = |time| NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").unwrap();
+        let window = || Window {
+            start: at("2024-05-01T15:00"),
+            end: at("2024-05-01T18:00"),
+            message: "Windy: wind speed up to 41.0".to_string(),
+        };
+        let now = DateTime::from_timestamp(1714500000, 0).unwrap();
+        let ics = calendar("Bern, Switzerland", &location, &daily, Units::METRIC, &[window()], now);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n") && ics.ends_with("END:VCALENDAR\r\n"));
+        for line in [
+            "UID:20240501-46.948,7.447@weather-searcher",
+            "DTSTAMP:20240430T180000Z",
+            "DTSTART;VALUE=DATE:20240501",
+            "DTEND;VALUE=DATE:20240502",
+            "SUMMARY:⚠ Thunderstorm 9–17°C",
+            "DESCRIPTION:Rain 12.4 mm\\, wind up to 41 km/h",
+            "LOCATION:Bern\\, Switzerland",
+            "DTSTART:20240501T150000",
+            "DTEND:20240501T180000",
+            "SUMMARY:⚠ Windy: wind speed up to 41.0",
+        ] {
+            assert!(ics.contains(&format!("\r\n{}\r\n", line)), "{}", line);
+        }
+
+        let ics = calendar("Bern, Switzerland", &location, &daily, Units::IMPERIAL, &[window()], now);
+        assert!(ics.contains("\r\nSUMMARY:⚠ Thunderstorm 47–63°F\r\n"), "{}", ics);
+        assert!(ics.contains("\r\nDESCRIPTION:Rain 12.4 mm\\, wind up to 25 mph\r\n"), "{}", ics);
+    }
+
+    #[test]
+    fn long_lines_fold_without_splitting_characters() {
+        let folded = fold(&format!("SUMMARY:{}", "°".repeat(40)));
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].len() <= 75 && lines[1].starts_with(' ') && lines[2].is_empty());
+        assert_eq!(folded.replace("\r\n ", ""), format!("SUMMARY:{}\r\n", "°".repeat(40)));
+    }
+}
@@ -0,0 +1,46 @@
+// Everything except terminal setup, so benchmarks and tests can reach it too
+pub mod action;
+pub mod api;
+pub mod app;
+pub mod barograph;
+pub mod bigtext;
+pub mod bugreport;
+pub mod cli;
+pub mod colors;
+pub mod comfort;
+pub mod config;
+pub mod demo;
+pub mod effects;
+pub mod events;
+pub mod faults;
+pub mod flatbuf;
+pub mod focus;
+pub mod forecast;
+pub mod gazetteer;
+pub mod geo;
+pub mod geocache;
+pub mod glyphs;
+pub mod fuzzy;
+pub mod history;
+pub mod httpcache;
+pub mod ics;
+pub mod plugins;
+pub mod popup;
+pub mod ranking;
+pub mod records;
+pub mod report;
+pub mod roads;
+pub mod route;
+pub mod rules;
+pub mod runtime;
+pub mod schema;
+pub mod score;
+pub mod series;
+pub mod session;
+pub mod snapshot;
+pub mod summary;
+pub mod theme;
+pub mod template;
+pub mod units;
+pub mod view;
+pub mod waypoints;
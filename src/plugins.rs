@@ -0,0 +1,164 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::{process::Stdio, time::Duration};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::config::Plugin;
+
+// Plugins that take longer than this are killed and shown as failed
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// What a plugin prints on stdout, e.g. {"title": "Surf", "rows": [["Swell", "1.2 m"], ["Water", 17]]}
+#[derive(Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    title: Option<String>,
+    rows: Vec<(String, Value)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginPanel {
+    pub title: String,
+    pub rows: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginResult {
+    pub name: String,
+    pub panel: Result<PluginPanel, String>,
+}
+
+// The platform shell, so commands in config can use pipes and ~ like anywhere else
+pub fn shell(command: &str) -> tokio::process::Command {
+    #[cfg(unix)]
+    let mut process = tokio::process::Command::new("sh");
+    #[cfg(unix)]
+    process.arg("-c").arg(command);
+    #[cfg(not(unix))]
+    let mut process = tokio::process::Command::new("cmd");
+    #[cfg(not(unix))]
+    process.arg("/C").arg(command);
+    process
+}
+
+// The desktop's way of opening a link in whatever it opens links with
+pub fn opener(url: &str) -> String {
+    if cfg!(target_os = "macos") {
+        format!("open '{}'", url)
+    } else if cfg!(windows) {
+        format!("start \"\" \"{}\"", url)
+    } else {
+        format!("xdg-open '{}'", url)
+    }
+}
+
+// Runs a command for its effect, like a browser opening; nothing comes back but whether it worked
+pub async fn launch(command: &str) -> Result<(), String> {
+    let status = shell(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| format!("`{}` didn't start: {}", command, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("`{}` exited with {}", command, status))
+    }
+}
+
+// Feeds the weather JSON to the plugin on stdin and reads one panel back from stdout
+pub async fn run(plugin: &Plugin, input: &Value) -> Result<PluginPanel, String> {
+    run_within(plugin, input, TIMEOUT).await
+}
+
+async fn run_within(plugin: &Plugin, input: &Value, limit: Duration) -> Result<PluginPanel, String> {
+    let mut child = shell(&plugin.command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("didn't start: {}", e))?;
+
+    let mut stdin = child.stdin.take().ok_or("no stdin")?;
+    let mut stdout = child.stdout.take().ok_or("no stdout")?;
+    let exchange = async {
+        // A plugin that ignores its input closes the pipe early, which is fine
+        let _ = stdin.write_all(input.to_string().as_bytes()).await;
+        drop(stdin);
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).await.map_err(|e| e.to_string())?;
+        let status = child.wait().await.map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("exited with {}", status));
+        }
+        Ok(output)
+    };
+    // Dropping the exchange on timeout drops the child too, which kills it
+    let output = tokio::time::timeout(limit, exchange)
+        .await
+        .map_err(|_| format!("no answer within {} seconds", limit.as_secs()))??;
+    parse(&plugin.name, &output)
+}
+
+fn parse(name: &str, output: &str) -> Result<PluginPanel, String> {
+    let output: PluginOutput = serde_json::from_str(output).map_err(|e| format!("unreadable output: {}", e))?;
+    let rows = output
+        .rows
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s,
+                Value::Null => "-".to_string(),
+                other => other.to_string(),
+            };
+            (key, value)
+        })
+        .collect();
+    Ok(PluginPanel { title: output.title.unwrap_or_else(|| name.to_string()), rows })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn plugin(command: &str) -> Plugin {
+        Plugin { name: "surf".to_string(), command: command.to_string() }
+    }
+
+    #[test]
+    fn rows_keep_strings_and_print_everything_else() {
+        let panel = parse("surf", r#"{"title": "Surf", "rows": [["Swell", "1.2 m"], ["Water", 17], ["Rip", null], ["Flag", true]]}"#).unwrap();
+        assert_eq!(panel.title, "Surf");
+        let rows: Vec<(&str, &str)> = panel.rows.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(rows, [("Swell", "1.2 m"), ("Water", "17"), ("Rip", "-"), ("Flag", "true")]);
+    }
+
+    #[test]
+    fn the_plugin_name_stands_in_for_a_missing_title() {
+        assert_eq!(parse("surf", r#"{"rows": []}"#).unwrap().title, "surf");
+        assert!(parse("surf", r#"{"title": "Surf"}"#).unwrap_err().starts_with("unreadable output"));
+        assert!(parse("surf", "Swell: 1.2 m").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn a_plugin_reads_the_weather_and_answers_on_stdout() {
+        let echo = plugin(r#"read -r input; case "$input" in *17.5*) echo '{"rows": [["Seen", "yes"]]}' ;; esac"#);
+        let panel = run(&echo, &json!({"temperature": 17.5})).await.unwrap();
+        assert_eq!(panel.rows, [("Seen".to_string(), "yes".to_string())]);
+        assert!(run(&plugin("exit 1"), &json!({})).await.unwrap_err().starts_with("exited with"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn a_stuck_plugin_fails_instead_of_loading_forever() {
+        let started = std::time::Instant::now();
+        let e = run_within(&plugin("sleep 30"), &json!({}), Duration::from_millis(200)).await.unwrap_err();
+        assert!(e.starts_with("no answer within"), "{}", e);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
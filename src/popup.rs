@@ -0,0 +1,263 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::fuzzy;
+
+// Modal overlays drawn on top of the main UI. While one is open it gets every key,
+// and reports back what the user chose through PopupOutcome.
+pub enum PopupKind<A> {
+    Confirm { message: String, action: A },
+    Picker { items: Vec<String>, selected: usize, action: A },
+    Help { lines: Vec<Line<'static>>, scroll: u16 },
+    // A picker filtered by typing; `matches` holds indices into `items`, best first
+    Palette { query: String, items: Vec<String>, matches: Vec<usize>, selected: usize, action: A },
+}
+
+pub struct Popup<A> {
+    pub title: String,
+    pub kind: PopupKind<A>,
+}
+
+pub enum PopupOutcome<A> {
+    // Key was handled and the popup stays open
+    Consumed,
+    Closed,
+    Confirmed(A),
+    Picked(A, usize),
+}
+
+impl<A: Clone> Popup<A> {
+    pub fn confirm(title: &str, message: String, action: A) -> Popup<A> {
+        Popup {
+            title: title.to_string(),
+            kind: PopupKind::Confirm { message, action },
+        }
+    }
+
+    pub fn picker(title: &str, items: Vec<String>, action: A) -> Popup<A> {
+        Popup {
+            title: title.to_string(),
+            kind: PopupKind::Picker { items, selected: 0, action },
+        }
+    }
+
+    pub fn palette(title: &str, items: Vec<String>, action: A) -> Popup<A> {
+        let matches = (0..items.len()).collect();
+        Popup {
+            title: title.to_string(),
+            kind: PopupKind::Palette { query: String::new(), items, matches, selected: 0, action },
+        }
+    }
+
+    pub fn help(title: &str, lines: Vec<Line<'static>>) -> Popup<A> {
+        Popup {
+            title: title.to_string(),
+            kind: PopupKind::Help { lines, scroll: 0 },
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PopupOutcome<A> {
+        match &mut self.kind {
+            PopupKind::Confirm { action, .. } => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    PopupOutcome::Confirmed(action.clone())
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                    PopupOutcome::Closed
+                }
+                _ => PopupOutcome::Consumed,
+            },
+            PopupKind::Picker { items, selected, action } => match key.code {
+                KeyCode::Down | KeyCode::Char('j') => {
+                    if !items.is_empty() {
+                        *selected = (*selected + 1) % items.len();
+                    }
+                    PopupOutcome::Consumed
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    if !items.is_empty() {
+                        *selected = selected.checked_sub(1).unwrap_or(items.len() - 1);
+                    }
+                    PopupOutcome::Consumed
+                }
+                KeyCode::Enter if !items.is_empty() => PopupOutcome::Picked(action.clone(), *selected),
+                KeyCode::Esc | KeyCode::Char('q') => PopupOutcome::Closed,
+                _ => PopupOutcome::Consumed,
+            },
+            PopupKind::Palette { query, items, matches, selected, action } => {
+                let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                let len = matches.len().max(1);
+                match key.code {
+                    KeyCode::Enter => {
+                        return match matches.get(*selected) {
+                            Some(&index) => PopupOutcome::Picked(action.clone(), index),
+                            None => PopupOutcome::Consumed,
+                        };
+                    }
+                    KeyCode::Esc => return PopupOutcome::Closed,
+                    KeyCode::Down | KeyCode::Tab => *selected = (*selected + 1) % len,
+                    KeyCode::Char('n') if ctrl => *selected = (*selected + 1) % len,
+                    KeyCode::Up | KeyCode::BackTab => *selected = (*selected + len - 1) % len,
+                    KeyCode::Char('p') if ctrl => *selected = (*selected + len - 1) % len,
+                    KeyCode::Backspace => {
+                        query.pop();
+                        *matches = palette_matches(items, query);
+                        *selected = 0;
+                    }
+                    KeyCode::Char(c) if !ctrl => {
+                        query.push(c);
+                        *matches = palette_matches(items, query);
+                        *selected = 0;
+                    }
+                    _ => {}
+                }
+                PopupOutcome::Consumed
+            }
+            PopupKind::Help { lines, scroll } => match key.code {
+                KeyCode::Down | KeyCode::Char('j') => {
+                    if (*scroll as usize) + 1 < lines.len() {
+                        *scroll += 1;
+                    }
+                    PopupOutcome::Consumed
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    *scroll = scroll.saturating_sub(1);
+                    PopupOutcome::Consumed
+                }
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('?') => {
+                    PopupOutcome::Closed
+                }
+                _ => PopupOutcome::Consumed,
+            },
+        }
+    }
+
+    // Percentage of the screen the popup covers
+    fn size(&self) -> (u16, u16) {
+        match &self.kind {
+            PopupKind::Confirm { .. } => (50, 20),
+            PopupKind::Picker { .. } => (50, 50),
+            PopupKind::Palette { .. } => (60, 60),
+            PopupKind::Help { .. } => (70, 70),
+        }
+    }
+
+    // After a resize, keep the help text from scrolling past its last screenful
+    pub fn clamp_scroll(&mut self, screen_width: u16, screen_height: u16) {
+        let (width, height) = self.size();
+        let area = centered_rect(width, height, Rect::new(0, 0, screen_width, screen_height));
+        if let PopupKind::Help { lines, scroll } = &mut self.kind {
+            let visible = area.height.saturating_sub(2) as usize;
+            let max_scroll = lines.len().saturating_sub(visible.max(1));
+            *scroll = (*scroll).min(max_scroll as u16);
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame) {
+        let (width, height) = self.size();
+        let area = centered_rect(width, height, f.area());
+        f.render_widget(Clear, area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(self.title.as_str());
+
+        match &self.kind {
+            PopupKind::Confirm { message, .. } => {
+                let text = vec![
+                    Line::from(message.as_str()),
+                    Line::from(""),
+                    Line::from("y/Enter = yes | n/ESC = no").style(Style::default().fg(Color::DarkGray)),
+                ];
+                let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+                f.render_widget(paragraph, area);
+            }
+            PopupKind::Picker { items, selected, .. } => {
+                let list_items: Vec<ListItem> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let style = if i == *selected {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default().fg(Color::White)
+                        };
+                        ListItem::new(item.as_str()).style(style)
+                    })
+                    .collect();
+                f.render_widget(List::new(list_items).block(block), area);
+            }
+            PopupKind::Help { lines, scroll } => {
+                let paragraph = Paragraph::new(lines.clone()).block(block).scroll((*scroll, 0));
+                f.render_widget(paragraph, area);
+            }
+            PopupKind::Palette { query, items, matches, selected, .. } => {
+                let inner = block.inner(area);
+                f.render_widget(block, area);
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(2), Constraint::Min(0)])
+                    .split(inner);
+
+                let prompt = Paragraph::new(format!("> {}█", query))
+                    .style(Style::default().fg(Color::Yellow))
+                    .block(Block::default().borders(Borders::BOTTOM));
+                f.render_widget(prompt, chunks[0]);
+
+                let list_items: Vec<ListItem> = matches
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &index)| {
+                        let style = if i == *selected {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default().fg(Color::White)
+                        };
+                        ListItem::new(items[index].as_str()).style(style)
+                    })
+                    .collect();
+                let mut state = ListState::default().with_selected(Some(*selected));
+                f.render_stateful_widget(List::new(list_items), chunks[1], &mut state);
+            }
+        }
+    }
+}
+
+// Items the query fuzzy-matches, best first; everything in its original order for an empty query
+fn palette_matches(items: &[String], query: &str) -> Vec<usize> {
+    let query = fuzzy::normalize(query);
+    let mut scored: Vec<(i64, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| fuzzy::fuzzy_match(&fuzzy::normalize(item), &query).map(|(score, _)| (score, i)))
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
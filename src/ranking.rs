@@ -0,0 +1,137 @@
+use std::cmp::Reverse;
+
+use crate::api::GeoLocation;
+use crate::fuzzy;
+use crate::geo;
+use crate::history::HistoryEntry;
+
+// A place picked before beats a slightly better-matching name, and the last few picks a bit more
+const PICKED_BOOST: i64 = 10;
+const RECENT_BOOST: i64 = 5;
+
+// Candidates that match what's typed, best first, with places from the history floated up
+// and repeats of the same place dropped
+pub fn rank(candidates: &[GeoLocation], query: &str, history: &[HistoryEntry]) -> Vec<GeoLocation> {
+    let query = fuzzy::normalize(query);
+    let mut scored: Vec<(i64, &GeoLocation)> = candidates
+        .iter()
+        .filter_map(|c| {
+            let (score, _) = fuzzy::fuzzy_match(&fuzzy::normalize(&c.name), &query)?;
+            Some((score + history_boost(c, history), c))
+        })
+        .collect();
+    // Stable, so ties keep the order the source gave them
+    scored.sort_by_key(|&(score, _)| Reverse(score));
+
+    // The same place can come back twice, e.g. from the city list and the geocoder
+    let mut ranked: Vec<GeoLocation> = Vec::new();
+    for (_, c) in scored {
+        if !ranked.iter().any(|r| same_place(r, c)) {
+            ranked.push(c.clone());
+        }
+    }
+    ranked
+}
+
+fn history_boost(candidate: &GeoLocation, history: &[HistoryEntry]) -> i64 {
+    history
+        .iter()
+        .position(|e| e.location.as_ref().is_some_and(|l| same_place(l, candidate)))
+        .map_or(0, |i| PICKED_BOOST + (RECENT_BOOST - i as i64).max(0))
+}
+
+// The most recent place from the history with this name, so a bare "springfield" means the
+// Springfield picked last time rather than whichever one the geocoder lists first
+pub fn previous_choice(name: &str, history: &[HistoryEntry]) -> Option<GeoLocation> {
+    let name = fuzzy::normalize(name);
+    history
+        .iter()
+        .filter_map(|e| e.location.as_ref())
+        .find(|l| l.near_km.is_none() && fuzzy::normalize(&l.name) == name)
+        .cloned()
+}
+
+pub fn same_place(a: &GeoLocation, b: &GeoLocation) -> bool {
+    a.name == b.name
+        && a.country == b.country
+        && a.admin1 == b.admin1
+        && geo::distance_km(a.latitude, a.longitude, b.latitude, b.longitude) < 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(name: &str, admin1: &str, latitude: f64) -> GeoLocation {
+        GeoLocation {
+            name: name.to_string(),
+            latitude,
+            longitude: -90.0,
+            country: "United States".to_string(),
+            admin1: Some(admin1.to_string()),
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        }
+    }
+
+    fn picked(location: &GeoLocation) -> HistoryEntry {
+        HistoryEntry::new(location.name.clone(), 0, Some(location.clone()))
+    }
+
+    fn admin1s(ranked: &[GeoLocation]) -> Vec<&str> {
+        ranked.iter().map(|p| p.admin1.as_deref().unwrap_or("")).collect()
+    }
+
+    #[test]
+    fn without_history_the_source_order_stands() {
+        let candidates = [place("Springfield", "Missouri", 37.2), place("Springfield", "Illinois", 39.8)];
+        assert_eq!(admin1s(&rank(&candidates, "spring", &[])), ["Missouri", "Illinois"]);
+    }
+
+    #[test]
+    fn a_previous_pick_floats_to_the_top() {
+        let candidates = [
+            place("Springfield", "Missouri", 37.2),
+            place("Springfield", "Massachusetts", 42.1),
+            place("Springfield", "Illinois", 39.8),
+        ];
+        let history = [picked(&candidates[2])];
+        assert_eq!(admin1s(&rank(&candidates, "spring", &history)), ["Illinois", "Missouri", "Massachusetts"]);
+    }
+
+    #[test]
+    fn a_pick_beats_a_slightly_closer_name() {
+        let candidates = [place("Spring", "Texas", 30.1), place("Springfield", "Illinois", 39.8)];
+        let history = [picked(&candidates[1])];
+        assert_eq!(admin1s(&rank(&candidates, "spring", &history)), ["Illinois", "Texas"]);
+    }
+
+    #[test]
+    fn the_more_recent_pick_wins() {
+        let candidates = [place("Springfield", "Missouri", 37.2), place("Springfield", "Illinois", 39.8)];
+        let history = [picked(&candidates[0]), picked(&candidates[1])];
+        assert_eq!(admin1s(&rank(&candidates, "spring", &history)), ["Missouri", "Illinois"]);
+    }
+
+    #[test]
+    fn history_never_brings_back_a_name_that_does_not_match() {
+        let candidates = [place("Springfield", "Illinois", 39.8), place("Chicago", "Illinois", 41.9)];
+        let history = [picked(&candidates[1])];
+        assert_eq!(rank(&candidates, "spring", &history).len(), 1);
+    }
+
+    #[test]
+    fn the_same_place_twice_is_listed_once() {
+        let candidates = [place("Springfield", "Illinois", 39.8), place("Springfield", "Illinois", 39.801)];
+        assert_eq!(rank(&candidates, "spring", &[]).len(), 1);
+    }
+
+    #[test]
+    fn previous_choice_ignores_case_and_accents() {
+        let zurich = GeoLocation { country: "Switzerland".to_string(), ..place("Zürich", "Zurich", 47.4) };
+        let history = [picked(&zurich)];
+        assert_eq!(previous_choice("zurich", &history).map(|l| l.country), Some("Switzerland".to_string()));
+        assert!(previous_choice("zug", &history).is_none());
+    }
+}
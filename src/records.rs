@@ -0,0 +1,225 @@
+use rusqlite::{params, Connection};
+use std::{error::Error, fs, path::PathBuf};
+
+use crate::api::WeatherData;
+use crate::config;
+
+// One row of the local observation archive
+pub struct Observation {
+    pub name: String,
+    pub country: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    // Local time reported by the API, e.g. "2024-05-01T14:15"
+    pub observed_at: String,
+    pub fetched_at: u64,
+    pub temperature: f64,
+    pub apparent_temperature: f64,
+    pub humidity: u32,
+    pub precipitation: f64,
+    pub weather_code: u32,
+    pub wind_speed: f64,
+    pub pressure: f64,
+    // Hourly temperatures forecast 18-30 hours ahead of observed_at, kept to score later
+    pub forecast: Vec<(String, f64)>,
+}
+
+// Range of lead times kept from each fetch; accuracy prefers whichever is closest to 24
+const FORECAST_LEAD_HOURS: std::ops::RangeInclusive<usize> = 18..=30;
+
+impl Observation {
+    pub fn from_weather(data: &WeatherData) -> Observation {
+        let current = &data.weather.current;
+        Observation {
+            name: data.location.name.clone(),
+            country: data.location.country.clone(),
+            latitude: data.location.latitude,
+            longitude: data.location.longitude,
+            observed_at: current.time.clone(),
+            fetched_at: data.fetched_at,
+            temperature: current.temperature_2m,
+            apparent_temperature: current.apparent_temperature,
+            humidity: current.relative_humidity_2m,
+            precipitation: current.precipitation,
+            weather_code: current.weather_code,
+            wind_speed: current.wind_speed_10m,
+            pressure: current.pressure_msl,
+            forecast: ahead_forecast(data),
+        }
+    }
+}
+
+fn ahead_forecast(data: &WeatherData) -> Vec<(String, f64)> {
+    let Some(hourly) = &data.weather.hourly else {
+        return Vec::new();
+    };
+    let Some(now) = hourly.temperature.slot_of(&data.weather.current.time) else {
+        return Vec::new();
+    };
+    hourly
+        .temperature
+        .iter()
+        .skip(now + FORECAST_LEAD_HOURS.start())
+        .take(FORECAST_LEAD_HOURS.count())
+        .map(|(time, temperature)| (time.to_string(), *temperature))
+        .collect()
+}
+
+pub fn get_records_path() -> PathBuf {
+    if let Some(mut path) = config::profile_dir() {
+        path.push("observations.db");
+        return path;
+    }
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("weather-tui");
+    path.push("observations.db");
+    path
+}
+
+pub fn open_records() -> Result<Connection, Box<dyn Error>> {
+    let path = get_records_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let conn = Connection::open(path)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS observations (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            country TEXT NOT NULL,
+            latitude REAL NOT NULL,
+            longitude REAL NOT NULL,
+            observed_at TEXT NOT NULL,
+            fetched_at INTEGER NOT NULL,
+            temperature REAL NOT NULL,
+            apparent_temperature REAL NOT NULL,
+            humidity INTEGER NOT NULL,
+            precipitation REAL NOT NULL,
+            weather_code INTEGER NOT NULL,
+            wind_speed REAL NOT NULL,
+            pressure REAL NOT NULL,
+            UNIQUE (latitude, longitude, observed_at)
+        );
+        CREATE INDEX IF NOT EXISTS observations_by_place
+            ON observations (latitude, longitude, fetched_at);
+        CREATE TABLE IF NOT EXISTS forecasts (
+            id INTEGER PRIMARY KEY,
+            latitude REAL NOT NULL,
+            longitude REAL NOT NULL,
+            issued_at TEXT NOT NULL,
+            valid_at TEXT NOT NULL,
+            temperature REAL NOT NULL,
+            UNIQUE (latitude, longitude, issued_at, valid_at)
+        );
+        CREATE INDEX IF NOT EXISTS forecasts_by_place
+            ON forecasts (latitude, longitude, valid_at);",
+    )?;
+    Ok(conn)
+}
+
+// Refreshing within the same 15-minute model step returns the same observation, so
+// duplicates are ignored rather than stored twice
+pub fn record_observation(observation: &Observation) -> Result<(), Box<dyn Error>> {
+    let mut conn = open_records()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT OR IGNORE INTO observations (
+            name, country, latitude, longitude, observed_at, fetched_at, temperature,
+            apparent_temperature, humidity, precipitation, weather_code, wind_speed, pressure
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            observation.name,
+            observation.country,
+            observation.latitude,
+            observation.longitude,
+            observation.observed_at,
+            observation.fetched_at as i64,
+            observation.temperature,
+            observation.apparent_temperature,
+            observation.humidity,
+            observation.precipitation,
+            observation.weather_code,
+            observation.wind_speed,
+            observation.pressure,
+        ],
+    )?;
+    for (valid_at, temperature) in &observation.forecast {
+        tx.execute(
+            "INSERT OR IGNORE INTO forecasts (latitude, longitude, issued_at, valid_at, temperature)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![observation.latitude, observation.longitude, observation.observed_at, valid_at, temperature],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub struct TrendPoint {
+    pub fetched_at: u64,
+    pub temperature: f64,
+    pub pressure: f64,
+    pub precipitation: f64,
+    pub weather_code: u32,
+}
+
+// Everything recorded within roughly a kilometre of the given coordinates since `since`
+pub fn load_trend(latitude: f64, longitude: f64, since: u64) -> Result<Vec<TrendPoint>, Box<dyn Error>> {
+    let conn = open_records()?;
+    let mut stmt = conn.prepare(
+        "SELECT fetched_at, temperature, pressure, precipitation, weather_code FROM observations
+         WHERE abs(latitude - ?1) < 0.01 AND abs(longitude - ?2) < 0.01 AND fetched_at >= ?3
+         ORDER BY fetched_at",
+    )?;
+    let points = stmt
+        .query_map(params![latitude, longitude, since as i64], |row| {
+            Ok(TrendPoint {
+                fetched_at: row.get::<_, i64>(0)? as u64,
+                temperature: row.get(1)?,
+                pressure: row.get(2)?,
+                precipitation: row.get(3)?,
+                weather_code: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(points)
+}
+
+// An observation next to what was forecast for that hour about a day earlier
+pub struct ForecastCheck {
+    pub observed_at: String,
+    pub observed: f64,
+    pub forecast: f64,
+}
+
+pub fn load_forecast_checks(latitude: f64, longitude: f64, since: u64) -> Result<Vec<ForecastCheck>, Box<dyn Error>> {
+    let conn = open_records()?;
+    // Observations fall inside an hourly slot, so match on the hour and then keep the
+    // forecast whose lead time was closest to 24 hours
+    let mut stmt = conn.prepare(
+        "SELECT o.observed_at, o.temperature, f.temperature FROM observations o
+         JOIN forecasts f
+           ON abs(f.latitude - o.latitude) < 0.01 AND abs(f.longitude - o.longitude) < 0.01
+          AND f.valid_at = substr(o.observed_at, 1, 13) || ':00'
+          AND f.issued_at < o.observed_at
+         WHERE abs(o.latitude - ?1) < 0.01 AND abs(o.longitude - ?2) < 0.01 AND o.fetched_at >= ?3
+         ORDER BY o.observed_at,
+                  abs((julianday(o.observed_at) - julianday(f.issued_at)) * 24 - 24)",
+    )?;
+    let rows = stmt.query_map(params![latitude, longitude, since as i64], |row| {
+        Ok(ForecastCheck {
+            observed_at: row.get(0)?,
+            observed: row.get(1)?,
+            forecast: row.get(2)?,
+        })
+    })?;
+
+    let mut checks: Vec<ForecastCheck> = Vec::new();
+    for check in rows {
+        let check = check?;
+        if checks.last().map(|c| &c.observed_at) != Some(&check.observed_at) {
+            checks.push(check);
+        }
+    }
+    Ok(checks)
+}
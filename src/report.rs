@@ -0,0 +1,199 @@
+// A weather report to paste into notes or a trip plan, for `:export`: the conditions, the week as a
+// table and the next two days as block charts. Markdown, or Org when the file ends in .org
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::api::{weather_code_to_description, DailyForecast, WeatherData};
+use crate::series::TimeSeries;
+use crate::summary;
+
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Markdown,
+    Org,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("org") => Format::Org,
+            _ => Format::Markdown,
+        }
+    }
+
+    fn heading(self, level: usize, text: &str) -> String {
+        match self {
+            Format::Markdown => format!("{} {}", "#".repeat(level), text),
+            Format::Org => format!("{} {}", "*".repeat(level), text),
+        }
+    }
+
+    // Columns after the first two are numbers, right-aligned where the format can say so
+    fn table(self, header: &[&str], rows: &[Vec<String>]) -> Vec<String> {
+        let row = |cells: &[String]| format!("| {} |", cells.join(" | "));
+        let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+        let rule = match self {
+            Format::Markdown => {
+                let rules: Vec<&str> = (0..header.len()).map(|i| if i < 2 { "---" } else { "---:" }).collect();
+                format!("|{}|", rules.join("|"))
+            }
+            Format::Org => format!("|{}|", vec!["---"; header.len()].join("+")),
+        };
+        [row(&header), rule].into_iter().chain(rows.iter().map(|r| row(r))).collect()
+    }
+
+    fn block(self, lines: Vec<String>) -> Vec<String> {
+        let (open, close) = match self {
+            Format::Markdown => ("```", "```"),
+            Format::Org => ("#+begin_example", "#+end_example"),
+        };
+        [open.to_string()].into_iter().chain(lines).chain([close.to_string()]).collect()
+    }
+}
+
+pub fn report(data: &WeatherData, daily: &DailyForecast, format: Format) -> String {
+    let current = &data.weather.current;
+    let units = data.units;
+    let place = if data.location.country.is_empty() {
+        data.location.label()
+    } else {
+        format!("{}, {}", data.location.label(), data.location.country)
+    };
+    let mut lines = vec![
+        format.heading(1, &format!("Weather in {}", place)),
+        String::new(),
+        format!("As of {} local time ({:.4}, {:.4})", current.time.replace('T', " "), data.location.latitude, data.location.longitude),
+        String::new(),
+        format.heading(2, "Now"),
+        String::new(),
+        summary::sentence(&data.weather, data.units),
+        String::new(),
+        format!(
+            "- {}, {:.1}{} (feels like {:.1}{})",
+            weather_code_to_description(current.weather_code),
+            units.temperature(current.temperature_2m),
+            units.temperature_unit(),
+            units.temperature(current.apparent_temperature),
+            units.temperature_unit()
+        ),
+        format!("- Wind {:.0} {}, humidity {}%", units.wind_speed(current.wind_speed_10m), units.wind_speed_unit(), current.relative_humidity_2m),
+        format!("- Pressure {:.0} {}, precipitation {:.1} mm", current.pressure_msl, data.weather.current_units.pressure_msl, current.precipitation),
+        String::new(),
+        format.heading(2, &format!("Next {} days", daily.weather_code.len())),
+        String::new(),
+    ];
+
+    let value_at = |series: &TimeSeries<f64>, date: &str| series.at(date).copied().unwrap_or(f64::NAN);
+    let rows: Vec<Vec<String>> = daily
+        .weather_code
+        .iter()
+        .map(|(date, &code)| {
+            let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_or(date.to_string(), |d| d.format("%a %-d %b").to_string());
+            vec![
+                day,
+                weather_code_to_description(code).to_string(),
+                format!("{:.0}{}", units.temperature(value_at(&daily.temperature_min, date)), units.temperature_unit()),
+                format!("{:.0}{}", units.temperature(value_at(&daily.temperature_max, date)), units.temperature_unit()),
+                format!("{:.1} {}", value_at(&daily.precipitation, date), daily.precipitation.unit),
+                format!("{:.0} {}", units.wind_speed(value_at(&daily.wind_speed_max, date)), units.wind_speed_unit()),
+            ]
+        })
+        .collect();
+    lines.extend(format.table(&["Day", "Sky", "Low", "High", "Rain", "Wind"], &rows));
+    lines.push(String::new());
+
+    lines.extend([format.heading(2, "Charts"), String::new()]);
+    let mut charts = Vec::new();
+    let temperatures = |values: &[f64]| values.iter().map(|&v| units.temperature(v)).collect::<Vec<f64>>();
+    if let Some(hourly) = &data.weather.hourly {
+        let now = hourly.temperature.slot_of(&current.time).unwrap_or(0);
+        let next = |series: &TimeSeries<f64>| series.slice(now..now + 48).values;
+        let winds: Vec<f64> = next(&hourly.wind_speed).into_iter().map(|v| units.wind_speed(v)).collect();
+        charts.extend([
+            "Next 48 hours".to_string(),
+            chart("Temperature", &temperatures(&next(&hourly.temperature)), units.temperature_unit(), false),
+            chart("Rain", &next(&hourly.precipitation), &hourly.precipitation.unit, true),
+            chart("Wind", &winds, units.wind_speed_unit(), false),
+            String::new(),
+        ]);
+    }
+    charts.push(format!("Next {} days", daily.weather_code.len()));
+    charts.push(chart("Highs", &temperatures(&daily.temperature_max.values), units.temperature_unit(), false));
+    charts.push(chart("Rain", &daily.precipitation.values, &daily.precipitation.unit, true));
+    lines.extend(format.block(charts));
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+// A label, the values as block characters and their range. Rain starts from nothing, so a dry hour is blank
+fn chart(label: &str, values: &[f64], unit: &str, from_zero: bool) -> String {
+    let finite = values.iter().copied().filter(|v| v.is_finite());
+    let (lo, hi) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let lo = if from_zero { 0.0 } else { lo };
+    let bars: String = values
+        .iter()
+        .map(|&v| {
+            if !v.is_finite() || (from_zero && v <= 0.0) {
+                ' '
+            } else if hi > lo {
+                BLOCKS[(((v - lo) / (hi - lo)) * 7.0).round() as usize]
+            } else {
+                BLOCKS[3]
+            }
+        })
+        .collect();
+    if lo > hi {
+        return format!("{:<12}no data", label);
+    }
+    format!("{:<12}{}  {:.0}–{:.0} {}", label, bars, lo, hi, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{SearchQuery, Source};
+    use crate::demo::Demo;
+    use crate::units::Units;
+
+    #[test]
+    fn charts_scale_to_their_range_and_rain_to_zero() {
+        assert_eq!(chart("Highs", &[10.0, 14.0, 17.0, f64::NAN], "°C", false), "Highs       ▁▅█   10–17 °C");
+        assert_eq!(chart("Rain", &[0.0, 0.5, 2.0], "mm", true), "Rain         ▃█  0–2 mm");
+        assert_eq!(chart("Wind", &[f64::NAN], "km/h", false), "Wind        no data");
+    }
+
+    #[test]
+    fn tables_in_either_format() {
+        let rows = vec![vec!["Wed 1 May".to_string(), "Fog".to_string(), "8°C".to_string()]];
+        assert_eq!(
+            Format::Markdown.table(&["Day", "Sky", "Low"], &rows),
+            ["| Day | Sky | Low |", "|---|---|---:|", "| Wed 1 May | Fog | 8°C |"]
+        );
+        assert_eq!(Format::Org.table(&["Day", "Sky", "Low"], &rows)[1], "|---+---+---|");
+        assert_eq!(Format::from_path(Path::new("trip/Plan.ORG")), Format::Org);
+        assert_eq!(Format::from_path(Path::new("report.md")), Format::Markdown);
+    }
+
+    #[tokio::test]
+    async fn an_imperial_report_is_imperial_throughout() {
+        let mut data = Demo.fetch_weather(&SearchQuery::parse("Lisbon", None), None).await.unwrap();
+        data.units = Units::IMPERIAL;
+        let days = vec!["2024-05-01".to_string(), "2024-05-02".to_string()];
+        let daily = DailyForecast {
+            weather_code: TimeSeries::new(days.clone(), vec![1, 3], ""),
+            temperature_max: TimeSeries::new(days.clone(), vec![20.0, 25.0], "°C"),
+            temperature_min: TimeSeries::new(days.clone(), vec![10.0, 15.0], "°C"),
+            precipitation: TimeSeries::new(days.clone(), vec![0.0, 1.5], "mm"),
+            wind_speed_max: TimeSeries::new(days, vec![16.09344, 32.18688], "km/h"),
+        };
+        let text = report(&data, &daily, Format::Markdown);
+        assert!(text.contains("| 50°F | 68°F | 0.0 mm | 10 mph |"), "{}", text);
+        assert!(text.contains("68–77 °F"), "{}", text);
+        let hours = text.lines().skip_while(|l| *l != "Next 48 hours").take(4).collect::<Vec<_>>().join("\n");
+        assert!(hours.contains("°F") && hours.contains("mph"), "{}", hours);
+        assert!(!text.contains("°C") && !text.contains("km/h"), "{}", text);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::api::HourlyForecast;
+use crate::forecast::{self, WET_MM};
+
+// Through tonight and the morning commute
+const LOOKAHEAD_HOURS: usize = 18;
+// Rain this recent can still be lying on the road when it freezes
+const WET_HOURS: usize = 6;
+// The surface can be a little above zero and still ice over where it's shaded or exposed
+const SURFACE_FREEZING: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum IceRisk {
+    // Freezing surface with moisture condensing out of the air
+    Possible = 1,
+    // Freezing surface on a road that's still wet
+    Likely = 2,
+}
+
+pub struct IceWarning {
+    pub risk: IceRisk,
+    // First hour at risk, e.g. "2024-01-10T22:00"
+    pub from: String,
+}
+
+impl IceWarning {
+    // e.g. "Black ice likely from 22:00"
+    pub fn message(&self) -> String {
+        let risk = match self.risk {
+            IceRisk::Possible => "possible",
+            IceRisk::Likely => "likely",
+        };
+        format!("Black ice {} from {}", risk, forecast::hour_of(&self.from))
+    }
+}
+
+// The worst black ice risk over the coming hours, starting at the hour `now` falls in
+pub fn black_ice(hourly: &HourlyForecast, now: &str) -> Option<IceWarning> {
+    let start = hourly.temperature.slot_of(now)?;
+    let mut warning: Option<IceWarning> = None;
+    for (i, (time, &air)) in hourly.temperature.iter().enumerate().skip(start).take(LOOKAHEAD_HOURS) {
+        // Without a ground reading, the air temperature has to be a bit lower to mean the same
+        let freezing = match hourly.surface_temperature.at(time) {
+            Some(&surface) => surface <= SURFACE_FREEZING,
+            None => air <= SURFACE_FREEZING - 1.0,
+        };
+        if !freezing {
+            continue;
+        }
+        let wet = hourly.precipitation.slice(i.saturating_sub(WET_HOURS)..i + 1).values.iter().sum::<f64>() >= WET_MM;
+        let condensing = hourly.dew_point.at(time).is_some_and(|&dew| air - dew <= 1.0);
+        let risk = match (wet, condensing) {
+            (true, _) => IceRisk::Likely,
+            (false, true) => IceRisk::Possible,
+            (false, false) => continue,
+        };
+        // Keep the first hour, but let a worse risk later on raise the level
+        match &mut warning {
+            Some(w) if risk > w.risk => w.risk = risk,
+            Some(_) => {}
+            None => warning = Some(IceWarning { risk, from: time.to_string() }),
+        }
+    }
+    warning
+}
+
+// Road ice is only worth a line on the weather view in the cold half of the year
+pub fn winter(latitude: f64, month: u32) -> bool {
+    let northern_winter = matches!(month, 11 | 12 | 1 | 2 | 3);
+    if latitude >= 0.0 {
+        northern_winter
+    } else {
+        matches!(month, 5..=9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::series::TimeSeries;
+
+    // Hourly from 18:00, one value per hour
+    fn night(air: &[f64], surface: &[f64], dew: &[f64], rain: &[f64]) -> HourlyForecast {
+        let time: Vec<String> = (0..air.len()).map(|h| format!("2024-01-10T{:02}:00", 18 + h)).collect();
+        let series = |values: &[f64]| TimeSeries::new(time.clone(), values.to_vec(), "");
+        HourlyForecast {
+            temperature: series(air),
+            precipitation: series(rain),
+            wind_speed: TimeSeries::default(),
+            precipitation_probability: TimeSeries::default(),
+            air_quality: TimeSeries::default(),
+            humidity: TimeSeries::default(),
+            cloud_cover: TimeSeries::default(),
+            sunshine: TimeSeries::default(),
+            is_day: TimeSeries::default(),
+            dew_point: series(dew),
+            surface_temperature: series(surface),
+        }
+    }
+
+    #[test]
+    fn rain_then_a_freeze_is_likely_ice() {
+        let hourly = night(&[3.0, 2.0, 1.0, 0.5], &[2.0, 1.0, 0.0, -1.0], &[-5.0; 4], &[1.2, 0.4, 0.0, 0.0]);
+        let warning = black_ice(&hourly, "2024-01-10T18:20").map(|w| w.message());
+        assert_eq!(warning.as_deref(), Some("Black ice likely from 20:00"));
+    }
+
+    #[test]
+    fn a_clear_dry_frost_with_dry_air_is_no_risk() {
+        let hourly = night(&[1.0, -1.0, -3.0], &[0.0, -2.0, -4.0], &[-10.0; 3], &[0.0; 3]);
+        assert!(black_ice(&hourly, "2024-01-10T18:00").is_none());
+    }
+
+    #[test]
+    fn hoar_frost_near_the_dew_point_is_possible_ice() {
+        let hourly = night(&[2.0, 0.0, -1.0], &[1.0, -0.5, -1.5], &[1.0, -0.5, -1.5], &[0.0; 3]);
+        let warning = black_ice(&hourly, "2024-01-10T18:00").map(|w| w.message());
+        assert_eq!(warning.as_deref(), Some("Black ice possible from 19:00"));
+    }
+
+    #[test]
+    fn winter_is_the_other_way_round_down_south() {
+        assert!(winter(52.5, 1) && !winter(52.5, 7));
+        assert!(winter(-33.9, 7) && !winter(-33.9, 1));
+    }
+}
@@ -0,0 +1,77 @@
+// Where along a track you'll be and when, for `weather-searcher route`: the forecast that matters
+// on a long ride is the one for the hour you get there, not the hour you set off
+use chrono::{Duration, NaiveDateTime};
+
+use crate::geo;
+use crate::waypoints::TrackPoint;
+
+pub struct Stop {
+    pub km: f64,
+    pub latitude: f64,
+    pub longitude: f64,
+    // From the track when it has heights; Open-Meteo looks it up otherwise
+    pub elevation: Option<f64>,
+    pub arrival: NaiveDateTime,
+}
+
+// What the profile chart plots for each stop; NaN where the forecast doesn't reach
+pub struct ProfilePoint {
+    pub km: f64,
+    pub elevation: f64,
+    pub temperature: f64,
+    pub wind: f64,
+}
+
+// A stop every `every_km` from the start, plus the finish, at a steady `speed_kmh`
+pub fn stops(track: &[TrackPoint], speed_kmh: f64, start: NaiveDateTime, every_km: f64) -> Vec<Stop> {
+    let stop = |km: f64, a: &TrackPoint, b: &TrackPoint, t: f64| Stop {
+        km,
+        latitude: a.latitude + (b.latitude - a.latitude) * t,
+        longitude: a.longitude + (b.longitude - a.longitude) * t,
+        elevation: a.elevation.zip(b.elevation).map(|(a, b)| a + (b - a) * t),
+        arrival: start + Duration::seconds((km / speed_kmh * 3600.0).round() as i64),
+    };
+    let (Some(first), Some(last)) = (track.first(), track.last()) else {
+        return Vec::new();
+    };
+    let mut stops = vec![stop(0.0, first, first, 0.0)];
+    let mut travelled = 0.0;
+    let mut next = every_km;
+    for pair in track.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let leg = geo::distance_km(a.latitude, a.longitude, b.latitude, b.longitude);
+        // Straight lines between track points are close enough at this spacing
+        while next <= travelled + leg {
+            stops.push(stop(next, a, b, (next - travelled) / leg));
+            next += every_km;
+        }
+        travelled += leg;
+    }
+    // The finish, unless a stop already landed on it
+    if stops.last().is_some_and(|s| travelled - s.km > 0.05) {
+        stops.push(stop(travelled, last, last, 0.0));
+    }
+    stops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_stop_each_hour_and_one_at_the_finish() {
+        // Due north along a meridian: a degree of latitude is about 111.2 km
+        let point = |latitude, elevation| TrackPoint { latitude, longitude: 6.0, elevation };
+        let track = [point(45.0, Some(400.0)), point(45.5, Some(1400.0)), point(46.0, None)];
+        let start = NaiveDateTime::parse_from_str("2024-05-01T07:00", "%Y-%m-%dT%H:%M").unwrap();
+        let stops = stops(&track, 40.0, start, 40.0);
+        let km: Vec<f64> = stops.iter().map(|s| s.km.round()).collect();
+        assert_eq!(km, [0.0, 40.0, 80.0, 111.0]);
+        assert_eq!(stops[1].arrival.format("%H:%M").to_string(), "08:00");
+        assert!((stops[1].latitude - 45.36).abs() < 0.01);
+        // 40 of the first 55.6 km climb 1000 m
+        assert_eq!(stops[1].elevation.map(f64::round), Some(1119.0));
+        assert_eq!(stops[2].elevation, None);
+        assert_eq!(stops[3].arrival.format("%H:%M").to_string(), "09:46");
+    }
+}
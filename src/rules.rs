@@ -0,0 +1,313 @@
+use chrono::{Duration, NaiveDateTime};
+use serde_json::{json, Value};
+
+use crate::api::{HourlyForecast, WeatherData};
+use crate::config::{Gear, Metric, Rule};
+use crate::roads;
+use crate::series::TimeSeries;
+
+// A rule whose condition holds for a reading
+pub struct Firing {
+    pub value: f64,
+    pub message: String,
+}
+
+impl Metric {
+    fn read(self, data: &WeatherData) -> f64 {
+        let current = &data.weather.current;
+        match self {
+            Metric::Temperature => current.temperature_2m,
+            Metric::ApparentTemperature => current.apparent_temperature,
+            Metric::WindSpeed => current.wind_speed_10m,
+            Metric::Precipitation => current.precipitation,
+            Metric::Humidity => current.relative_humidity_2m as f64,
+            Metric::Pressure => current.pressure_msl,
+            Metric::UvIndex => current.uv_index,
+            Metric::RainAhead => data.weather.hourly.as_ref().map_or(current.precipitation, |hourly| {
+                let now = hourly.precipitation.slot_of(&current.time).unwrap_or(0);
+                hourly.precipitation.slice(now..now + 12).values.iter().sum()
+            }),
+            Metric::BlackIce => {
+                let warning = data.weather.hourly.as_ref().and_then(|hourly| roads::black_ice(hourly, &current.time));
+                warning.map_or(0.0, |w| w.risk as u8 as f64)
+            }
+        }
+    }
+
+    // The forecast for metrics that have one hour by hour
+    fn hourly(self, hourly: &HourlyForecast) -> Option<&TimeSeries<f64>> {
+        match self {
+            Metric::Temperature => Some(&hourly.temperature),
+            Metric::WindSpeed => Some(&hourly.wind_speed),
+            Metric::Precipitation => Some(&hourly.precipitation),
+            Metric::Humidity => Some(&hourly.humidity),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Metric::Temperature => "temperature",
+            Metric::ApparentTemperature => "feels-like temperature",
+            Metric::WindSpeed => "wind speed",
+            Metric::Precipitation => "precipitation",
+            Metric::Humidity => "humidity",
+            Metric::Pressure => "pressure",
+            Metric::UvIndex => "UV index",
+            Metric::RainAhead => "rain over the next 12 hours",
+            Metric::BlackIce => "black ice risk",
+        }
+    }
+}
+
+pub fn check(rule: &Rule, data: &WeatherData) -> Option<Firing> {
+    let value = rule.metric.read(data);
+    let breached = rule.below.is_some_and(|limit| value < limit) || rule.above.is_some_and(|limit| value > limit);
+    if !breached {
+        return None;
+    }
+
+    let limit = match (rule.below, rule.above) {
+        (Some(below), _) if value < below => format!("below {}", below),
+        (_, Some(above)) => format!("above {}", above),
+        _ => String::new(),
+    };
+    let message = format!(
+        "{} in {}: {} {:.1} ({})",
+        rule.name,
+        data.location.name,
+        rule.metric.label(),
+        value,
+        limit
+    );
+    Some(Firing { value, message })
+}
+
+// A condition from the command line, like "rain_ahead>0", for `get --fail-if`
+#[derive(Debug, Clone, Copy)]
+pub struct Predicate {
+    metric: Metric,
+    op: &'static str,
+    value: f64,
+}
+
+impl std::str::FromStr for Predicate {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Predicate, String> {
+        // Two-character operators first, so ">=" isn't read as ">" and "=0"
+        let (at, op) = [">=", "<=", "!=", ">", "<", "="]
+            .iter()
+            .find_map(|op| text.find(op).map(|at| (at, *op)))
+            .ok_or_else(|| format!("'{}' needs a comparison, like precipitation>0", text))?;
+        let name = text[..at].trim();
+        let metric: Metric = serde_json::from_value(json!(name)).map_err(|_| {
+            format!(
+                "Unknown metric '{}'; try temperature, apparent_temperature, wind_speed, precipitation, humidity, pressure, uv_index, rain_ahead or black_ice",
+                name
+            )
+        })?;
+        let value = text[at + op.len()..].trim();
+        let value = value.parse().map_err(|_| format!("'{}' in '{}' isn't a number", value, text))?;
+        Ok(Predicate { metric, op, value })
+    }
+}
+
+impl Predicate {
+    // The reading that made it true, e.g. "rain over the next 12 hours 3.2 > 0"
+    pub fn check(&self, data: &WeatherData) -> Option<String> {
+        let reading = self.metric.read(data);
+        let holds = match self.op {
+            ">=" => reading >= self.value,
+            "<=" => reading <= self.value,
+            "!=" => reading != self.value,
+            ">" => reading > self.value,
+            "<" => reading < self.value,
+            _ => reading == self.value,
+        };
+        holds.then(|| format!("{} {:.1} {} {}", self.metric.label(), reading, self.op, self.value))
+    }
+}
+
+// A stretch of forecast hours in which a rule would fire
+pub struct Window {
+    pub start: NaiveDateTime,
+    // The end of the last hour, so a single hour runs 14:00 to 15:00
+    pub end: NaiveDateTime,
+    pub message: String,
+}
+
+impl Window {
+    fn new(rule: &Rule, hours: &[(&str, f64)]) -> Option<Window> {
+        let parse = |time: &str| NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").ok();
+        let start = parse(hours.first()?.0)?;
+        let end = parse(hours.last()?.0)? + Duration::hours(1);
+        let values = hours.iter().map(|&(_, v)| v);
+        let (word, peak) = if rule.below.is_some_and(|limit| hours[0].1 < limit) {
+            ("down to", values.fold(f64::INFINITY, f64::min))
+        } else {
+            ("up to", values.fold(f64::NEG_INFINITY, f64::max))
+        };
+        let message = format!("{}: {} {} {:.1}", rule.name, rule.metric.label(), word, peak);
+        Some(Window { start, end, message })
+    }
+}
+
+// Every run of forecast hours that breaches the rule. Metrics only known for right now
+// (UV, pressure, ...) have no windows
+pub fn windows(rule: &Rule, hourly: &HourlyForecast) -> Vec<Window> {
+    let Some(series) = rule.metric.hourly(hourly) else {
+        return Vec::new();
+    };
+    let breached = |value: f64| rule.below.is_some_and(|limit| value < limit) || rule.above.is_some_and(|limit| value > limit);
+    let mut windows = Vec::new();
+    let mut run: Vec<(&str, f64)> = Vec::new();
+    for (time, &value) in series.iter() {
+        if breached(value) {
+            run.push((time, value));
+        } else if !run.is_empty() {
+            windows.extend(Window::new(rule, &run));
+            run.clear();
+        }
+    }
+    windows.extend(Window::new(rule, &run));
+    windows
+}
+
+// Everything from the gear table that suits the weather right now, e.g. "jacket + umbrella"
+pub fn gear(table: &[Gear], data: &WeatherData) -> Option<String> {
+    let mut items: Vec<&str> = Vec::new();
+    for gear in table {
+        let value = gear.metric.read(data);
+        let suits = gear.above.is_none_or(|limit| value > limit) && gear.below.is_none_or(|limit| value < limit);
+        if suits && !items.contains(&gear.item.as_str()) {
+            items.push(&gear.item);
+        }
+    }
+    (!items.is_empty()).then(|| items.join(" + "))
+}
+
+// What gets POSTed to each webhook when a rule fires
+pub fn payload(rule: &Rule, firing: &Firing, data: &WeatherData) -> Value {
+    json!({
+        "rule": rule.name,
+        "metric": rule.metric,
+        "value": firing.value,
+        "below": rule.below,
+        "above": rule.above,
+        "message": firing.message,
+        "location": data.location,
+        "observed_at": data.weather.current.time,
+        "timestamp": data.fetched_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FetchTimings, GeoLocation};
+    use crate::config::Config;
+    use crate::units::Units;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn reading(apparent: f64, uv_index: f64, rain: &[f64]) -> WeatherData {
+        let time: Vec<String> = (0..rain.len()).map(|h| format!("2024-05-01T{:02}:00", h)).collect();
+        let weather = serde_json::from_value(json!({
+            "current": {
+                "time": "2024-05-01T00:00",
+                "temperature_2m": apparent,
+                "relative_humidity_2m": 60,
+                "apparent_temperature": apparent,
+                "precipitation": 0.0,
+                "weather_code": 1,
+                "wind_speed_10m": 10.0,
+                "pressure_msl": 1015.0,
+                "uv_index": uv_index,
+            },
+            "current_units": { "temperature_2m": "°C", "wind_speed_10m": "km/h", "pressure_msl": "hPa" },
+            "hourly": { "time": time, "temperature_2m": vec![apparent; rain.len()], "precipitation": rain },
+        }))
+        .expect("test reading should parse");
+        let location = GeoLocation {
+            name: "Testville".to_string(),
+            latitude: 0.0,
+            longitude: 0.0,
+            country: "Nowhere".to_string(),
+            admin1: None,
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        };
+        let timings = FetchTimings { geocode: None, forecast: Duration::ZERO, json_fallback: None };
+        WeatherData { location, weather, timings, fetched_at: 0, units: Units::default() }
+    }
+
+    #[test]
+    fn built_in_gear_for_a_wet_cool_day() {
+        let mut rain = vec![0.0; 24];
+        rain[6] = 2.5;
+        let gear = gear(Config::default().gear(), &reading(9.0, 1.0, &rain));
+        assert_eq!(gear.as_deref(), Some("jacket + umbrella"));
+    }
+
+    #[test]
+    fn rain_after_twelve_hours_needs_no_umbrella_yet() {
+        let mut rain = vec![0.0; 24];
+        rain[20] = 2.5;
+        let gear = gear(Config::default().gear(), &reading(24.0, 7.0, &rain));
+        assert_eq!(gear.as_deref(), Some("sunscreen"));
+    }
+
+    #[test]
+    fn fail_if_predicates() {
+        let mut rain = vec![0.0; 24];
+        rain[3] = 1.5;
+        let data = reading(9.0, 2.0, &rain);
+        let check = |text: &str| text.parse::<Predicate>().map(|p| p.check(&data));
+        assert_eq!(check("rain_ahead>0"), Ok(Some("rain over the next 12 hours 1.5 > 0".to_string())));
+        assert_eq!(check(" uv_index >= 2.5 "), Ok(None));
+        assert_eq!(check("temperature<=9"), Ok(Some("temperature 9.0 <= 9".to_string())));
+        assert!(check("rain>0").unwrap_err().starts_with("Unknown metric 'rain'"));
+        assert!(check("temperature").is_err());
+        assert!(check("temperature>warm").is_err());
+    }
+
+    #[test]
+    fn forecast_hours_past_a_limit_make_windows() {
+        let mut rain = vec![0.0; 24];
+        rain[6..9].copy_from_slice(&[1.0, 4.5, 2.0]);
+        rain[23] = 3.0;
+        let data = reading(9.0, 1.0, &rain);
+        let hourly = data.weather.hourly.as_ref().unwrap();
+        let rule = Rule { name: "Downpour".to_string(), metric: Metric::Precipitation, below: None, above: Some(0.5) };
+        let windows: Vec<(String, String, String)> = windows(&rule, hourly)
+            .into_iter()
+            .map(|w| (w.start.format("%d %H:%M").to_string(), w.end.format("%d %H:%M").to_string(), w.message))
+            .collect();
+        assert_eq!(
+            windows,
+            [
+                ("01 06:00".to_string(), "01 09:00".to_string(), "Downpour: precipitation up to 4.5".to_string()),
+                ("01 23:00".to_string(), "02 00:00".to_string(), "Downpour: precipitation up to 3.0".to_string()),
+            ]
+        );
+        let uv = Rule { metric: Metric::UvIndex, ..rule };
+        assert!(super::windows(&uv, hourly).is_empty());
+    }
+
+    #[test]
+    fn a_config_table_replaces_the_built_in_one() {
+        let config: Config = toml::from_str(
+            r#"
+            [[gear]]
+            item = "shorts"
+            metric = "apparent_temperature"
+            above = 20
+            "#,
+        )
+        .expect("config should parse");
+        assert_eq!(gear(config.gear(), &reading(24.0, 7.0, &[0.0])).as_deref(), Some("shorts"));
+        assert_eq!(gear(config.gear(), &reading(2.0, 0.0, &[0.0])), None);
+    }
+}
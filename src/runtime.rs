@@ -0,0 +1,412 @@
+use crossterm::event::Event;
+use futures::{Stream, StreamExt};
+use ratatui::{
+    backend::{Backend, CrosstermBackend, TestBackend},
+    layout::{Rect, Size},
+    Terminal,
+};
+use std::{
+    fs,
+    future::Future,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::api::{Source, WeatherData};
+use crate::app::{self, App, Command, Msg};
+use crate::bugreport;
+use crate::events;
+use crate::history;
+use crate::plugins::{self, PluginResult};
+use crate::records;
+use crate::snapshot;
+use crate::view;
+
+// What the loop needs from a terminal beyond drawing on it
+pub trait Console: Backend {
+    // The bell goes to the same place as the frames, so it never lands in a pipe or a test's output
+    fn bell(&mut self) -> io::Result<()>;
+}
+
+impl<W: Write> Console for CrosstermBackend<W> {
+    fn bell(&mut self) -> io::Result<()> {
+        self.write_all(b"\x07")?;
+        Write::flush(self)
+    }
+}
+
+impl Console for TestBackend {
+    fn bell(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Drives the app until it quits or `input` runs dry, and hands it back for a final look.
+// `suspend` is called for Ctrl-Z; everything that talks to the network goes through `source`.
+pub async fn run_app<B, E, S>(
+    terminal: &mut Terminal<B>,
+    mut app: App,
+    mut input: E,
+    source: Arc<S>,
+    mut suspend: impl FnMut(&mut Terminal<B>) -> io::Result<()>,
+) -> io::Result<App>
+where
+    B: Console,
+    E: Stream<Item = io::Result<Event>> + Unpin,
+    S: Source,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // Ctrl-Z arrives as a key in raw mode, but `kill -TSTP` from elsewhere should behave the same
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sigtstp = signal(SignalKind::from_raw(libc::SIGTSTP))?;
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            while sigtstp.recv().await.is_some() {
+                let _ = tx.send(Msg::Suspend);
+            }
+        });
+    }
+    let mut ticker = tokio::time::interval(app::TICK_RATE);
+    let pool = Arc::new(Semaphore::new(app.config.max_concurrent_fetches.unwrap_or(4).max(1)));
+
+    for command in app.start() {
+        run_command(command, &tx, &pool, &source);
+    }
+
+    loop {
+        if app.take_dirty() {
+            let started = Instant::now();
+            terminal.draw(|f| view::ui(f, &app))?;
+            app.frame_drawn(started.elapsed());
+        }
+
+        // Input, task results and the ticker are independent sources; whichever is ready first wins
+        let msg = tokio::select! {
+            event = input.next() => match event.transpose()? {
+                Some(Event::Key(key)) => Msg::Key(key),
+                Some(Event::Mouse(mouse)) => Msg::Mouse(mouse),
+                Some(Event::Resize(width, height)) => {
+                    // Throw away the old buffers so nothing from the previous size lingers
+                    terminal.resize(Rect::new(0, 0, width, height))?;
+                    Msg::Resize(width, height)
+                }
+                Some(_) => continue,
+                None => return Ok(app),
+            },
+            Some(msg) = rx.recv() => msg,
+            _ = ticker.tick() => Msg::Tick,
+        };
+
+        for command in app.update(msg) {
+            match command {
+                Command::Quit => return Ok(app),
+                Command::Suspend => {
+                    suspend(terminal)?;
+                    terminal.draw(|f| view::ui(f, &app))?;
+                }
+                Command::Bell => {
+                    let _ = terminal.backend_mut().bell();
+                }
+                Command::WriteSnapshot { path, title } => {
+                    let _ = tx.send(Msg::ReportWritten(write_snapshot(&app, terminal.size()?, &path, &title)));
+                }
+                command => run_command(command, &tx, &pool, &source),
+            }
+        }
+    }
+}
+
+// Draws the app once more off-screen, at the terminal's size, and saves that frame as a page
+fn write_snapshot(app: &App, size: Size, path: &Path, title: &str) -> Result<PathBuf, String> {
+    let mut offscreen = Terminal::new(TestBackend::new(size.width, size.height)).map_err(|e| e.to_string())?;
+    offscreen.draw(|f| view::ui(f, app)).map_err(|e| e.to_string())?;
+    let generated = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+    let html = snapshot::html(offscreen.backend().buffer(), title, &generated)?;
+    fs::write(path, html).map_err(|e| format!("Couldn't write {}: {}", path.display(), e))?;
+    Ok(path.to_path_buf())
+}
+
+// Long enough for a notification or a curl, short enough that a stuck hook doesn't pile up
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Output is discarded so hooks can't scribble over the TUI
+async fn run_hook(command: &str, env: Vec<(String, String)>, stdin: String, limit: Duration) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut child = plugins::shell(command)
+        .envs(env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Hook `{}` didn't start: {}", command, e))?;
+    let pipe = child.stdin.take();
+    let finished = tokio::time::timeout(limit, async {
+        if let Some(mut pipe) = pipe {
+            // A hook that ignores stdin closes the pipe early, which is fine
+            let _ = pipe.write_all(stdin.as_bytes()).await;
+        }
+        child.wait().await
+    })
+    .await;
+    let status = match finished {
+        Ok(status) => status.map_err(|e| e.to_string())?,
+        Err(_) => {
+            let _ = child.kill().await;
+            return Err(format!("Hook `{}` took longer than {}s and was stopped", command, limit.as_secs()));
+        }
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Hook `{}` exited with {}", command, status))
+    }
+}
+
+// Network tasks wait here for a pool permit, so a big dashboard can't fire dozens of requests at once
+fn spawn_fetch(pool: &Arc<Semaphore>, task: impl Future<Output = ()> + Send + 'static) {
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        if let Ok(_permit) = pool.acquire_owned().await {
+            task.await;
+        }
+    });
+}
+
+// Carries out side effects that don't need the terminal
+fn run_command<S: Source>(command: Command, tx: &mpsc::UnboundedSender<Msg>, pool: &Arc<Semaphore>, source: &Arc<S>) {
+    match command {
+        Command::FetchWeather { key, city, query, cached, units } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_weather(&query, cached).await.map(|data| Box::new(WeatherData { units, ..data }));
+                let _ = tx.send(Msg::WeatherResults(key, city, result));
+            });
+        }
+        Command::FetchAutocomplete { input, query } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                if let Ok(suggestions) = source.fetch_autocomplete(&query).await {
+                    let _ = tx.send(Msg::AutocompleteResults(input, suggestions));
+                }
+            });
+        }
+        Command::SaveHistory(history) => {
+            let _ = history::save_history(&history);
+        }
+        Command::SaveHome(home) => {
+            let _ = history::save_home(&home);
+        }
+        Command::RecordObservation(observation) => {
+            tokio::task::spawn_blocking(move || {
+                let _ = records::record_observation(&observation);
+            });
+        }
+        Command::LoadTrends { latitude, longitude } => {
+            let tx = tx.clone();
+            tokio::task::spawn_blocking(move || {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let since = now.saturating_sub(app::TREND_HOURS * 3600);
+                let result = records::load_trend(latitude, longitude, since)
+                    .map(|points| (now, points))
+                    .map_err(|e| e.to_string());
+                let _ = tx.send(Msg::TrendsLoaded(result));
+            });
+        }
+        Command::FetchEarthquakes { latitude, longitude, radius_km } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_earthquakes(latitude, longitude, radius_km).await;
+                let _ = tx.send(Msg::EarthquakesLoaded { latitude, longitude, result });
+            });
+        }
+        Command::FetchBrief { icao } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_brief(&icao).await.map(Box::new);
+                let _ = tx.send(Msg::BriefLoaded(result));
+            });
+        }
+        Command::FetchCyclones => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_cyclones().await;
+                let _ = tx.send(Msg::CyclonesLoaded(result));
+            });
+        }
+        Command::FetchMountain { latitude, longitude, elevations } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_mountain(latitude, longitude, &elevations).await.map(Box::new);
+                let _ = tx.send(Msg::MountainLoaded(result));
+            });
+        }
+        Command::FetchFreeFlight { latitude, longitude } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_free_flight(latitude, longitude).await.map(Box::new);
+                let _ = tx.send(Msg::FreeFlightLoaded(result));
+            });
+        }
+        Command::FetchTides { latitude, longitude, worldtides_key } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_tides(latitude, longitude, worldtides_key.as_deref()).await.map(Box::new);
+                let _ = tx.send(Msg::TidesLoaded(result));
+            });
+        }
+        Command::FetchSeaTemperature { latitude, longitude } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_sea_temperature(latitude, longitude).await;
+                let _ = tx.send(Msg::SeaTemperatureLoaded(result));
+            });
+        }
+        Command::FetchPressure { latitude, longitude } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_pressure(latitude, longitude).await;
+                let _ = tx.send(Msg::PressureLoaded(result));
+            });
+        }
+        Command::CheckForUpdate => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let _ = tx.send(Msg::ReleaseChecked(source.fetch_latest_release().await));
+            });
+        }
+        Command::WriteBugReport { context } => {
+            let tx = tx.clone();
+            tokio::task::spawn_blocking(move || {
+                let _ = tx.send(Msg::BugReportWritten(bugreport::write(None, &context)));
+            });
+        }
+        Command::FetchReport { path, location } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_daily(&location, 7).await.map(Box::new);
+                let (latitude, longitude) = (location.latitude, location.longitude);
+                let _ = tx.send(Msg::ReportLoaded { path, latitude, longitude, result });
+            });
+        }
+        Command::WriteReport { path, text } => {
+            let result = std::fs::write(&path, text).map(|_| path.clone()).map_err(|e| format!("Couldn't write {}: {}", path.display(), e));
+            let _ = tx.send(Msg::ReportWritten(result));
+        }
+        Command::FetchNearby { latitude, longitude, offset_km } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let result = source.fetch_nearby(latitude, longitude, offset_km).await;
+                let _ = tx.send(Msg::NearbyLoaded(result));
+            });
+        }
+        Command::LoadForecastChecks { latitude, longitude } => {
+            let tx = tx.clone();
+            tokio::task::spawn_blocking(move || {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let since = now.saturating_sub(app::ACCURACY_DAYS * 86400);
+                let result = records::load_forecast_checks(latitude, longitude, since).map_err(|e| e.to_string());
+                let _ = tx.send(Msg::ForecastChecksLoaded(result));
+            });
+        }
+        Command::PostWebhook { url, payload } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                if let Err(e) = source.post_webhook(&url, &payload).await {
+                    let _ = tx.send(Msg::DeliveryFailed(e));
+                }
+            });
+        }
+        Command::PublishNtfy { ntfy, title, message, priority } => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                if let Err(e) = source.publish_ntfy(&ntfy, &title, &message, priority).await {
+                    let _ = tx.send(Msg::DeliveryFailed(e));
+                }
+            });
+        }
+        Command::RunHook { command, env, stdin } => {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                if let Err(e) = run_hook(&command, env, stdin, HOOK_TIMEOUT).await {
+                    let _ = tx.send(Msg::HookFailed(e));
+                }
+            });
+        }
+        Command::Launch(command) => {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                if let Err(e) = plugins::launch(&command).await {
+                    let _ = tx.send(Msg::LaunchFailed(e));
+                }
+            });
+        }
+        Command::RunPlugin { plugin, latitude, longitude, input } => {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let panel = plugins::run(&plugin, &input).await;
+                let result = PluginResult { name: plugin.name, panel };
+                let _ = tx.send(Msg::PluginLoaded { latitude, longitude, result });
+            });
+        }
+        Command::ResolveHome(query) => {
+            let (tx, source) = (tx.clone(), source.clone());
+            spawn_fetch(pool, async move {
+                let _ = tx.send(Msg::HomeResolved(source.geocode(&query).await));
+            });
+        }
+        Command::LogEvent(event) => {
+            tokio::task::spawn_blocking(move || {
+                let _ = events::append_event(&event);
+            });
+        }
+        Command::Quit | Command::Suspend | Command::Bell | Command::WriteSnapshot { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn a_hook_gets_the_json_on_stdin_and_its_exit_status_counts() {
+        let env = vec![("WEATHER_CITY".to_string(), "Berlin".to_string())];
+        let check = r#"[ "$WEATHER_CITY" = Berlin ] && grep -q '"temperature"'"#;
+        assert_eq!(run_hook(check, env, r#"{"temperature":17.5}"#.to_string(), HOOK_TIMEOUT).await, Ok(()));
+        let e = run_hook("exit 3", Vec::new(), String::new(), HOOK_TIMEOUT).await.unwrap_err();
+        assert!(e.contains("exit status: 3"), "{}", e);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn a_stuck_hook_is_killed() {
+        let started = Instant::now();
+        let e = run_hook("sleep 30", Vec::new(), String::new(), Duration::from_millis(200)).await.unwrap_err();
+        assert!(e.contains("was stopped"), "{}", e);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn the_bell_rings_on_the_terminal_not_stdout() {
+        let mut out = Vec::new();
+        CrosstermBackend::new(&mut out).bell().unwrap();
+        assert_eq!(out, b"\x07");
+        assert!(TestBackend::new(10, 2).bell().is_ok());
+    }
+}
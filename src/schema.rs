@@ -0,0 +1,343 @@
+// What `get --json`, `forecast --json`, hooks and plugins print, pinned down so scripts can rely on it.
+// These structs are the contract and are kept apart from the API ones, which change whenever
+// Open-Meteo does. Adding a field is fine within a version; renaming, removing or retyping one
+// means bumping SCHEMA_VERSION
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::api::{weather_code_to_description, DailyForecast, GeoLocation, WeatherData};
+use crate::series::TimeSeries;
+use crate::summary;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Place {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub country: String,
+    pub admin1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin2: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin3: Option<String>,
+    // Kilometres to `name` when the lookup was by coordinates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub near_km: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Current {
+    pub schema_version: u32,
+    // What was asked for, in `get --batch` output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    pub location: Place,
+    // The place's local time, YYYY-MM-DDTHH:MM
+    pub observed_at: String,
+    pub description: String,
+    pub summary: String,
+    pub weather_code: u32,
+    pub temperature: f64,
+    pub apparent_temperature: f64,
+    pub humidity: u32,
+    pub precipitation: f64,
+    pub wind_speed: f64,
+    pub pressure: f64,
+    pub uv_index: f64,
+    pub units: CurrentUnits,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CurrentUnits {
+    pub temperature: String,
+    pub wind_speed: String,
+    pub pressure: String,
+    pub precipitation: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Forecast {
+    pub schema_version: u32,
+    pub location: Place,
+    pub units: ForecastUnits,
+    pub days: Vec<Day>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ForecastUnits {
+    pub temperature: String,
+    pub precipitation: String,
+    pub wind_speed: String,
+}
+
+// Readings are null for days the model doesn't reach
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Day {
+    // YYYY-MM-DD
+    pub date: String,
+    pub description: String,
+    pub weather_code: u32,
+    pub temperature_min: Option<f64>,
+    pub temperature_max: Option<f64>,
+    pub precipitation: Option<f64>,
+    pub wind_speed_max: Option<f64>,
+}
+
+// A `get --batch --json` line for a city that couldn't be looked up
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchError {
+    pub schema_version: u32,
+    pub query: String,
+    pub error: String,
+}
+
+impl From<&GeoLocation> for Place {
+    fn from(location: &GeoLocation) -> Place {
+        Place {
+            name: location.name.clone(),
+            latitude: location.latitude,
+            longitude: location.longitude,
+            country: location.country.clone(),
+            admin1: location.admin1.clone(),
+            admin2: location.admin2.clone(),
+            admin3: location.admin3.clone(),
+            near_km: location.near_km,
+        }
+    }
+}
+
+impl From<&WeatherData> for Current {
+    fn from(data: &WeatherData) -> Current {
+        let current = &data.weather.current;
+        let units = data.units;
+        Current {
+            schema_version: SCHEMA_VERSION,
+            query: None,
+            location: Place::from(&data.location),
+            observed_at: current.time.clone(),
+            description: weather_code_to_description(current.weather_code).to_string(),
+            summary: summary::sentence(&data.weather, data.units),
+            weather_code: current.weather_code,
+            temperature: units.temperature(current.temperature_2m),
+            apparent_temperature: units.temperature(current.apparent_temperature),
+            humidity: current.relative_humidity_2m,
+            precipitation: current.precipitation,
+            wind_speed: units.wind_speed(current.wind_speed_10m),
+            pressure: current.pressure_msl,
+            uv_index: current.uv_index,
+            units: CurrentUnits {
+                temperature: units.temperature_unit().to_string(),
+                wind_speed: units.wind_speed_unit().to_string(),
+                pressure: data.weather.current_units.pressure_msl.clone(),
+                precipitation: "mm".to_string(),
+            },
+        }
+    }
+}
+
+impl Forecast {
+    pub fn new(location: &GeoLocation, daily: &DailyForecast) -> Forecast {
+        let value_at = |series: &TimeSeries<f64>, date: &str| series.at(date).copied().filter(|v| v.is_finite());
+        let days = daily
+            .weather_code
+            .iter()
+            .map(|(date, &code)| Day {
+                date: date.to_string(),
+                description: weather_code_to_description(code).to_string(),
+                weather_code: code,
+                temperature_min: value_at(&daily.temperature_min, date),
+                temperature_max: value_at(&daily.temperature_max, date),
+                precipitation: value_at(&daily.precipitation, date),
+                wind_speed_max: value_at(&daily.wind_speed_max, date),
+            })
+            .collect();
+        Forecast {
+            schema_version: SCHEMA_VERSION,
+            location: Place::from(location),
+            units: ForecastUnits {
+                temperature: daily.temperature_max.unit.clone(),
+                precipitation: daily.precipitation.unit.clone(),
+                wind_speed: daily.wind_speed_max.unit.clone(),
+            },
+            days,
+        }
+    }
+}
+
+impl BatchError {
+    pub fn new(query: &str, error: &str) -> BatchError {
+        BatchError { schema_version: SCHEMA_VERSION, query: query.to_string(), error: error.to_string() }
+    }
+}
+
+// The same thing as a JSON Schema, for `weather-searcher schema`; the test below keeps it honest
+pub fn json_schema() -> Value {
+    let number = json!({ "type": "number" });
+    let maybe_number = json!({ "type": ["number", "null"] });
+    let string = json!({ "type": "string" });
+    let version = json!({ "const": SCHEMA_VERSION });
+    let object = |properties: Value, optional: &[&str]| {
+        let required: Vec<&String> = properties.as_object().map_or(Vec::new(), |p| p.keys().filter(|k| !optional.contains(&k.as_str())).collect());
+        json!({ "type": "object", "properties": properties, "required": required })
+    };
+    let place = object(
+        json!({
+            "name": string,
+            "latitude": number,
+            "longitude": number,
+            "country": string,
+            "admin1": { "type": ["string", "null"] },
+            "admin2": string,
+            "admin3": string,
+            "near_km": number,
+        }),
+        &["admin2", "admin3", "near_km"],
+    );
+    let current = object(
+        json!({
+            "schema_version": version,
+            "query": string,
+            "location": { "$ref": "#/$defs/place" },
+            "observed_at": string,
+            "description": string,
+            "summary": string,
+            "weather_code": { "type": "integer" },
+            "temperature": number,
+            "apparent_temperature": number,
+            "humidity": { "type": "integer" },
+            "precipitation": number,
+            "wind_speed": number,
+            "pressure": number,
+            "uv_index": number,
+            "units": object(json!({ "temperature": string, "wind_speed": string, "pressure": string, "precipitation": string }), &[]),
+        }),
+        &["query"],
+    );
+    let day = object(
+        json!({
+            "date": string,
+            "description": string,
+            "weather_code": { "type": "integer" },
+            "temperature_min": maybe_number,
+            "temperature_max": maybe_number,
+            "precipitation": maybe_number,
+            "wind_speed_max": maybe_number,
+        }),
+        &[],
+    );
+    let forecast = object(
+        json!({
+            "schema_version": version,
+            "location": { "$ref": "#/$defs/place" },
+            "units": object(json!({ "temperature": string, "precipitation": string, "wind_speed": string }), &[]),
+            "days": { "type": "array", "items": day },
+        }),
+        &[],
+    );
+    let batch_error = object(json!({ "schema_version": version, "query": string, "error": string }), &[]);
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "weather-searcher JSON output",
+        "$defs": {
+            "place": place,
+            "current": current,
+            "forecast": forecast,
+            "batch_error": batch_error,
+        },
+        "anyOf": [
+            { "$ref": "#/$defs/current" },
+            { "$ref": "#/$defs/forecast" },
+            { "$ref": "#/$defs/batch_error" },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place() -> Place {
+        Place {
+            name: "Bern".to_string(),
+            latitude: 46.948,
+            longitude: 7.447,
+            country: "Switzerland".to_string(),
+            admin1: None,
+            admin2: None,
+            admin3: None,
+            near_km: None,
+        }
+    }
+
+    // Every field the structs write is in the schema, and every required one is always written
+    fn assert_matches(value: &Value, schema: &Value) {
+        let (Some(fields), Some(properties)) = (value.as_object(), schema["properties"].as_object()) else {
+            return;
+        };
+        for (name, field) in fields {
+            assert!(properties.contains_key(name), "{} isn't in the schema", name);
+            assert_matches(field, &properties[name]);
+        }
+        for name in schema["required"].as_array().unwrap() {
+            assert!(fields.contains_key(name.as_str().unwrap()), "{} is required but missing", name);
+        }
+    }
+
+    #[test]
+    fn output_round_trips_and_matches_the_schema() {
+        let current = Current {
+            schema_version: SCHEMA_VERSION,
+            query: Some("bern@ch".to_string()),
+            location: place(),
+            observed_at: "2024-05-01T12:00".to_string(),
+            description: "Fog".to_string(),
+            summary: "Foggy and still".to_string(),
+            weather_code: 45,
+            temperature: 8.5,
+            apparent_temperature: 7.0,
+            humidity: 97,
+            precipitation: 0.0,
+            wind_speed: 3.0,
+            pressure: 1021.0,
+            uv_index: 1.5,
+            units: CurrentUnits {
+                temperature: "°C".to_string(),
+                wind_speed: "km/h".to_string(),
+                pressure: "hPa".to_string(),
+                precipitation: "mm".to_string(),
+            },
+        };
+        let forecast = Forecast {
+            schema_version: SCHEMA_VERSION,
+            location: place(),
+            units: ForecastUnits { temperature: "°C".to_string(), precipitation: "mm".to_string(), wind_speed: "km/h".to_string() },
+            days: vec![Day {
+                date: "2024-05-01".to_string(),
+                description: "Fog".to_string(),
+                weather_code: 45,
+                temperature_min: Some(6.0),
+                temperature_max: None,
+                precipitation: Some(0.2),
+                wind_speed_max: Some(12.0),
+            }],
+        };
+        let schema = json_schema();
+        let defs = &schema["$defs"];
+        let mut defs_with_place = defs["current"].clone();
+        defs_with_place["properties"]["location"] = defs["place"].clone();
+        let current_json = serde_json::to_value(&current).unwrap();
+        assert_matches(&current_json, &defs_with_place);
+        assert_eq!(serde_json::from_value::<Current>(current_json).unwrap(), current);
+
+        let forecast_json = serde_json::to_value(&forecast).unwrap();
+        assert_eq!(forecast_json["days"][0]["temperature_max"], Value::Null);
+        assert_matches(&forecast_json["days"][0], &defs["forecast"]["properties"]["days"]["items"]);
+        assert_eq!(serde_json::from_value::<Forecast>(forecast_json).unwrap(), forecast);
+
+        let error = serde_json::to_value(BatchError::new("Atlantis", "No such place")).unwrap();
+        assert_matches(&error, &defs["batch_error"]);
+        assert_eq!(error["schema_version"], json!(SCHEMA_VERSION));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::api::HourlyForecast;
+use crate::config::{Activity, Factor, HourlyMetric};
+use crate::geo;
+
+impl HourlyMetric {
+    // None when the forecast doesn't cover it, so the factor sits that hour out
+    fn at(self, hourly: &HourlyForecast, time: &str) -> Option<f64> {
+        let series = match self {
+            HourlyMetric::Temperature => &hourly.temperature,
+            HourlyMetric::WindSpeed => &hourly.wind_speed,
+            HourlyMetric::Precipitation => &hourly.precipitation,
+            HourlyMetric::RainChance => &hourly.precipitation_probability,
+            HourlyMetric::AirQuality => &hourly.air_quality,
+            HourlyMetric::Humidity => &hourly.humidity,
+            HourlyMetric::CloudCover => &hourly.cloud_cover,
+            HourlyMetric::Sunshine => return hourly.sunshine.at(time).map(|s| s / 60.0),
+            HourlyMetric::Daylight => &hourly.is_day,
+            HourlyMetric::Moon => return geo::moon_illumination(time),
+        };
+        series.at(time).copied()
+    }
+}
+
+impl Factor {
+    // 1 inside the ideal range, falling to 0 at `tolerance` beyond it
+    fn fit(&self, value: f64) -> f64 {
+        let off = self.above.map_or(0.0, |low| low - value).max(self.below.map_or(0.0, |high| value - high));
+        (1.0 - off.max(0.0) / self.tolerance.max(f64::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+// 0-10 for one hour, or None when none of the factors could be read
+pub fn at(activity: &Activity, hourly: &HourlyForecast, time: &str) -> Option<f64> {
+    let mut score = 1.0;
+    let mut read_any = false;
+    for factor in &activity.factors {
+        if let Some(value) = factor.metric.at(hourly, time) {
+            // Multiplied rather than averaged, so one bad factor (a downpour) sinks the lot
+            score *= factor.fit(value).powf(factor.weight.max(0.0));
+            read_any = true;
+        }
+    }
+    read_any.then_some(score * 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::series::TimeSeries;
+
+    fn hour(temperature: f64, wind: f64, rain_chance: f64) -> HourlyForecast {
+        let time = vec!["2024-05-01T12:00".to_string()];
+        HourlyForecast {
+            temperature: TimeSeries::new(time.clone(), vec![temperature], "°C"),
+            precipitation: TimeSeries::new(time.clone(), vec![0.0], "mm"),
+            wind_speed: TimeSeries::new(time.clone(), vec![wind], "km/h"),
+            precipitation_probability: TimeSeries::new(time, vec![rain_chance], "%"),
+            air_quality: TimeSeries::default(),
+            humidity: TimeSeries::default(),
+            cloud_cover: TimeSeries::default(),
+            sunshine: TimeSeries::default(),
+            is_day: TimeSeries::default(),
+            dew_point: TimeSeries::default(),
+            surface_temperature: TimeSeries::default(),
+        }
+    }
+
+    fn run() -> Activity {
+        Activity {
+            name: "Run".to_string(),
+            factors: vec![
+                Factor { metric: HourlyMetric::Temperature, above: Some(5.0), below: Some(18.0), tolerance: 10.0, weight: 1.0 },
+                Factor { metric: HourlyMetric::RainChance, above: None, below: Some(20.0), tolerance: 60.0, weight: 2.0 },
+            ],
+        }
+    }
+
+    #[test]
+    fn ideal_conditions_score_ten() {
+        assert_eq!(at(&run(), &hour(12.0, 5.0, 0.0), "2024-05-01T12:00"), Some(10.0));
+    }
+
+    #[test]
+    fn each_factor_pulls_the_score_down() {
+        let too_warm = at(&run(), &hour(23.0, 5.0, 0.0), "2024-05-01T12:00").unwrap_or_default();
+        assert!((too_warm - 5.0).abs() < 1e-9);
+        // Half way to the rain tolerance, squared by the weight of 2
+        let wet = at(&run(), &hour(12.0, 5.0, 50.0), "2024-05-01T12:00").unwrap_or_default();
+        assert!((wet - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stargazing_wants_the_moon_down() {
+        // Full moon on 2024-04-23, new moon on 2024-05-08
+        assert!(geo::moon_illumination("2024-04-23T23:00").unwrap_or_default() > 97.0);
+        assert!(geo::moon_illumination("2024-05-08T03:00").unwrap_or(100.0) < 3.0);
+    }
+
+    #[test]
+    fn activities_by_built_in_name_or_spelled_out() {
+        let config: crate::config::Config = toml::from_str(
+            r#"activities = ["laundry", { name = "Kite", factors = [{ metric = "wind_speed", above = 20, tolerance = 15 }] }]"#,
+        )
+        .expect("config should parse");
+        let names: Vec<&str> = config.activities().iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Laundry", "Kite"]);
+        assert!(toml::from_str::<crate::config::Config>(r#"activities = ["sunbathing"]"#).is_err());
+    }
+
+    #[test]
+    fn hours_outside_the_forecast_have_no_score() {
+        assert_eq!(at(&run(), &hour(12.0, 5.0, 0.0), "2024-05-02T12:00"), None);
+    }
+}
@@ -0,0 +1,116 @@
+use std::ops::Range;
+
+// Values stamped with local ISO times ("2024-05-01T14:00" hourly, "2024-05-01" daily), plus their unit.
+// Times sort lexically, which the lookups below rely on
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeSeries<T> {
+    pub time: Vec<String>,
+    pub values: Vec<T>,
+    pub unit: String,
+}
+
+impl<T> TimeSeries<T> {
+    // A short `values` (the API omits variables it has no data for) trims the times to match
+    pub fn new(mut time: Vec<String>, mut values: Vec<T>, unit: impl Into<String>) -> TimeSeries<T> {
+        let len = time.len().min(values.len());
+        time.truncate(len);
+        values.truncate(len);
+        TimeSeries { time, values, unit: unit.into() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.time.iter().map(String::as_str).zip(&self.values)
+    }
+
+    pub fn position(&self, time: &str) -> Option<usize> {
+        self.time.iter().position(|t| t == time)
+    }
+
+    pub fn at(&self, time: &str) -> Option<&T> {
+        self.position(time).map(|i| &self.values[i])
+    }
+
+    // The slot a moment falls into: "2024-05-01T14:15" is in the hourly slot "2024-05-01T14:00"
+    pub fn slot_of(&self, time: &str) -> Option<usize> {
+        self.time.iter().rposition(|t| t.as_str() <= time)
+    }
+
+    // Clamped to what's there, so asking for the next 24 hours near the end just gives fewer
+    pub fn slice(&self, range: Range<usize>) -> TimeSeries<T>
+    where
+        T: Clone,
+    {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        TimeSeries {
+            time: self.time[start..end].to_vec(),
+            values: self.values[start..end].to_vec(),
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+impl TimeSeries<f64> {
+    // Folds every `step` values into one, stamped with the first time of each group
+    pub fn resample(&self, step: usize, fold: impl Fn(&[f64]) -> f64) -> TimeSeries<f64> {
+        let step = step.max(1);
+        TimeSeries {
+            time: self.time.iter().step_by(step).cloned().collect(),
+            values: self.values.chunks(step).map(fold).collect(),
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hours(values: &[f64]) -> TimeSeries<f64> {
+        let time = (0..values.len()).map(|h| format!("2024-05-01T{:02}:00", h)).collect();
+        TimeSeries::new(time, values.to_vec(), "mm")
+    }
+
+    #[test]
+    fn a_slice_past_the_end_gives_what_there_is() {
+        let series = hours(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(series.slice(2..10).values, [3.0, 4.0]);
+        assert_eq!(series.slice(2..10).time, ["2024-05-01T02:00", "2024-05-01T03:00"]);
+        assert_eq!(series.slice(6..9).len(), 0);
+        assert_eq!(series.slice(0..4), series);
+        assert_eq!(series.slice(1..3).unit, "mm");
+    }
+
+    #[test]
+    fn resampling_keeps_a_short_last_bucket() {
+        let series = hours(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let sums = series.resample(2, |bucket| bucket.iter().sum());
+        assert_eq!(sums.values, [3.0, 7.0, 5.0]);
+        assert_eq!(sums.time, ["2024-05-01T00:00", "2024-05-01T02:00", "2024-05-01T04:00"]);
+
+        let means = series.resample(3, mean);
+        assert_eq!(means.values, [2.0, 4.5]);
+        assert_eq!(series.resample(0, mean), series);
+    }
+
+    #[test]
+    fn lookups_by_time() {
+        let series = TimeSeries::new(vec!["2024-05-01T13:00".into(), "2024-05-01T14:00".into()], vec![7.0, 8.0, 9.0], "°C");
+        assert_eq!(series.len(), 2);
+        assert_eq!(series.at("2024-05-01T14:00"), Some(&8.0));
+        assert_eq!(series.slot_of("2024-05-01T14:15"), Some(1));
+        assert_eq!(series.slot_of("2024-05-01T12:59"), None);
+    }
+}
@@ -0,0 +1,206 @@
+use crossterm::event::Event;
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+use tokio::time::{Duration, Instant};
+
+use crate::api::GeoLocation;
+use crate::history::HistoryEntry;
+
+// What --record writes and --replay plays back: the starting point, every key press and when it
+// came, and every HTTP exchange in the order it happened
+#[derive(Serialize, Deserialize, Default)]
+pub struct Session {
+    pub version: String,
+    pub history: Vec<HistoryEntry>,
+    pub home: Option<GeoLocation>,
+    pub events: Vec<TimedEvent>,
+    pub exchanges: Vec<Exchange>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TimedEvent {
+    // Milliseconds since the session started
+    pub at_ms: u64,
+    pub event: Event,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Exchange {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    // Only the ones the app reads back, for revalidating cached bodies
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    pub body: Body,
+}
+
+// Text where it is, so a session file can be read (and trimmed) by hand before it goes in an issue
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Body {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Body {
+    pub fn new(bytes: Vec<u8>) -> Body {
+        match String::from_utf8(bytes) {
+            Ok(text) => Body::Text(text),
+            Err(e) => Body::Bytes(e.into_bytes()),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Body::Text(text) => text.into_bytes(),
+            Body::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+enum Mode {
+    Record { path: PathBuf, started: Instant, session: Box<Mutex<Session>> },
+    Replay { exchanges: Mutex<Vec<Exchange>> },
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+pub fn start_recording(path: PathBuf, history: Vec<HistoryEntry>, home: Option<GeoLocation>) {
+    let session = Session { version: env!("CARGO_PKG_VERSION").to_string(), history, home, ..Session::default() };
+    let _ = MODE.set(Mode::Record { path, started: Instant::now(), session: Box::new(Mutex::new(session)) });
+}
+
+// Loads a recording and answers every request from it from now on; the caller gets the rest back to start from
+pub fn start_replay(path: &Path) -> Result<Session, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+    let mut session: Session =
+        serde_json::from_str(&content).map_err(|e| format!("{} isn't a session recording: {}", path.display(), e))?;
+    let exchanges = std::mem::take(&mut session.exchanges);
+    let _ = MODE.set(Mode::Replay { exchanges: Mutex::new(exchanges) });
+    Ok(session)
+}
+
+// Caches would answer differently on another machine, so they're skipped while either is going
+pub fn active() -> bool {
+    MODE.get().is_some()
+}
+
+pub fn recording() -> bool {
+    matches!(MODE.get(), Some(Mode::Record { .. }))
+}
+
+// During a replay, the recorded answer to a request, or a 404 for one the recording never made.
+// Identical requests are answered in the order they were recorded
+pub fn replayed(method: &str, url: &str) -> Option<Exchange> {
+    let Some(Mode::Replay { exchanges }) = MODE.get() else {
+        return None;
+    };
+    let url = scrub(url);
+    let mut exchanges = exchanges.lock().ok()?;
+    Some(match exchanges.iter().position(|e| e.method == method && e.url == url) {
+        Some(i) => exchanges.remove(i),
+        None => Exchange {
+            method: method.to_string(),
+            url,
+            status: 404,
+            headers: Vec::new(),
+            body: Body::Text("Not in the recording".to_string()),
+        },
+    })
+}
+
+pub fn record_exchange(mut exchange: Exchange) {
+    if let Some(Mode::Record { session, .. }) = MODE.get() {
+        exchange.url = scrub(&exchange.url);
+        if let Ok(mut session) = session.lock() {
+            session.exchanges.push(exchange);
+        }
+    }
+}
+
+pub fn record_event(event: &Event) {
+    // Mouse movement would swamp the file, and hovering never changes what a replay ends up doing
+    if !matches!(event, Event::Key(_) | Event::Resize(..)) {
+        return;
+    }
+    if let Some(Mode::Record { started, session, .. }) = MODE.get() {
+        let at_ms = started.elapsed().as_millis() as u64;
+        if let Ok(mut session) = session.lock() {
+            session.events.push(TimedEvent { at_ms, event: event.clone() });
+        }
+    }
+}
+
+// Writes out everything recorded so far; called on the way out, and from the panic hook so a crash still leaves a file
+pub fn save() -> Result<(), String> {
+    let Some(Mode::Record { path, session, .. }) = MODE.get() else {
+        return Ok(());
+    };
+    let session = session.lock().map_err(|_| "The recording was poisoned by a panic".to_string())?;
+    let json = serde_json::to_string_pretty(&*session).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Can't write {}: {}", path.display(), e))
+}
+
+// The recorded key presses at their original pace
+pub fn replay_events(events: Vec<TimedEvent>) -> impl Stream<Item = io::Result<Event>> {
+    let started = Instant::now();
+    stream::iter(events).then(move |e| async move {
+        tokio::time::sleep_until(started + Duration::from_millis(e.at_ms)).await;
+        Ok(e.event)
+    })
+}
+
+// API keys ride along in some query strings; they shouldn't end up attached to an issue
+pub fn scrub(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let params: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((name @ ("key" | "token" | "apikey"), _)) => format!("{}=REDACTED", name),
+            _ => param.to_string(),
+        })
+        .collect();
+    format!("{}?{}", base, params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    #[test]
+    fn keys_come_out_of_the_url() {
+        assert_eq!(
+            scrub("https://www.worldtides.info/api/v3?extremes&lat=1&key=s3cret&days=2"),
+            "https://www.worldtides.info/api/v3?extremes&lat=1&key=REDACTED&days=2"
+        );
+        assert_eq!(scrub("https://example.com/a"), "https://example.com/a");
+    }
+
+    #[test]
+    fn a_session_survives_the_round_trip() {
+        let session = Session {
+            events: vec![TimedEvent { at_ms: 250, event: Event::Key(KeyEvent::from(KeyCode::Char('i'))) }],
+            exchanges: vec![Exchange {
+                method: "GET".to_string(),
+                url: "https://example.com/".to_string(),
+                status: 200,
+                headers: Vec::new(),
+                body: Body::new(vec![0xff, 0x00]),
+            }],
+            ..Session::default()
+        };
+        let json = serde_json::to_string(&session).expect("session should serialize");
+        let back: Session = serde_json::from_str(&json).expect("session should parse");
+        assert_eq!(back.events[0].event, Event::Key(KeyEvent::from(KeyCode::Char('i'))));
+        assert_eq!(back.exchanges[0].body.clone().into_bytes(), vec![0xff, 0x00]);
+    }
+}
@@ -0,0 +1,120 @@
+// The weather view as a standalone HTML page, for `:export view.html`: every cell of the frame in a
+// <pre>, in the colours it had on screen, so it can be mailed or put on a web server as it is.
+// A snapshot.html next to config.toml replaces the built-in page
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+    text::Span,
+};
+use std::fs;
+
+use crate::colors;
+use crate::config;
+use crate::template::{self, escape_html};
+
+// Colours the terminal left at its default come out in the page's own
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{ title }}</title>
+<style>
+  body { background: #101418; margin: 2em; }
+  pre { color: #d0d0d0; background: #101418; font: 14px/1.2 "DejaVu Sans Mono", Menlo, Consolas, monospace; }
+  footer { color: #808080; font: 12px sans-serif; }
+</style>
+</head>
+<body>
+<pre>{{ screen }}</pre>
+<footer>{{ title }}, {{ generated }}</footer>
+</body>
+</html>
+"#;
+
+pub fn html(buf: &Buffer, title: &str, generated: &str) -> Result<String, String> {
+    let path = config::get_config_path().with_file_name("snapshot.html");
+    let custom = fs::read_to_string(&path).ok();
+    let screen = screen(buf);
+    let (title, generated) = (escape_html(title), escape_html(generated));
+    template::render(custom.as_deref().unwrap_or(TEMPLATE), &[("title", &title), ("generated", &generated), ("screen", &screen)])
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// One <span> per run of cells styled alike, rather than one per cell
+fn screen(buf: &Buffer) -> String {
+    let mut out = String::new();
+    for y in 0..buf.area.height {
+        let mut run: Option<(String, String)> = None;
+        let mut covered = 0;
+        for x in 0..buf.area.width {
+            // The cell after a wide character is drawn over by it
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            let cell = &buf[(x, y)];
+            covered = Span::raw(cell.symbol()).width().saturating_sub(1);
+            let style = css(cell.fg, cell.bg, cell.modifier);
+            match &mut run {
+                Some((current, text)) if *current == style => text.push_str(&escape_html(cell.symbol())),
+                _ => {
+                    close(&mut out, run.take());
+                    run = Some((style, escape_html(cell.symbol())));
+                }
+            }
+        }
+        close(&mut out, run);
+        out.push('\n');
+    }
+    out
+}
+
+fn close(out: &mut String, run: Option<(String, String)>) {
+    match run {
+        Some((style, text)) if style.is_empty() => out.push_str(&text),
+        Some((style, text)) => out.push_str(&format!("<span style=\"{}\">{}</span>", style, text)),
+        None => {}
+    }
+}
+
+fn css(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let (fg, bg) = if modifier.contains(Modifier::REVERSED) {
+        (colors::hex(bg).or_else(|| Some("#101418".to_string())), colors::hex(fg).or_else(|| Some("#d0d0d0".to_string())))
+    } else {
+        (colors::hex(fg), colors::hex(bg))
+    };
+    let mut css = Vec::new();
+    css.extend(fg.map(|c| format!("color:{}", c)));
+    css.extend(bg.map(|c| format!("background:{}", c)));
+    if modifier.contains(Modifier::BOLD) {
+        css.push("font-weight:bold".to_string());
+    }
+    if modifier.contains(Modifier::ITALIC) {
+        css.push("font-style:italic".to_string());
+    }
+    if modifier.contains(Modifier::DIM) {
+        css.push("opacity:0.6".to_string());
+    }
+    if modifier.contains(Modifier::UNDERLINED) {
+        css.push("text-decoration:underline".to_string());
+    }
+    css.join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn cells_become_spans_in_their_colours() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+        buf.set_string(0, 0, "Oslo", Style::default().fg(Color::Rgb(0xff, 0x87, 0x00)).add_modifier(Modifier::BOLD));
+        buf.set_string(5, 0, "<3", Style::default());
+        buf.set_string(0, 1, "⛅ 7°", Style::default().fg(Color::Cyan));
+        assert_eq!(
+            screen(&buf),
+            "<span style=\"color:#ff8700;font-weight:bold\">Oslo</span> &lt;3 \n<span style=\"color:#00cdcd\">⛅ 7°</span>   \n"
+        );
+    }
+}
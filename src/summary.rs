@@ -0,0 +1,159 @@
+use crate::api::WeatherResponse;
+use crate::forecast::{self, WET_MM};
+use crate::units::Units;
+
+// How far ahead the rain and overnight-low clauses look
+pub const LOOKAHEAD_HOURS: usize = 12;
+// From this hour on, the rest of the day's high is old news and the night's low matters more
+const EVENING_HOUR: u32 = 15;
+
+// One line a person would say looking out the window, e.g.
+// "Cold and breezy, rain likely after 15:00, high of 7°", the high or low in `units`
+pub fn sentence(weather: &WeatherResponse, units: Units) -> String {
+    let current = &weather.current;
+    let mut feel = vec![feel(current.apparent_temperature)];
+    feel.extend(sky(current.weather_code));
+    feel.extend(wind(current.wind_speed_10m));
+    let mut parts = vec![join_and(&feel)];
+
+    parts.extend(rain_outlook(weather));
+    if let Some(hourly) = &weather.hourly {
+        if let Some(now) = hourly.temperature.slot_of(&current.time) {
+            let ahead = hourly.temperature.slice(now..now + LOOKAHEAD_HOURS);
+            let hour: u32 = forecast::hour_of(&current.time).get(..2).and_then(|h| h.parse().ok()).unwrap_or(0);
+            let today = current.time.split('T').next().unwrap_or_default();
+            let rest_of_today = ahead.iter().filter(|(time, _)| time.starts_with(today)).map(|(_, &t)| t);
+            if hour < EVENING_HOUR {
+                if let Some(high) = rest_of_today.reduce(f64::max) {
+                    parts.push(format!("high of {:.0}°", units.temperature(high)));
+                }
+            } else if let Some(low) = ahead.values.iter().copied().reduce(f64::min) {
+                parts.push(format!("low of {:.0}° tonight", units.temperature(low)));
+            }
+        }
+    }
+    parts.join(", ")
+}
+
+// When the rain (or snow) starts or stops over the next few hours, e.g. "rain likely after 15:00";
+// None when it stays dry or there's no hourly forecast to go on
+pub fn rain_outlook(weather: &WeatherResponse) -> Option<String> {
+    let current = &weather.current;
+    let hourly = weather.hourly.as_ref()?;
+    let now = hourly.temperature.slot_of(&current.time)?;
+    let ahead = hourly.temperature.slice(now..now + LOOKAHEAD_HOURS);
+    let wet = |time: &str| hourly.precipitation.at(time).is_some_and(|&mm| mm >= WET_MM);
+    let falling = |time: &str| if hourly.temperature.at(time).is_some_and(|&t| t <= 1.0) { "snow" } else { "rain" };
+
+    let raining = current.precipitation >= WET_MM;
+    let change = ahead.iter().skip(1).find(|&(time, _)| wet(time) != raining).map(|(time, _)| time);
+    match (raining, change) {
+        (true, Some(time)) => Some(format!("drying out by {}", forecast::hour_of(time))),
+        (true, None) => Some(format!("{} for the next {} hours", falling(&current.time), LOOKAHEAD_HOURS)),
+        (false, Some(time)) => Some(format!("{} likely after {}", falling(time), forecast::hour_of(time))),
+        (false, None) => None,
+    }
+}
+
+// By the feels-like temperature, since that's what decides the coat
+fn feel(apparent: f64) -> &'static str {
+    match apparent {
+        t if t <= -10.0 => "Bitterly cold",
+        t if t <= 0.0 => "Freezing",
+        t if t <= 8.0 => "Cold",
+        t if t <= 14.0 => "Cool",
+        t if t <= 20.0 => "Mild",
+        t if t <= 27.0 => "Warm",
+        t if t <= 33.0 => "Hot",
+        _ => "Sweltering",
+    }
+}
+
+// Only the skies worth a word; rain and snow get their own clause
+fn sky(code: u32) -> Option<&'static str> {
+    match code {
+        0 | 1 => Some("clear"),
+        3 => Some("grey"),
+        45 | 48 => Some("foggy"),
+        95..=99 => Some("stormy"),
+        _ => None,
+    }
+}
+
+// km/h
+fn wind(speed: f64) -> Option<&'static str> {
+    match speed {
+        s if s >= 50.0 => Some("blowing a gale"),
+        s if s >= 30.0 => Some("windy"),
+        s if s >= 20.0 => Some("breezy"),
+        _ => None,
+    }
+}
+
+// ["Cold", "grey", "windy"] -> "Cold, grey and windy"
+fn join_and(words: &[&str]) -> String {
+    match words {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Hourly from midnight, with `now` as the observation time
+    fn weather(now: &str, apparent: f64, wind: f64, code: u32, temperatures: &[f64], rain: &[f64]) -> WeatherResponse {
+        let time: Vec<String> = (0..temperatures.len()).map(|h| format!("2024-05-01T{:02}:00", h)).collect();
+        serde_json::from_value(json!({
+            "current": {
+                "time": now,
+                "temperature_2m": temperatures[0],
+                "relative_humidity_2m": 70,
+                "apparent_temperature": apparent,
+                "precipitation": rain[0],
+                "weather_code": code,
+                "wind_speed_10m": wind,
+                "pressure_msl": 1010.0,
+            },
+            "current_units": { "temperature_2m": "°C", "wind_speed_10m": "km/h", "pressure_msl": "hPa" },
+            "hourly": { "time": time, "temperature_2m": temperatures, "precipitation": rain },
+            "hourly_units": { "temperature_2m": "°C", "precipitation": "mm" },
+        }))
+        .expect("test forecast should parse")
+    }
+
+    #[test]
+    fn a_dry_morning_with_rain_later() {
+        let mut rain = vec![0.0; 24];
+        rain[15] = 0.6;
+        let temperatures: Vec<f64> = (0..24).map(|h| 2.0 + (h as f64 / 3.0).min(5.0)).collect();
+        let weather = weather("2024-05-01T08:10", 1.5, 24.0, 2, &temperatures, &rain);
+        assert_eq!(sentence(&weather, Units::default()), "Cold and breezy, rain likely after 15:00, high of 7°");
+    }
+
+    #[test]
+    fn rain_that_stops() {
+        let mut rain = vec![0.0; 24];
+        rain[..12].fill(1.0);
+        let weather = weather("2024-05-01T09:00", 16.0, 5.0, 3, &[16.0; 24], &rain);
+        assert_eq!(sentence(&weather, Units::default()), "Mild and grey, drying out by 12:00, high of 16°");
+    }
+
+    #[test]
+    fn evenings_give_the_low() {
+        let temperatures: Vec<f64> = (0..24).map(|h| 30.0 - h as f64 * 0.4).collect();
+        let weather = weather("2024-05-01T18:30", 29.0, 0.0, 0, &temperatures, &[0.0; 24]);
+        assert_eq!(sentence(&weather, Units::default()), "Hot and clear, low of 21° tonight");
+        assert_eq!(sentence(&weather, Units::IMPERIAL), "Hot and clear, low of 69° tonight");
+    }
+
+    #[test]
+    fn without_hourly_data_just_the_feel() {
+        let mut weather = weather("2024-05-01T12:00", -12.0, 55.0, 71, &[-8.0], &[0.2]);
+        weather.hourly = None;
+        assert_eq!(sentence(&weather, Units::default()), "Bitterly cold and blowing a gale");
+    }
+}
@@ -0,0 +1,88 @@
+// Just enough templating for the HTML snapshot and `get --format`. Values go in as they are; escaping
+// is the caller's job, since only the caller knows which values are already markup
+use std::borrow::Borrow;
+
+// `{{ name }}`, for pages, where single braces are all over the CSS
+pub fn render(template: &str, values: &[(&str, &str)]) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| "A {{ in the template is never closed".to_string())?;
+        let name = after[..end].trim();
+        let value = lookup(values, name).ok_or_else(|| format!("Unknown placeholder {{{{{}}}}} in the template", name))?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// `{name}`, for one-line formats; `{{` and `}}` are literal braces, as in Rust's format!
+pub fn format<V: Borrow<str>>(pattern: &str, values: &[(&str, V)]) -> Result<String, String> {
+    let mut out = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let value = lookup(values, name.trim()).ok_or_else(|| {
+                    let known: Vec<&str> = values.iter().map(|(key, _)| *key).collect();
+                    format!("Unknown placeholder {{{}}}; there's {{{}}}", name.trim(), known.join("}, {"))
+                })?;
+                out.push_str(value);
+            }
+            _ => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+fn lookup<'a, V: Borrow<str>>(values: &'a [(&str, V)], name: &str) -> Option<&'a str> {
+    values.iter().find(|(key, _)| *key == name).map(|(_, value)| value.borrow())
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_fill_in_and_mistakes_are_errors() {
+        let values = [("title", "Rain &amp; wind"), ("body", "<pre></pre>")];
+        assert_eq!(render("<h1>{{title}}</h1>{{ body }}", &values).unwrap(), "<h1>Rain &amp; wind</h1><pre></pre>");
+        assert_eq!(render("{{ colour }}", &values).unwrap_err(), "Unknown placeholder {{colour}} in the template");
+        assert!(render("{{ title", &values).is_err());
+        assert_eq!(escape_html("<b>\"Sun\" & cloud</b>"), "&lt;b&gt;&quot;Sun&quot; &amp; cloud&lt;/b&gt;");
+    }
+
+    #[test]
+    fn one_line_formats() {
+        let values = [("city", "Oslo"), ("temp", "-3")];
+        assert_eq!(format("{city}: {temp}°", &values).unwrap(), "Oslo: -3°");
+        assert_eq!(format("{{{city}}}", &values).unwrap(), "{Oslo}");
+        assert_eq!(format("{ wind }", &values).unwrap_err(), "Unknown placeholder {wind}; there's {city}, {temp}");
+    }
+}
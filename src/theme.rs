@@ -0,0 +1,104 @@
+// The views pick from the terminal's sixteen named colours; a theme swaps those for its own on the
+// finished frame, so the drawing code never has to know which one is on
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    // Whatever the terminal's palette says
+    #[default]
+    Default,
+    Nord,
+    Gruvbox,
+    // No colour at all; highlighted cells are drawn in reverse video instead
+    Mono,
+}
+
+impl Theme {
+    pub fn apply(self, buf: &mut Buffer) {
+        match self {
+            Theme::Default => {}
+            Theme::Mono => {
+                for cell in buf.content.iter_mut() {
+                    if cell.bg != Color::Reset {
+                        cell.modifier.insert(Modifier::REVERSED);
+                    }
+                    cell.fg = Color::Reset;
+                    cell.bg = Color::Reset;
+                }
+            }
+            Theme::Nord | Theme::Gruvbox => {
+                for cell in buf.content.iter_mut() {
+                    cell.fg = self.color(cell.fg);
+                    cell.bg = self.color(cell.bg);
+                }
+            }
+        }
+    }
+
+    fn color(self, color: Color) -> Color {
+        let (r, g, b) = match (self, color) {
+            (Theme::Nord, Color::Black) => (46, 52, 64),
+            (Theme::Nord, Color::Red | Color::LightRed) => (191, 97, 106),
+            (Theme::Nord, Color::Green | Color::LightGreen) => (163, 190, 140),
+            (Theme::Nord, Color::Yellow | Color::LightYellow) => (235, 203, 139),
+            (Theme::Nord, Color::Blue | Color::LightBlue) => (129, 161, 193),
+            (Theme::Nord, Color::Magenta | Color::LightMagenta) => (180, 142, 173),
+            (Theme::Nord, Color::Cyan | Color::LightCyan) => (136, 192, 208),
+            (Theme::Nord, Color::Gray) => (216, 222, 233),
+            (Theme::Nord, Color::DarkGray) => (76, 86, 106),
+            (Theme::Nord, Color::White) => (236, 239, 244),
+            (Theme::Gruvbox, Color::Black) => (40, 40, 40),
+            (Theme::Gruvbox, Color::Red | Color::LightRed) => (251, 73, 52),
+            (Theme::Gruvbox, Color::Green | Color::LightGreen) => (184, 187, 38),
+            (Theme::Gruvbox, Color::Yellow | Color::LightYellow) => (250, 189, 47),
+            (Theme::Gruvbox, Color::Blue | Color::LightBlue) => (131, 165, 152),
+            (Theme::Gruvbox, Color::Magenta | Color::LightMagenta) => (211, 134, 155),
+            (Theme::Gruvbox, Color::Cyan | Color::LightCyan) => (142, 192, 124),
+            (Theme::Gruvbox, Color::Gray) => (168, 153, 132),
+            (Theme::Gruvbox, Color::DarkGray) => (102, 92, 84),
+            (Theme::Gruvbox, Color::White) => (235, 219, 178),
+            // Reset, and anything a view already gave an exact colour, stays as it is
+            _ => return color,
+        };
+        Color::Rgb(r, g, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    fn frame() -> Buffer {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buf.set_string(0, 0, "a", Style::default().fg(Color::Cyan));
+        buf.set_string(1, 0, "b", Style::default().fg(Color::Black).bg(Color::Yellow));
+        buf.set_string(2, 0, "c", Style::default().fg(Color::Rgb(1, 2, 3)));
+        buf
+    }
+
+    #[test]
+    fn a_palette_theme_recolours_named_colours_only() {
+        let mut buf = frame();
+        Theme::Nord.apply(&mut buf);
+        assert_eq!(buf.content[0].fg, Color::Rgb(136, 192, 208));
+        assert_eq!((buf.content[1].fg, buf.content[1].bg), (Color::Rgb(46, 52, 64), Color::Rgb(235, 203, 139)));
+        assert_eq!(buf.content[2].fg, Color::Rgb(1, 2, 3));
+        assert_eq!(buf.content[0].bg, Color::Reset);
+    }
+
+    #[test]
+    fn mono_keeps_highlights_visible_without_colour() {
+        let mut buf = frame();
+        Theme::Mono.apply(&mut buf);
+        assert!(buf.content.iter().all(|c| c.fg == Color::Reset && c.bg == Color::Reset));
+        assert!(buf.content[1].modifier.contains(Modifier::REVERSED));
+        assert!(!buf.content[0].modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,113 @@
+// What readings are shown in. Forecasts are always fetched and reasoned about in °C and km/h,
+// so rules, gear and scores mean the same thing everywhere; these only change what's on screen
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct Units {
+    pub temperature: Temperature,
+    pub wind_speed: WindSpeed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Temperature {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WindSpeed {
+    #[default]
+    Kmh,
+    Mph,
+    Ms,
+    Kn,
+}
+
+impl Units {
+    pub const METRIC: Units = Units { temperature: Temperature::Celsius, wind_speed: WindSpeed::Kmh };
+    pub const IMPERIAL: Units = Units { temperature: Temperature::Fahrenheit, wind_speed: WindSpeed::Mph };
+
+    pub fn temperature(self, celsius: f64) -> f64 {
+        match self.temperature {
+            Temperature::Celsius => celsius,
+            Temperature::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    // How far apart two readings are, which unlike a reading has no offset to add
+    pub fn temperature_difference(self, celsius: f64) -> f64 {
+        match self.temperature {
+            Temperature::Celsius => celsius,
+            Temperature::Fahrenheit => celsius * 9.0 / 5.0,
+        }
+    }
+
+    pub fn temperature_unit(self) -> &'static str {
+        match self.temperature {
+            Temperature::Celsius => "°C",
+            Temperature::Fahrenheit => "°F",
+        }
+    }
+
+    pub fn wind_speed(self, kmh: f64) -> f64 {
+        match self.wind_speed {
+            WindSpeed::Kmh => kmh,
+            WindSpeed::Mph => kmh / 1.609344,
+            WindSpeed::Ms => kmh / 3.6,
+            WindSpeed::Kn => kmh / 1.852,
+        }
+    }
+
+    pub fn wind_speed_unit(self) -> &'static str {
+        match self.wind_speed {
+            WindSpeed::Kmh => "km/h",
+            WindSpeed::Mph => "mph",
+            WindSpeed::Ms => "m/s",
+            WindSpeed::Kn => "kn",
+        }
+    }
+
+    // `:units fahrenheit`, `:units imperial` or `:units c kn`: each word changes what it names
+    // and leaves the rest as it was
+    pub fn with(self, words: &[&str]) -> Result<Units, String> {
+        let mut units = self;
+        for word in words {
+            match word.to_lowercase().as_str() {
+                "metric" => units = Units::METRIC,
+                "imperial" => units = Units::IMPERIAL,
+                "c" | "celsius" => units.temperature = Temperature::Celsius,
+                "f" | "fahrenheit" => units.temperature = Temperature::Fahrenheit,
+                "kmh" | "km/h" => units.wind_speed = WindSpeed::Kmh,
+                "mph" => units.wind_speed = WindSpeed::Mph,
+                "ms" | "m/s" => units.wind_speed = WindSpeed::Ms,
+                "kn" | "knots" => units.wind_speed = WindSpeed::Kn,
+                other => {
+                    return Err(format!("Unknown unit '{}'; try metric, imperial, celsius, fahrenheit, kmh, mph, ms or kn", other))
+                }
+            }
+        }
+        Ok(units)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_and_words() {
+        assert_eq!(Units::IMPERIAL.temperature(-40.0), -40.0);
+        assert_eq!(Units::IMPERIAL.temperature(20.0), 68.0);
+        assert_eq!(Units::IMPERIAL.temperature_difference(5.0), 9.0);
+        assert_eq!(format!("{:.1}", Units::IMPERIAL.wind_speed(100.0)), "62.1");
+        assert_eq!(Units::METRIC.with(&["f"]).unwrap(), Units { temperature: Temperature::Fahrenheit, wind_speed: WindSpeed::Kmh });
+        assert_eq!(Units::IMPERIAL.with(&["Celsius", "kn"]).unwrap().wind_speed_unit(), "kn");
+        assert!(Units::METRIC.with(&["kelvin"]).is_err());
+        let units: Units = toml::from_str("wind_speed = \"ms\"").unwrap();
+        assert_eq!(units, Units { temperature: Temperature::Celsius, wind_speed: WindSpeed::Ms });
+    }
+}
@@ -0,0 +1,2422 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{
+        canvas::{Canvas, Line as CanvasLine, Map, MapResolution},
+        Axis, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem, ListState, Paragraph, Widget, Wrap,
+    },
+    Frame,
+};
+use std::borrow::Cow;
+
+use crate::api::{weather_code_to_description, Cyclone, HourlyForecast, Provider, WeatherData};
+use crate::app::{App, AppState, KioskTile, Mode, ACCURACY_DAYS, NEARBY_OFFSET_KM, SPINNER_FRAMES, TICKS_PER_SECOND};
+use crate::barograph::{self, DROP_HOURS, STORM_DROP_HPA};
+use crate::bigtext;
+use crate::action::{self, Action};
+use crate::comfort::{Comfort, BANDS};
+use crate::colors;
+use crate::config::{self, Glyphs, ViewStyle};
+use crate::effects::{Falling, Precipitation};
+use crate::events::{Event, EventKind};
+use crate::focus::Pane;
+use crate::forecast;
+use crate::geo;
+use crate::glyphs::{self, Icon};
+use crate::history;
+use crate::roads;
+use crate::route::ProfilePoint;
+use crate::rules;
+use crate::score;
+use crate::series;
+use crate::summary;
+use crate::units::Units;
+use crate::fuzzy;
+
+const SEARCH_PLACEHOLDER: &str = "Type a city, postal code, or lat,lon…";
+// Bearable without a wetsuit for most people, when swim_temperature isn't set
+const SWIM_TEMPERATURE: f64 = 16.0;
+
+pub fn ui(f: &mut Frame, app: &App) {
+    draw(f, app);
+    if app.config.glyphs == Glyphs::Ascii {
+        glyphs::asciify(f.buffer_mut());
+    }
+    // A theme's colours are 24-bit, so they get brought down along with everything else
+    app.config.theme.apply(f.buffer_mut());
+    colors::degrade(f.buffer_mut(), app.config.colors);
+}
+
+fn icon(app: &App, icon: Icon) -> &'static str {
+    icon.glyph(app.config.glyphs)
+}
+
+fn draw(f: &mut Frame, app: &App) {
+    if app.kiosk {
+        kiosk(f, app);
+        return;
+    }
+    if let (ViewStyle::Compact, AppState::Display, Some(data)) = (app.config.view, &app.state, &app.weather_data) {
+        compact(f, app, data);
+        if let Some(popup) = &app.popup {
+            popup.render(f);
+        }
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(10),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    let mode_text = match app.mode {
+        Mode::Normal => " -- NORMAL --",
+        Mode::Insert => " -- INSERT --",
+    };
+    
+    let profile = config::profile().map(|p| format!(" [{}]", p)).unwrap_or_default();
+    let read_only = if app.read_only { " [read-only]" } else { "" };
+    let title = Paragraph::new(format!("{}  Weather TUI Search{}{}{}{}", icon(app, Icon::Title), profile, read_only, mode_text, from_home(app)))
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Large print needs all the width it can get, so the history makes way
+    let history_width = if app.config.view == ViewStyle::Large && app.state == AppState::Display { 0 } else { 30 };
+    let main_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(100 - history_width), Constraint::Percentage(history_width)])
+        .split(chunks[1]);
+
+    match app.state {
+        AppState::Input => {
+            // The input with the cursor drawn in, as slices of it rather than a copy
+            let cursor = app.input.char_indices().nth(app.cursor_position).map(|(at, _)| at);
+            let at_end = app.cursor_position == app.input.chars().count();
+            // and how many of the spans it takes to get past the cursor
+            let (mut input_display, through_cursor) = match (cursor, at_end) {
+                (Some(at), _) if app.mode == Mode::Insert => {
+                    (vec![Span::raw(&app.input[..at]), Span::raw("█"), Span::raw(&app.input[at..])], 2)
+                }
+                (Some(at), _) => {
+                    let next = at + app.input[at..].chars().next().map_or(0, char::len_utf8);
+                    let (before, under, after) = (&app.input[..at], &app.input[at..next], &app.input[next..]);
+                    (vec![Span::raw(before), Span::raw("["), Span::raw(under), Span::raw("]"), Span::raw(after)], 4)
+                }
+                (None, true) => (vec![Span::raw(app.input.as_str()), Span::raw("█")], 2),
+                (None, false) => (vec![Span::raw(app.input.as_str())], 1),
+            };
+            // A query longer than the box slides left to keep the cursor in view, rather than
+            // losing its tail off the right-hand edge
+            let cursor_end: usize = input_display.iter().take(through_cursor).map(Span::width).sum();
+            let scroll = (cursor_end as u16).saturating_sub(main_chunks[0].width.saturating_sub(2));
+            // Dimmed hints after the cursor: what to type, or the rest of the top suggestion
+            let hint = Style::default().fg(Color::DarkGray);
+            if app.input.is_empty() {
+                input_display.push(Span::styled(SEARCH_PLACEHOLDER, hint));
+            } else {
+                input_display.extend(app.ghost_text().map(|h| Span::styled(h, hint)));
+            }
+
+            let search_border_style = if app.focused_pane == Pane::Search {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+
+            let input = Paragraph::new(Line::from(input_display))
+                .scroll((0, scroll))
+                .style(Style::default().fg(Color::Yellow))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(search_border_style)
+                        .title(format!("Search City (Mode: {})", 
+                            if app.mode == Mode::Normal { "NORMAL" } else { "INSERT" })),
+                );
+
+            if app.show_autocomplete && !app.autocomplete_suggestions.is_empty() {
+                let input_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Min(5)])
+                    .split(main_chunks[0]);
+
+                f.render_widget(input, input_chunks[0]);
+
+                let query = app.autocomplete_query();
+                let suggestions: Vec<ListItem> = app
+                    .autocomplete_suggestions
+                    .iter()
+                    .zip(&app.autocomplete_regions)
+                    .enumerate()
+                    .map(|(i, (s, region_str))| {
+                        let style = if i == app.selected_suggestion {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default().fg(Color::White)
+                        };
+                        let number = if i < 9 { format!("{} ", i + 1) } else { "  ".to_string() };
+                        let number_style = if i == app.selected_suggestion {
+                            style
+                        } else {
+                            style.fg(Color::DarkGray)
+                        };
+                        let mut spans = vec![Span::styled(number, number_style)];
+                        spans.extend(highlight_matches(&s.name, &query, style));
+                        spans.push(Span::styled(format!("{} ({})", region_str, s.country), style));
+                        ListItem::new(Line::from(spans)).style(style)
+                    })
+                    .collect();
+
+                let autocomplete = List::new(suggestions).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(
+                            "Suggestions ({} to select, {} or Alt+1-9 to accept)",
+                            keys(app, &[Action::SelectPrev, Action::SelectNext]),
+                            keys(app, &[Action::Complete])
+                        )),
+                );
+                // Stateful so the selection stays scrolled into view at any terminal height
+                let mut state = ListState::default().with_selected(Some(app.selected_suggestion));
+                f.render_stateful_widget(autocomplete, input_chunks[1], &mut state);
+            } else if app.showing_recent() {
+                let input_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Min(5)])
+                    .split(main_chunks[0]);
+
+                f.render_widget(input, input_chunks[0]);
+
+                let recent: Vec<ListItem> = app
+                    .search_history
+                    .iter()
+                    .take(app.recent_count())
+                    .enumerate()
+                    .map(|(i, entry)| {
+                        let style = if i == app.selected_recent {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default().fg(Color::Gray)
+                        };
+                        ListItem::new(entry.query.as_str()).style(style)
+                    })
+                    .collect();
+
+                let recent_list = List::new(recent).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(
+                            "Recent searches ({} to select, {} to search, {} to edit)",
+                            keys(app, &[Action::SelectPrev, Action::SelectNext]),
+                            keys(app, &[Action::Submit]),
+                            keys(app, &[Action::Complete])
+                        )),
+                );
+                f.render_widget(recent_list, input_chunks[1]);
+            } else {
+                f.render_widget(input, main_chunks[0]);
+            }
+        }
+        AppState::Loading => {
+            let spinner = SPINNER_FRAMES[app.tick as usize % SPINNER_FRAMES.len()];
+            let loading = Paragraph::new(format!("{} Loading weather data...", spinner))
+                .style(Style::default().fg(Color::Yellow))
+                .block(Block::default().borders(Borders::ALL).title("Status"));
+            f.render_widget(loading, main_chunks[0]);
+        }
+        AppState::Display if app.config.view == ViewStyle::Large => {
+            if let Some(data) = &app.weather_data {
+                large_print(f, app, data, main_chunks[0]);
+            }
+        }
+        AppState::Display => {
+            if let Some(data) = &app.weather_data {
+                let details_area = match &app.earthquakes {
+                    Some(quakes) => {
+                        let rows = quakes.as_ref().map_or(1, |q| q.len().clamp(1, 5)) as u16;
+                        let hazard_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Min(10), Constraint::Length(rows + 2)])
+                            .split(main_chunks[0]);
+                        hazards(f, app, data, hazard_chunks[1]);
+                        hazard_chunks[0]
+                    }
+                    None => main_chunks[0],
+                };
+                // Plugin panels get a column on the right
+                let details_area = if app.plugin_panels.is_empty() {
+                    details_area
+                } else {
+                    let plugin_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Min(40), Constraint::Length(36)])
+                        .split(details_area);
+                    plugin_panels(f, app, plugin_chunks[1]);
+                    plugin_chunks[0]
+                };
+
+                let weather_desc = weather_code_to_description(data.weather.current.weather_code);
+                let region_str = data.location.admin1.as_ref()
+                    .map(|r| format!(", {}", r))
+                    .unwrap_or_default();
+
+                let mut weather_text = Vec::new();
+                if let Some(severe) = app.severe_weather() {
+                    // Flashes once a second unless the steady high-contrast banner is configured
+                    let style = if app.config.static_alert_banner {
+                        Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+                    } else if (app.tick / TICKS_PER_SECOND).is_multiple_of(2) {
+                        Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                    };
+                    weather_text.push(Line::from(Span::styled(format!(" {} SEVERE WEATHER: {} ", icon(app, Icon::Warning), severe), style)));
+                    weather_text.push(Line::from(""));
+                }
+                weather_text.extend([
+                    Line::from(Span::styled(
+                        summary::sentence(&data.weather, data.units),
+                        Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+                    )),
+                ]);
+                if let Some(warning) = road_ice(data) {
+                    weather_text.push(Line::from(Span::styled(
+                        format!("{} Roads: {}", icon(app, Icon::Warning), warning.message()),
+                        Style::default().fg(Color::LightBlue).add_modifier(Modifier::BOLD),
+                    )));
+                }
+                if let Some(gear) = rules::gear(app.config.gear(), data) {
+                    weather_text.push(Line::from(vec![
+                        Span::styled("Take: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(gear, Style::default().fg(Color::Magenta)),
+                    ]));
+                }
+                weather_text.extend([
+                    Line::from(""),
+                    Line::from(vec![
+                        Span::styled("Location: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("{}{} ({})", 
+                                data.location.label(),
+                                region_str,
+                                data.location.country),
+                            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+                        ),
+                    ]),
+                    Line::from(""),
+                    Line::from(vec![
+                        Span::styled("Condition: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            weather_desc,
+                            Style::default().fg(Color::Yellow),
+                        ),
+                    ]),
+                    Line::from(""),
+                    Line::from(vec![
+                        Span::styled("Temperature: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("{:.1}{}", 
+                                data.units.temperature(data.weather.current.temperature_2m),
+                                data.units.temperature_unit()),
+                            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+                        ),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Feels like: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("{:.1}{}", 
+                                data.units.temperature(data.weather.current.apparent_temperature),
+                                data.units.temperature_unit()),
+                            Style::default().fg(Color::Green),
+                        ),
+                    ]),
+                    Line::from(""),
+                    Line::from(vec![
+                        Span::styled("Humidity: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("{}%", data.weather.current.relative_humidity_2m),
+                            Style::default().fg(Color::White),
+                        ),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Pressure: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("{:.1} {}", 
+                                data.weather.current.pressure_msl,
+                                data.weather.current_units.pressure_msl),
+                            Style::default().fg(Color::White),
+                        ),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Wind Speed: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("{:.1} {}", 
+                                data.units.wind_speed(data.weather.current.wind_speed_10m),
+                                data.units.wind_speed_unit()),
+                            Style::default().fg(Color::White),
+                        ),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Precipitation: ", Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("{:.1} mm", data.weather.current.precipitation),
+                            Style::default().fg(Color::White),
+                        ),
+                    ]),
+                ]);
+
+                if let Some(hourly) = &data.weather.hourly {
+                    weather_text.push(Line::from(""));
+                    weather_text.extend(next_hours(app, hourly, &data.weather.current.time, data.units));
+                }
+
+                if app.debug {
+                    let geocode = data.timings.geocode
+                        .map(|d| format!("{} ms", d.as_millis()))
+                        .unwrap_or_else(|| "cached".to_string());
+                    weather_text.push(Line::from(""));
+                    weather_text.push(Line::from(Span::styled(
+                        format!("Fetch latency: geocode {}, forecast {} ms",
+                            geocode,
+                            data.timings.forecast.as_millis()),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                    if let Some(reason) = &data.timings.json_fallback {
+                        weather_text.push(Line::from(Span::styled(
+                            format!("Forecast came as JSON: {}", reason),
+                            Style::default().fg(Color::DarkGray),
+                        )));
+                    }
+                }
+
+                let weather_display = Paragraph::new(weather_text)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(weather_title(app)),
+                    )
+                    .wrap(Wrap { trim: true });
+                if app.big_temperature {
+                    let banner_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Length(bigtext::HEIGHT as u16 + 2),
+                            Constraint::Min(5),
+                        ])
+                        .split(details_area);
+
+                    let temperature = format!("{:.1}{}",
+                        data.units.temperature(data.weather.current.temperature_2m),
+                        data.units.temperature_unit());
+                    let banner = Paragraph::new(
+                        bigtext::render(&temperature, '█')
+                            .into_iter()
+                            .map(Line::from)
+                            .collect::<Vec<_>>(),
+                    )
+                    .style(Style::default().fg(Color::Green))
+                    .alignment(Alignment::Center)
+                    .block(Block::default().borders(Borders::ALL));
+
+                    f.render_widget(banner, banner_chunks[0]);
+                    f.render_widget(weather_display, banner_chunks[1]);
+                } else {
+                    f.render_widget(weather_display, details_area);
+                }
+                weather_effects(f, app, data, details_area);
+            }
+        }
+        AppState::Trends => trends(f, app, main_chunks[0]),
+        AppState::Accuracy => accuracy(f, app, main_chunks[0]),
+        AppState::Cyclones => cyclones(f, app, main_chunks[0]),
+        AppState::Mountain => mountain(f, app, main_chunks[0]),
+        AppState::FreeFlight => free_flight(f, app, main_chunks[0]),
+        AppState::Marine => marine(f, app, main_chunks[0]),
+        AppState::Barograph => barograph(f, app, main_chunks[0]),
+        AppState::DewPoint => dew_point(f, app, main_chunks[0]),
+        AppState::Nearby => nearby(f, app, main_chunks[0]),
+        AppState::Brief => brief(f, app, main_chunks[0]),
+        AppState::Events => events(f, app, main_chunks[0]),
+        AppState::Error => {
+            let mut text = vec![Line::from(Span::styled(app.error_message.as_str(), Style::default().fg(Color::Red)))];
+            if !app.corrections.is_empty() {
+                text.push(Line::from(""));
+                text.push(Line::from("Did you mean"));
+                for (i, correction) in app.corrections.iter().enumerate() {
+                    let name = match &correction.location {
+                        Some(location) => {
+                            let region = location.admin1.as_ref().map(|r| format!(", {}", r)).unwrap_or_default();
+                            format!("{}{} ({})", location.name, region, location.country)
+                        }
+                        None => correction.query.clone(),
+                    };
+                    text.push(Line::from(vec![
+                        Span::styled(format!("  {} ", i + 1), Style::default().fg(Color::DarkGray)),
+                        Span::styled(name, Style::default().fg(Color::Yellow)),
+                    ]));
+                }
+            }
+            let again = keys(app, &[Action::NewSearch]);
+            let title = if app.corrections.is_empty() {
+                format!("Error (Press '{}' to try again)", again)
+            } else {
+                format!("Error (Press '{}' to try again, or a number to pick a suggestion)", again)
+            };
+            let error = Paragraph::new(text)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .wrap(Wrap { trim: true });
+            f.render_widget(error, main_chunks[0]);
+        }
+    }
+
+    // History panel
+    let history_border_style = if app.focused_pane == Pane::History {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+
+    let history_items: Vec<ListItem> = app
+        .search_history
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let style = if app.focused_pane == Pane::History && i == app.selected_history_index {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            // Once anything is marked, every row gets a column for it so the names stay lined up
+            let mut row = Vec::with_capacity(5);
+            if !app.marked_history.is_empty() {
+                let marked = app.marked_history.contains(&entry.query);
+                row.extend([Span::raw(if marked { icon(app, Icon::Marked) } else { " " }), Span::raw(" ")]);
+            }
+            if entry.favorite {
+                row.extend([Span::raw(icon(app, Icon::Favorite)), Span::raw(" ")]);
+            }
+            row.push(Span::raw(entry.query.as_str()));
+            ListItem::new(Line::from(row)).style(style)
+        })
+        .collect();
+
+    let history_title = match app.marked_history.len() {
+        0 => format!(
+            "History ({} to switch, {} to navigate, {} to load, {} to mark)",
+            keys_in(&AppState::Input, &Mode::Normal, &[Action::NextPane]),
+            keys_in(&AppState::Input, &Mode::Normal, &[Action::SelectNext, Action::SelectPrev]),
+            keys_in(&AppState::Input, &Mode::Normal, &[Action::Submit]),
+            keys_in(&AppState::Input, &Mode::Normal, &[Action::ToggleMark])
+        ),
+        marked => format!(
+            "History ({} marked, {} for what to do with them)",
+            marked,
+            keys_in(&AppState::Input, &Mode::Normal, &[Action::Submit])
+        ),
+    };
+    let history = List::new(history_items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(history_border_style)
+            .title(history_title),
+    );
+    let mut history_state = ListState::default().with_selected(
+        (app.focused_pane == Pane::History).then_some(app.selected_history_index),
+    );
+    f.render_stateful_widget(history, main_chunks[1], &mut history_state);
+
+    let footer_text = match app.mode {
+        Mode::Normal => hints(
+            app,
+            "NORMAL: ",
+            &[
+                (&[Action::EnterInsert], "insert"),
+                (&[Action::NextPane], "switch panes"),
+                (&[Action::SelectNext, Action::SelectPrev], "navigate history"),
+                (&[Action::Submit], "search/load"),
+                (&[Action::ShowHelp], "help"),
+                (&[Action::Quit], "quit"),
+            ],
+        ),
+        Mode::Insert => hints(
+            app,
+            "INSERT: Type to search | ",
+            &[
+                (&[Action::SelectPrev, Action::SelectNext], "select"),
+                (&[Action::Complete], "accept/switch"),
+                (&[Action::LeaveInsert], "normal mode"),
+            ],
+        ),
+    };
+    
+    let message = app.toast.as_ref().map(|t| t.message.as_str()).or(app.notice.as_deref());
+    let footer = match (&app.command_line, message) {
+        (Some(line), _) => Paragraph::new(format!(":{}█", line)).style(Style::default().fg(Color::Yellow)),
+        (None, Some(message)) => Paragraph::new(message).style(Style::default().fg(Color::Black).bg(Color::Cyan)),
+        (None, None) => Paragraph::new(footer_text.as_str()).style(Style::default().fg(Color::White)),
+    }
+    .block(Block::default().borders(Borders::ALL).title_bottom(attribution(&providers(app))));
+    f.render_widget(footer, chunks[2]);
+
+    if let Some(popup) = &app.popup {
+        popup.render(f);
+    }
+}
+
+// The sources behind whatever is on screen right now
+fn providers(app: &App) -> Vec<Provider> {
+    match app.state {
+        AppState::Display if app.earthquakes.is_some() => vec![Provider::OpenMeteo, Provider::Usgs],
+        AppState::Display
+        | AppState::Trends
+        | AppState::Accuracy
+        | AppState::Mountain
+        | AppState::FreeFlight
+        | AppState::Barograph
+        | AppState::DewPoint
+        | AppState::Nearby => vec![Provider::OpenMeteo],
+        AppState::Cyclones => vec![Provider::Nhc],
+        AppState::Marine => {
+            let tides = app.tides.iter().flatten().map(|t| t.provider);
+            tides.chain(app.sea_temperature.as_ref().and_then(|s| s.as_ref().ok()).map(|_| Provider::OpenMeteo)).collect()
+        }
+        AppState::Brief => vec![Provider::AviationWeather, Provider::OpenMeteo],
+        AppState::Input if app.gazetteer.is_some() => vec![Provider::GeoNames],
+        AppState::Input | AppState::Loading | AppState::Error | AppState::Events => Vec::new(),
+    }
+}
+
+fn attribution(providers: &[Provider]) -> Line<'static> {
+    let text: Vec<&str> = providers.iter().map(|p| p.attribution()).collect();
+    let text = if text.is_empty() { String::new() } else { format!(" {} ", text.join(" · ")) };
+    Line::from(Span::styled(text, Style::default().fg(Color::DarkGray))).right_aligned()
+}
+
+// Just the essentials in a small card in the middle, sized to fit a terminal popup
+fn compact(f: &mut Frame, app: &App, data: &WeatherData) {
+    let current = &data.weather.current;
+    let units = data.units;
+    // No hourly forecast means no idea, rather than dry
+    let rain = match (summary::rain_outlook(&data.weather), &data.weather.hourly) {
+        (Some(rain), _) => rain,
+        (None, Some(_)) => format!("dry for the next {} hours", summary::LOOKAHEAD_HOURS),
+        (None, None) => String::new(),
+    };
+    let mut rain_chars = rain.chars();
+    let rain: String = rain_chars.next().map(|c| c.to_uppercase().chain(rain_chars).collect()).unwrap_or_default();
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw(format!("{}  ", icon(app, Icon::sky(current.weather_code)))),
+            Span::styled(
+                format!("{:.0}{}", units.temperature(current.temperature_2m), units.temperature_unit()),
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("  {}", weather_code_to_description(current.weather_code)), Style::default().fg(Color::Yellow)),
+        ]),
+        Line::from(vec![
+            Span::styled("Feels like ", dim),
+            Span::raw(format!("{:.0}{}", units.temperature(current.apparent_temperature), units.temperature_unit())),
+            Span::styled("  Wind ", dim),
+            Span::raw(format!("{:.0} {}", units.wind_speed(current.wind_speed_10m), units.wind_speed_unit())),
+        ]),
+        Line::from(rain),
+    ];
+    // Toasts have no footer to go in here; they get two rows since most need to wrap
+    let mut rows = lines.len() as u16;
+    if let Some(message) = app.toast.as_ref().map(|t| t.message.as_str()).or(app.notice.as_deref()) {
+        lines.push(Line::from(Span::styled(message.to_string(), Style::default().fg(Color::Cyan))));
+        rows += 2;
+    }
+
+    let screen = f.area();
+    let width = 48.min(screen.width);
+    let height = (rows + 2).min(screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    let card = Paragraph::new(lines).alignment(Alignment::Center).wrap(Wrap { trim: true }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {}, {} ", data.location.label(), data.location.country))
+            .title_bottom(attribution(&[Provider::OpenMeteo])),
+    );
+    f.render_widget(card, area);
+}
+
+// Most important first, so a short terminal loses the least useful lines off the bottom. Nothing
+// flashes and everything is bold; the temperature gets the block digits
+fn large_print(f: &mut Frame, app: &App, data: &WeatherData, area: Rect) {
+    let current = &data.weather.current;
+    let units = data.units;
+    let bold = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    let label = |name: &str, value: String| {
+        Line::from(vec![Span::styled(format!("{} ", name), bold.fg(Color::Cyan)), Span::styled(value, bold)])
+    };
+
+    let mut lines = Vec::new();
+    if let Some(severe) = app.severe_weather() {
+        lines.push(Line::from(Span::styled(format!(" {} {} ", icon(app, Icon::Warning), severe.to_uppercase()), bold.fg(Color::Black).bg(Color::Yellow))));
+        lines.push(Line::from(""));
+    }
+    let temperature = format!("{:.0}{}", units.temperature(current.temperature_2m), units.temperature_unit());
+    lines.extend(bigtext::render(&temperature, '█').into_iter().map(|l| Line::from(Span::styled(l, bold.fg(Color::Green)))));
+    let mut items = vec![
+        Line::from(Span::styled(weather_code_to_description(current.weather_code), bold.fg(Color::Yellow))),
+        label("Feels", format!("{:.0}{}", units.temperature(current.apparent_temperature), units.temperature_unit())),
+        label("Wind", format!("{:.0} {}", units.wind_speed(current.wind_speed_10m), units.wind_speed_unit())),
+    ];
+    if let Some(rain) = summary::rain_outlook(&data.weather) {
+        items.push(label("Rain", rain));
+    }
+    items.push(label("Hum.", format!("{}%", current.relative_humidity_2m)));
+    for item in items {
+        lines.push(Line::from(""));
+        lines.push(item);
+    }
+
+    let title = Span::styled(format!(" {} ", data.location.label()), bold);
+    let page = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).border_style(bold).title(title));
+    f.render_widget(page, area);
+}
+
+fn kiosk(f: &mut Frame, app: &App) {
+    if app.kiosk_tiles.is_empty() {
+        let text = vec![
+            Line::from(""),
+            Line::from("No locations to show."),
+            Line::from("Add kiosk_locations = [\"Berlin\"] to config.toml, or search for something first."),
+            Line::from(""),
+            Line::from("Press q to quit."),
+        ];
+        let message = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("Kiosk"));
+        f.render_widget(message, f.area());
+        return;
+    }
+
+    // The last few events along the bottom, for whoever walks past in the morning
+    const EVENT_ROWS: usize = 3;
+    let mut area = f.area();
+    if !app.events.is_empty() {
+        let rows = app.events.len().min(EVENT_ROWS);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(10), Constraint::Length(rows as u16 + 2)])
+            .split(area);
+        let lines: Vec<Line> = app.events.iter().rev().take(rows).map(event_line).collect();
+        let log = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Recent events"));
+        f.render_widget(log, chunks[1]);
+        area = chunks[0];
+    }
+
+    let count = app.kiosk_tiles.len() as u32;
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(app.kiosk_tiles.iter().map(|_| Constraint::Ratio(1, count)))
+        .split(area);
+
+    for (i, (tile, area)) in app.kiosk_tiles.iter().zip(columns.iter()).enumerate() {
+        let (title, text) = kiosk_tile(app, tile);
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+        // Credit once, in the bottom-right corner of the dashboard
+        if i + 1 == app.kiosk_tiles.len() {
+            block = block.title_bottom(attribution(&[Provider::OpenMeteo]));
+        }
+        let paragraph = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false })
+            .block(block);
+        f.render_widget(paragraph, *area);
+        if let Some(data) = &tile.data {
+            weather_effects(f, app, data, *area);
+        }
+    }
+
+    if app.kiosk_tiles.iter().any(|t| t.pending) {
+        fetch_progress(f, app);
+    }
+}
+
+// Whatever's coming down at the place, inside the pane's border
+fn weather_effects(f: &mut Frame, app: &App, data: &WeatherData, area: Rect) {
+    if !app.effects {
+        return;
+    }
+    if let Some(kind) = Precipitation::from_code(data.weather.current.weather_code) {
+        f.render_widget(Falling { kind, tick: app.tick }, area.inner(Margin::new(1, 1)));
+    }
+}
+
+// Top-right overlay while a refresh is running, one row per location
+fn fetch_progress(f: &mut Frame, app: &App) {
+    let done = app.kiosk_tiles.iter().filter(|t| !t.pending).count();
+    let spinner = SPINNER_FRAMES[app.tick as usize % SPINNER_FRAMES.len()];
+    let lines: Vec<Line> = app
+        .kiosk_tiles
+        .iter()
+        .map(|tile| {
+            let (mark, status, color) = match (&tile.error, tile.pending) {
+                (_, true) => (spinner, "fetching".to_string(), Color::Yellow),
+                (Some(e), false) => (icon(app, Icon::Failed), e.clone(), Color::Red),
+                (None, false) => (icon(app, Icon::Done), "done".to_string(), Color::Green),
+            };
+            Line::from(vec![
+                Span::styled(format!("{} ", mark), Style::default().fg(color)),
+                Span::raw(format!("{:<16} ", tile.query)),
+                Span::styled(status, Style::default().fg(Color::DarkGray)),
+            ])
+        })
+        .collect();
+
+    let screen = f.area();
+    let width = 44.min(screen.width);
+    let height = (lines.len() as u16 + 2).min(screen.height);
+    let area = Rect::new(screen.x + screen.width - width, screen.y, width, height);
+    let title = format!(" Refreshing {}/{} ", done, app.kiosk_tiles.len());
+    let panel = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(panel, area);
+}
+
+// A kiosk tile's reading, formatted once when it arrives: a Pi driving a wall display redraws
+// every tick for the rain and snow, and the text only changes every few minutes
+pub struct TileText {
+    title: String,
+    lines: Vec<(String, Style)>,
+}
+
+fn kiosk_tile<'a>(app: &App, tile: &'a KioskTile) -> (Cow<'a, str>, Vec<Line<'a>>) {
+    let Some(data) = &tile.data else {
+        let line = match &tile.error {
+            Some(e) => Line::from(Span::styled(e.as_str(), Style::default().fg(Color::Red))),
+            None => {
+                let spinner = SPINNER_FRAMES[app.tick as usize % SPINNER_FRAMES.len()];
+                Line::from(format!("{} Loading...", spinner))
+            }
+        };
+        return (Cow::Owned(format!(" {} ", tile.query)), vec![Line::from(""), line]);
+    };
+
+    let text = tile.text.get_or_init(|| tile_text(app, data));
+    let mut lines: Vec<Line> = text.lines.iter().map(|(line, style)| Line::from(Span::styled(line.as_str(), *style))).collect();
+    if let Some(e) = &tile.error {
+        lines.push(Line::from(Span::styled(
+            format!("Refresh failed: {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    (Cow::Borrowed(text.title.as_str()), lines)
+}
+
+fn tile_text(app: &App, data: &WeatherData) -> TileText {
+    let current = &data.weather.current;
+    let units = data.units;
+    // Imported waypoints have no country
+    let title = match data.location.country.as_str() {
+        "" => format!(" {} ", data.location.name),
+        country => format!(" {}, {} ", data.location.name, country),
+    };
+    let temperature = format!("{:.0}{}", units.temperature(current.temperature_2m), units.temperature_unit());
+    let big = Style::default().add_modifier(Modifier::BOLD);
+    let blank = || (String::new(), Style::default());
+
+    let mut lines = vec![blank()];
+    lines.extend(bigtext::render(&temperature, '█').into_iter().map(|l| (l, Style::default().fg(Color::Green))));
+    lines.extend([
+        blank(),
+        (weather_code_to_description(current.weather_code).to_string(), big.fg(Color::Yellow)),
+        (
+            rules::gear(app.config.gear(), data).map(|g| format!("Take: {}", g)).unwrap_or_default(),
+            big.fg(Color::Magenta),
+        ),
+        blank(),
+        (format!("Feels like {:.0}{}", units.temperature(current.apparent_temperature), units.temperature_unit()), big),
+        (format!("Humidity {}%", current.relative_humidity_2m), big),
+        (format!("Wind {:.0} {}", units.wind_speed(current.wind_speed_10m), units.wind_speed_unit()), big),
+        blank(),
+    ]);
+
+    // The next 12 hours in 3-hour steps, small enough for a tile
+    if let Some(hourly) = &data.weather.hourly {
+        if let Some(now) = hourly.temperature.slot_of(&current.time) {
+            let outlook = hourly.temperature.slice(now + 1..now + 13).resample(3, series::mean);
+            let steps: Vec<String> = outlook
+                .iter()
+                .map(|(time, &t)| format!("{} {:.0}°", forecast::hour_of(time), units.temperature(t)))
+                .collect();
+            lines.push((steps.join("   "), Style::default()));
+            lines.push(blank());
+        }
+    }
+
+    let observed = current.time.split('T').nth(1).unwrap_or(&current.time);
+    lines.push((format!("Observed {}", observed), Style::default().fg(Color::DarkGray)));
+    TileText { title, lines }
+}
+
+fn trends(f: &mut Frame, app: &App, area: Rect) {
+    let Some(trends) = &app.trends else {
+        return;
+    };
+    let place = app
+        .weather_data
+        .as_ref()
+        .map(|data| format!("{}, {}", data.location.name, data.location.country))
+        .unwrap_or_default();
+    let window = match trends.offset {
+        0 => format!("last {}", span(trends.hours)),
+        offset => format!("{} up to {} ago", span(trends.hours), span(offset)),
+    };
+    let title = format!(
+        "Trends for {} - {} (1-4 or +/- to zoom, h/l to pan, Left/Right to read a point, T or ESC to go back)",
+        place, window
+    );
+
+    // x is hours relative to now, so with no panning the newest reading sits at the right edge
+    let recent = trends.recent();
+    if recent.len() < 2 {
+        let text = vec![
+            Line::from(""),
+            Line::from(format!("Only {} recorded observation(s) in the {}.", recent.len(), window)),
+            Line::from("Keep fetching this location (auto-refresh helps) to build up a trend."),
+        ];
+        let message = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(message, area);
+        return;
+    }
+
+    let hours_ago = |fetched_at: u64| -((trends.now.saturating_sub(fetched_at)) as f64 / 3600.0);
+    let units = units(app);
+    let temperature: Vec<(f64, f64)> = recent.iter().map(|p| (hours_ago(p.fetched_at), units.temperature(p.temperature))).collect();
+    let pressure: Vec<(f64, f64)> = recent.iter().map(|p| (hours_ago(p.fetched_at), p.pressure)).collect();
+    let precipitation: Vec<(f64, f64)> = recent.iter().map(|p| (hours_ago(p.fetched_at), p.precipitation)).collect();
+    let temperature_unit = units.temperature_unit();
+    let pressure_unit = app.weather_data.as_ref().map(|data| data.weather.current_units.pressure_msl.clone()).unwrap_or_default();
+
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    // Precipitation shares the temperature chart, so wet spells line up with the temperature drops
+    // that bring them; it gets its own axis on the right, which the pressure chart leaves blank to
+    // keep the two time axes lined up
+    let rain_top = precipitation.iter().map(|&(_, mm)| mm).fold(1.0, f64::max).ceil();
+    let rain_labels = [format!("{:.0}", rain_top), format!("{:.1}", rain_top / 2.0), "0".to_string()];
+    let axis_width = rain_labels.iter().map(|l| l.len()).max().unwrap_or(0).max(2) as u16 + 1;
+    let split = |row: Rect| {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(axis_width)])
+            .split(row);
+        (columns[0], columns[1])
+    };
+    let (temperature_area, rain_axis) = split(rows[0]);
+    let (pressure_area, _) = split(rows[1]);
+
+    let until = trends.now.saturating_sub(trends.offset * 3600);
+    let bounds = [hours_ago(until.saturating_sub(trends.hours * 3600)), hours_ago(until)];
+    // The mouse wins while it's over a chart, otherwise whatever Left/Right picked
+    let hovered = trends.pointer.and_then(|(column, row)| {
+        [(temperature_area, &temperature), (pressure_area, &pressure)]
+            .into_iter()
+            .find(|(area, _)| area.contains((column, row).into()))
+            .and_then(|(area, data)| point_under(area, data, bounds, column))
+    });
+    let selected = hovered.or(trends.cursor).and_then(|i| Some((i, *recent.get(i)?)));
+    // Lines straight down each chart: one at every local midnight, and one through the picked reading
+    let days: Vec<f64> = midnights(until.saturating_sub(trends.hours * 3600), until).into_iter().map(hours_ago).collect();
+    let guides = |data: &[(f64, f64)]| days.iter().map(|&x| (vertical(data, x), Color::DarkGray)).collect::<Vec<_>>();
+    let marker = |data: &[(f64, f64)]| selected.map(|(i, _)| vertical(data, data[i].0).to_vec()).unwrap_or_default();
+    let (mut temperature_lines, pressure_lines) = (guides(&temperature), guides(&pressure));
+    // Bars rise from the bottom of the chart, scaled to the right-hand axis
+    temperature_lines.extend(precipitation.iter().filter(|&&(_, mm)| mm > 0.0).map(|&(x, mm)| {
+        let [(_, low), (_, high)] = vertical(&temperature, x);
+        ([(x, low), (x, low + mm / rain_top * (high - low))], Color::Blue)
+    }));
+    let (temperature_marker, pressure_marker) = (marker(&temperature), marker(&pressure));
+    f.render_widget(
+        trend_chart(
+            format!("Temperature ({}) and precipitation (mm)", temperature_unit),
+            &temperature,
+            Color::Green,
+            bounds,
+            &temperature_lines,
+            &temperature_marker,
+        ),
+        temperature_area,
+    );
+    f.render_widget(
+        trend_chart(format!("Pressure ({})", pressure_unit), &pressure, Color::Cyan, bounds, &pressure_lines, &pressure_marker),
+        pressure_area,
+    );
+
+    // Where ratatui puts the left-hand labels: top and bottom of the plot, and halfway between
+    let height = rain_axis.height as usize;
+    if height >= 4 {
+        let (top, bottom) = (1, height - 3);
+        let mut text = vec![Line::from(""); height - 2];
+        text[0] = Line::from(Span::styled(" mm", Style::default().fg(Color::Blue)));
+        for (row, label) in [top, bottom - (bottom - top) / 2, bottom].into_iter().zip(rain_labels) {
+            text[row] = Line::from(Span::styled(format!(" {}", label), Style::default().fg(Color::DarkGray)));
+        }
+        f.render_widget(Paragraph::new(text), rain_axis);
+    }
+
+    if let Some((i, point)) = selected {
+        let when = chrono::DateTime::from_timestamp(point.fetched_at as i64, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%a %H:%M").to_string())
+            .unwrap_or_default();
+        let text = vec![
+            Line::from(Span::styled(when, Style::default().add_modifier(Modifier::BOLD))),
+            Line::from(Span::styled(format!("{:.1}{}", units.temperature(point.temperature), temperature_unit), Style::default().fg(Color::Green))),
+            Line::from(Span::styled(format!("{:.1} mm", point.precipitation), Style::default().fg(Color::Blue))),
+            Line::from(Span::styled(format!("{:.1} {}", point.pressure, pressure_unit), Style::default().fg(Color::Cyan))),
+            Line::from(format!("{} {}", icon(app, Icon::sky(point.weather_code)), weather_code_to_description(point.weather_code))),
+        ];
+        let width = text.iter().map(|line| line.width() as u16).max().unwrap_or(0) + 2;
+        let height = text.len() as u16 + 2;
+        // Beside the point, on whichever side has room, so it never covers what it's describing
+        let x = chart_column(temperature_area, &temperature, bounds, temperature[i].0);
+        let x = if x + 2 + width <= inner.right() { x + 2 } else { x.saturating_sub(width + 1).max(inner.x) };
+        let tooltip = Rect::new(x, temperature_area.y + 1, width, height).intersection(inner);
+        f.render_widget(Clear, tooltip);
+        f.render_widget(Paragraph::new(text).block(Block::default().borders(Borders::ALL)), tooltip);
+    }
+}
+
+// "24 h", or "7 d" once it's whole days
+fn span(hours: u64) -> String {
+    if hours >= 72 && hours.is_multiple_of(24) {
+        format!("{} d", hours / 24)
+    } else {
+        format!("{} h", hours)
+    }
+}
+
+// An x axis label for a trend chart: -18h, or -3.5d when the window is wider than two days
+fn ago(hours: f64, in_days: bool) -> String {
+    if hours.abs() < 0.01 {
+        "now".to_string()
+    } else if in_days {
+        format!("{:.1}", hours / 24.0).trim_end_matches(".0").to_string() + "d"
+    } else {
+        format!("{:.0}h", hours)
+    }
+}
+
+// Local midnights from `since` to `until`, both unix times
+fn midnights(since: u64, until: u64) -> Vec<u64> {
+    let Some(start) = chrono::DateTime::from_timestamp(since as i64, 0) else {
+        return Vec::new();
+    };
+    let mut day = start.with_timezone(&chrono::Local).date_naive();
+    let mut found = Vec::new();
+    while let Some(next) = day.succ_opt() {
+        day = next;
+        // A midnight skipped by a DST change has no line
+        let Some(midnight) = day.and_hms_opt(0, 0, 0).and_then(|t| t.and_local_timezone(chrono::Local).earliest()) else {
+            continue;
+        };
+        let midnight = midnight.timestamp() as u64;
+        if midnight > until {
+            break;
+        }
+        found.push(midnight);
+    }
+    found
+}
+
+// Top to bottom of a trend chart at `x`
+fn vertical(data: &[(f64, f64)], x: f64) -> [(f64, f64); 2] {
+    let (low, high) = trend_range(data);
+    [(x, low), (x, high)]
+}
+
+// Roughly where ratatui puts the plotting area of a trend_chart: past the y labels and the axis
+// line, which is all a pointer needs to land on the right reading
+fn plot_span(area: Rect, data: &[(f64, f64)], bounds: [f64; 2]) -> (u16, u16) {
+    let (low, high) = trend_range(data);
+    let labels = [low, (low + high) / 2.0, high].map(|y| format!("{:.1}", y).len());
+    let first = ago(bounds[0], bounds[1] - bounds[0] > 48.0).len();
+    let widest = labels.into_iter().max().unwrap_or(0).max(first) as u16;
+    let left = (area.x + widest + 1).min(area.right().saturating_sub(1));
+    (left, area.right().saturating_sub(1).max(left))
+}
+
+fn chart_column(area: Rect, data: &[(f64, f64)], bounds: [f64; 2], x: f64) -> u16 {
+    let (left, right) = plot_span(area, data, bounds);
+    left + ((x - bounds[0]) / (bounds[1] - bounds[0]) * (right - left) as f64).round() as u16
+}
+
+// The reading closest to a screen column
+fn point_under(area: Rect, data: &[(f64, f64)], bounds: [f64; 2], column: u16) -> Option<usize> {
+    let (left, right) = plot_span(area, data, bounds);
+    if column < left || right == left {
+        return None;
+    }
+    let x = bounds[0] + (column.min(right) - left) as f64 / (right - left) as f64 * (bounds[1] - bounds[0]);
+    (0..data.len()).min_by(|&a, &b| (data[a].0 - x).abs().total_cmp(&(data[b].0 - x).abs()))
+}
+
+fn accuracy(f: &mut Frame, app: &App, area: Rect) {
+    let place = app
+        .weather_data
+        .as_ref()
+        .map(|data| format!("{}, {}", data.location.name, data.location.country))
+        .unwrap_or_default();
+    let title = format!(
+        "24 h forecast vs reality for {} - past {} days (A or ESC to go back)",
+        place, ACCURACY_DAYS
+    );
+    let checks = &app.forecast_checks;
+
+    let text = if checks.is_empty() {
+        vec![
+            Line::from(""),
+            Line::from("Nothing to compare yet."),
+            Line::from("Forecasts are kept every time this location is fetched; check back once"),
+            Line::from("there are readings from a day after an earlier fetch."),
+        ]
+    } else {
+        let misses: Vec<f64> = checks.iter().map(|c| c.forecast - c.observed).collect();
+        let count = misses.len() as f64;
+        let mean_abs = misses.iter().map(|m| m.abs()).sum::<f64>() / count;
+        let bias = misses.iter().sum::<f64>() / count;
+        let worst = checks
+            .iter()
+            .max_by(|a, b| (a.forecast - a.observed).abs().total_cmp(&(b.forecast - b.observed).abs()))
+            .expect("checks is not empty");
+        let units = units(app);
+        let degrees = units.temperature_unit();
+        let label = Style::default().fg(Color::Cyan);
+        let tendency = if bias.abs() < 0.05 {
+            "no lean either way".to_string()
+        } else {
+            format!("runs {:.1}{} {}", units.temperature_difference(bias.abs()), degrees, if bias > 0.0 { "warm" } else { "cold" })
+        };
+
+        let mut text = vec![
+            Line::from(vec![Span::styled("Readings compared: ", label), Span::raw(checks.len().to_string())]),
+            Line::from(vec![Span::styled("Average miss: ", label), Span::raw(format!("{:.1}{}", units.temperature_difference(mean_abs), degrees))]),
+            Line::from(vec![Span::styled("Tendency: ", label), Span::raw(tendency)]),
+            Line::from(vec![
+                Span::styled("Worst miss: ", label),
+                Span::raw(format!(
+                    "{:+.1}{} at {}",
+                    units.temperature_difference(worst.forecast - worst.observed),
+                    degrees,
+                    worst.observed_at.replace('T', " ")
+                )),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("{:<18}{:>10}{:>10}{:>8}", "Observed", "Forecast", "Actual", "Miss"),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+        ];
+        text.extend(checks.iter().rev().map(|c| {
+            let miss = c.forecast - c.observed;
+            let color = match miss.abs() {
+                m if m < 1.0 => Color::Green,
+                m if m < 3.0 => Color::Yellow,
+                _ => Color::Red,
+            };
+            Line::from(vec![
+                Span::raw(format!(
+                    "{:<18}{:>8.1}{}{:>8.1}{}",
+                    c.observed_at.replace('T', " "),
+                    units.temperature(c.forecast),
+                    degrees,
+                    units.temperature(c.observed),
+                    degrees
+                )),
+                Span::styled(format!("{:>+8.1}", units.temperature_difference(miss)), Style::default().fg(color)),
+            ])
+        }));
+        text
+    };
+
+    let paragraph = Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
+
+fn hazards(f: &mut Frame, app: &App, data: &WeatherData, area: Rect) {
+    let radius = app.config.earthquake_radius_km.unwrap_or_default();
+    let title = format!("Earthquakes M2.5+ within {:.0} km, past week", radius);
+    let lines: Vec<Line> = match &app.earthquakes {
+        Some(Ok(quakes)) if quakes.is_empty() => vec![Line::from(Span::styled(
+            "None. Nice and quiet.",
+            Style::default().fg(Color::DarkGray),
+        ))],
+        Some(Ok(quakes)) => quakes
+            .iter()
+            .map(|quake| {
+                let color = match quake.magnitude {
+                    m if m >= 6.0 => Color::Red,
+                    m if m >= 4.5 => Color::Yellow,
+                    _ => Color::White,
+                };
+                let distance = geo::distance_km(
+                    data.location.latitude,
+                    data.location.longitude,
+                    quake.latitude,
+                    quake.longitude,
+                );
+                let hours = data.fetched_at.saturating_sub(quake.time) / 3600;
+                let ago = if hours < 48 { format!("{}h ago", hours) } else { format!("{}d ago", hours / 24) };
+                Line::from(vec![
+                    Span::styled(format!("M{:<4.1}", quake.magnitude), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                    Span::raw(format!(
+                        " {:>4.0} km deep  {:>7}  {:>5.0} km away  {}",
+                        quake.depth_km, ago, distance, quake.place
+                    )),
+                ])
+            })
+            .collect(),
+        Some(Err(e)) => vec![Line::from(Span::styled(e.clone(), Style::default().fg(Color::Red)))],
+        None => Vec::new(),
+    };
+    let pane = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(pane, area);
+}
+
+fn plugin_panels(f: &mut Frame, app: &App, area: Rect) {
+    let heights: Vec<Constraint> = app
+        .plugin_panels
+        .iter()
+        .map(|p| Constraint::Length(p.panel.as_ref().map_or(1, |panel| panel.rows.len().max(1)) as u16 + 2))
+        .chain([Constraint::Min(0)])
+        .collect();
+    let chunks = Layout::default().direction(Direction::Vertical).constraints(heights).split(area);
+
+    for (result, &chunk) in app.plugin_panels.iter().zip(chunks.iter()) {
+        let (title, lines) = match &result.panel {
+            Ok(panel) if panel.rows.is_empty() => (
+                panel.title.clone(),
+                vec![Line::from(Span::styled("Nothing to report", Style::default().fg(Color::DarkGray)))],
+            ),
+            Ok(panel) => {
+                let width = panel.rows.iter().map(|(k, _)| k.chars().count()).max().unwrap_or(0);
+                let lines = panel
+                    .rows
+                    .iter()
+                    .map(|(key, value)| {
+                        Line::from(vec![
+                            Span::styled(format!("{:<width$} ", key, width = width), Style::default().fg(Color::Cyan)),
+                            Span::raw(value.clone()),
+                        ])
+                    })
+                    .collect();
+                (panel.title.clone(), lines)
+            }
+            Err(e) => (
+                result.name.clone(),
+                vec![Line::from(Span::styled(e.clone(), Style::default().fg(Color::Red)))],
+            ),
+        };
+        let pane = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(pane, chunk);
+    }
+}
+
+fn events(f: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app.events.iter().rev().map(|e| ListItem::new(event_line(e))).collect();
+    let title = "Event log, newest first (j/k to scroll, L or ESC to go back)";
+    if items.is_empty() {
+        let message = Paragraph::new("Nothing has happened yet. Alerts, forecast changes and failed refreshes end up here.")
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(message, area);
+        return;
+    }
+    let list = List::new(items)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    let mut state = ListState::default().with_selected(Some(app.events_scroll));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn event_line(event: &Event) -> Line<'static> {
+    let (label, color) = match event.kind {
+        EventKind::Alert => ("ALERT  ", Color::Red),
+        EventKind::ForecastChange => ("UPDATE ", Color::Magenta),
+        EventKind::RefreshFailed => ("FAILED ", Color::Yellow),
+        EventKind::Threshold => ("RULE   ", Color::Red),
+        EventKind::DeliveryFailed => ("UNSENT ", Color::Yellow),
+        EventKind::UpdateAvailable => ("NEW    ", Color::Green),
+    };
+    let when = chrono::DateTime::from_timestamp(event.timestamp as i64, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%a %d %b %H:%M").to_string())
+        .unwrap_or_default();
+    Line::from(vec![
+        Span::styled(format!("{}  ", when), Style::default().fg(Color::DarkGray)),
+        Span::styled(label, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+        Span::raw(event.message.clone()),
+    ])
+}
+
+fn mountain(f: &mut Frame, app: &App, area: Rect) {
+    // Snow usually reaches about this far below the freezing level
+    const SNOWLINE_BELOW_FREEZING: f64 = 300.0;
+    let (Some(mountain), Some(data)) = (&app.mountain, &app.weather_data) else {
+        return;
+    };
+    let units = data.units;
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let now = mountain.freezing_level.slot_of(&data.weather.current.time).unwrap_or(0);
+    let day = mountain.freezing_level.slice(now..now + 24);
+
+    let mut text = vec![Line::from(Span::styled("Freezing level", heading))];
+    if let Some(&level) = day.values.first() {
+        let (low, high) = day.values.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        text.push(Line::from(format!(
+            "Now {:.0} m, snowline around {:.0} m",
+            level,
+            (level - SNOWLINE_BELOW_FREEZING).max(0.0)
+        )));
+        text.push(Line::from(format!("Next 24 h between {:.0} and {:.0} m", low, high)));
+    }
+
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled(
+        format!("{:>9}  {:>7}  {:>10}  {:>10}", "Elevation", "Now", "Snow 24 h", "Snow 72 h"),
+        heading,
+    )));
+    for height in &mountain.elevations {
+        let snow = |hours: usize| height.snowfall.slice(now..now + hours).values.iter().sum::<f64>();
+        let temperature = height.temperature.values.get(now).copied().unwrap_or(f64::NAN);
+        let style = if snow(24) >= 1.0 { Style::default().fg(Color::White).add_modifier(Modifier::BOLD) } else { Style::default() };
+        text.push(Line::from(Span::styled(
+            format!("{:>7.0} m  {:>6.1}°  {:>7.1} cm  {:>7.1} cm", height.elevation, units.temperature(temperature), snow(24), snow(72)),
+            style,
+        )));
+    }
+
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled("Wind at 850 hPa (about 1500 m)", heading)));
+    let winds = mountain.wind_850.slice(now..now + 24);
+    let direction = |time: &str| mountain.wind_850_direction.at(time).copied().unwrap_or(0.0);
+    if let Some((time, speed)) = winds.iter().next() {
+        text.push(Line::from(format!("Now {:.0} {} from the {}", units.wind_speed(*speed), units.wind_speed_unit(), geo::compass(direction(time)))));
+    }
+    if let Some((time, speed)) = winds.iter().max_by(|a, b| a.1.total_cmp(b.1)) {
+        text.push(Line::from(format!(
+            "Strongest in the next 24 h: {:.0} {} from the {} at {}",
+            units.wind_speed(*speed),
+            units.wind_speed_unit(),
+            geo::compass(direction(time)),
+            forecast::hour_of(time)
+        )));
+    }
+
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled("Next 24 hours", heading)));
+    for (time, level) in day.iter().step_by(3) {
+        let wind = mountain.wind_850.at(time).copied().unwrap_or(f64::NAN);
+        text.push(Line::from(format!(
+            "{:>5}  freezing level {:>5.0} m  wind {:>3.0} {} {}",
+            forecast::hour_of(time),
+            level,
+            units.wind_speed(wind),
+            units.wind_speed_unit(),
+            geo::compass(direction(time))
+        )));
+    }
+
+    let title = format!("Mountain forecast for {}, {} (M or ESC to go back)", data.location.name, data.location.country);
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
+
+// The wind at each pressure level hour by hour, colored for a paraglider, plus a thermals guess
+fn free_flight(f: &mut Frame, app: &App, area: Rect) {
+    const HOURS: usize = 12;
+    let (Some(flight), Some(data)) = (&app.free_flight, &app.weather_data) else {
+        return;
+    };
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let now = flight.boundary_layer.slot_of(&data.weather.current.time).unwrap_or(0);
+
+    let mut header = vec![Span::styled(format!("{:<7}", "Hour"), heading)];
+    for level in &flight.levels {
+        header.push(Span::styled(format!("{:>17}", format!("{} hPa ~{} m", level.hpa, level.altitude)), heading));
+    }
+    header.push(Span::styled(format!("  {:<10}{:>8}", "Thermals", "Top"), heading));
+    let mut text = vec![Line::from(header)];
+
+    for (time, &top) in flight.boundary_layer.slice(now..now + HOURS).iter() {
+        let mut row = vec![Span::raw(format!("{:<7}", forecast::hour_of(time)))];
+        for level in &flight.levels {
+            let speed = level.speed.at(time).copied().unwrap_or(f64::NAN);
+            let direction = level.direction.at(time).copied().unwrap_or(0.0);
+            let color = match speed {
+                s if s < 15.0 => Color::Green,
+                s if s < 25.0 => Color::Yellow,
+                _ => Color::Red,
+            };
+            row.push(Span::styled(
+                format!("{:>17}", format!("{} {:.0} {}", geo::compass(direction), data.units.wind_speed(speed), data.units.wind_speed_unit())),
+                Style::default().fg(color),
+            ));
+        }
+        // A shallow boundary layer means it's night or the air is capped, whatever the lapse rate says
+        let lapse = flight.lapse_rate.at(time).copied().unwrap_or(0.0);
+        let thermals = match lapse {
+            _ if top < 300.0 => "none",
+            l if l >= 7.5 => "strong",
+            l if l >= 6.0 => "moderate",
+            l if l >= 4.5 => "weak",
+            _ => "none",
+        };
+        row.push(Span::raw(format!("  {:<10}{:>6.0} m", thermals, top)));
+        text.push(Line::from(row));
+    }
+
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled(
+        "Green is comfortable for most paragliders, red is a day for the bar. Top is the boundary layer above ground.",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let title = format!("Free flight over {}, {} (F or ESC to go back)", data.location.name, data.location.country);
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
+
+// The forecast at the location next to the same forecast a few km each way; where they disagree,
+// the terrain or the coastline is doing something the model can only roughly place
+fn nearby(f: &mut Frame, app: &App, area: Rect) {
+    const HOURS: usize = 24;
+    let (Some(here), Some(data)) = (app.nearby.first(), &app.weather_data) else {
+        return;
+    };
+    let units = data.units;
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let offset = app.config.nearby_offset_km.unwrap_or(NEARBY_OFFSET_KM);
+    let now = here.temperature.slot_of(&data.weather.current.time).unwrap_or(0);
+    let day = |series: &series::TimeSeries<f64>| series.slice(now..now + HOURS).values;
+    let spread = |values: &mut dyn Iterator<Item = f64>| {
+        let (low, high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        (high - low).max(0.0)
+    };
+
+    let mut text = vec![Line::from(Span::styled(
+        format!("{:<6}{:>8}{:>8}{:>18}{:>12}{:>12}", "", "Height", "Now", "Next 24 h", "Rain", "Wind max"),
+        heading,
+    ))];
+    for point in &app.nearby {
+        let temperatures = day(&point.temperature);
+        let (low, high) = temperatures.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        text.push(Line::from(format!(
+            "{:<6}{:>6.0} m{:>7.1}°{:>18}{:>9.1} mm{:>7.0} {}",
+            point.label,
+            point.elevation,
+            units.temperature(temperatures.first().copied().unwrap_or(f64::NAN)),
+            format!("{:.1}° to {:.1}°", units.temperature(low), units.temperature(high)),
+            day(&point.precipitation).iter().sum::<f64>(),
+            units.wind_speed(day(&point.wind_speed).iter().copied().fold(0.0, f64::max)),
+            units.wind_speed_unit()
+        )));
+    }
+
+    // The widest disagreement in any one hour, rather than between daily highs that may come at different times
+    let temperature_spread = (now..now + HOURS)
+        .map(|i| spread(&mut app.nearby.iter().filter_map(|p| p.temperature.values.get(i).copied())))
+        .fold(0.0, f64::max);
+    let rain_spread = spread(&mut app.nearby.iter().map(|p| day(&p.precipitation).iter().sum::<f64>()));
+    let height_spread = spread(&mut app.nearby.iter().map(|p| p.elevation));
+    let (verdict, color) = if temperature_spread < 1.5 && rain_spread < 1.0 {
+        ("The points agree, so the forecast here is about as sharp as it gets.".to_string(), Color::Green)
+    } else {
+        (
+            format!(
+                "Up to {:.1}° and {:.1} mm apart within {:.0} km ({:.0} m of height between them): take the details with a pinch of salt.",
+                units.temperature_difference(temperature_spread), rain_spread, offset, height_spread
+            ),
+            if temperature_spread >= 4.0 || rain_spread >= 5.0 { Color::Red } else { Color::Yellow },
+        )
+    };
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled(verdict, Style::default().fg(color))));
+
+    text.push(Line::from(""));
+    let mut header = vec![Span::styled(format!("{:<7}", "Hour"), heading)];
+    header.extend(app.nearby.iter().map(|p| Span::styled(format!("{:>8}", p.label), heading)));
+    header.push(Span::styled(format!("{:>9}", "Spread"), heading));
+    text.push(Line::from(header));
+    for (i, (time, _)) in here.temperature.iter().enumerate().skip(now).take(HOURS).step_by(2) {
+        let temperatures: Vec<f64> = app.nearby.iter().filter_map(|p| p.temperature.values.get(i).copied()).collect();
+        let mut row = vec![Span::raw(format!("{:<7}", forecast::hour_of(time)))];
+        row.extend(temperatures.iter().map(|&t| Span::raw(format!("{:>7.1}°", units.temperature(t)))));
+        let gap = spread(&mut temperatures.iter().copied());
+        let style = if gap >= 2.0 { Style::default().fg(Color::Yellow) } else { Style::default().fg(Color::DarkGray) };
+        row.push(Span::styled(format!("{:>8.1}°", units.temperature_difference(gap)), style));
+        text.push(Line::from(row));
+    }
+
+    let title = format!(
+        "{}, {} and {:.0} km each way (N or ESC to go back)",
+        data.location.name, data.location.country, offset
+    );
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
+
+// Upcoming high and low tides counted down from the location's current time, over a chart of the sea temperature
+fn marine(f: &mut Frame, app: &App, area: Rect) {
+    const WEEK_HOURS: f64 = 7.0 * 24.0;
+    let Some(data) = &app.weather_data else {
+        return;
+    };
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(Color::DarkGray);
+    let now = data.weather.current.time.as_str();
+
+    let mut text = vec![Line::from(Span::styled("Tides", heading))];
+    match &app.tides {
+        Some(Ok(tides)) => {
+            let upcoming: Vec<_> = tides.extremes.iter().filter(|t| t.time.as_str() >= now).collect();
+            text.push(Line::from(Span::styled(
+                match &tides.station {
+                    Some(station) => format!("Predictions for {}", station),
+                    None => "Predictions from a global tide model".to_string(),
+                },
+                dim,
+            )));
+            if let Some(next) = upcoming.first() {
+                let (trend, kind) = if next.high { ("Rising", "high") } else { ("Falling", "low") };
+                text.push(Line::from(Span::styled(
+                    format!("{}, {} tide at {} ({})", trend, kind, forecast::hour_of(&next.time), until(now, &next.time)),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+            for tide in upcoming {
+                text.push(Line::from(format!(
+                    "{:<10}  {:<4}  {:>5.2} m  {}",
+                    day_and_hour(&tide.time),
+                    if tide.high { "High" } else { "Low" },
+                    tide.height,
+                    until(now, &tide.time)
+                )));
+            }
+        }
+        Some(Err(e)) => text.push(Line::from(Span::styled(e.clone(), dim))),
+        None => text.push(Line::from(Span::styled("Loading...", dim))),
+    }
+
+    let title = format!("Marine forecast for {}, {} (W or ESC to go back)", data.location.name, data.location.country);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(text.len() as u16 + 2), Constraint::Min(0)])
+        .split(area);
+    let block = Block::default().borders(Borders::ALL);
+    f.render_widget(Paragraph::new(text).block(block.clone().title(title)), rows[0]);
+
+    let sea = match &app.sea_temperature {
+        Some(Ok(sea)) => sea,
+        Some(Err(e)) => return f.render_widget(Paragraph::new(Span::styled(e.clone(), dim)).block(block), rows[1]),
+        None => return f.render_widget(Paragraph::new(Span::styled("Loading...", dim)).block(block), rows[1]),
+    };
+    let units = data.units;
+    let swim = app.config.swim_temperature.unwrap_or(SWIM_TEMPERATURE);
+    let title = sea_outlook(sea, now, swim, units);
+    // Plotted in the units on screen; swim_temperature, like every threshold, is in °C
+    let swim = units.temperature(swim);
+    let points: Vec<(f64, f64)> =
+        sea.iter().filter_map(|(time, &t)| Some((hours_between(now, time)?, units.temperature(t)))).collect();
+    let split = points.partition_point(|&(hours, _)| hours <= 0.0);
+    let swim_line = [(-WEEK_HOURS, swim), (WEEK_HOURS, swim)];
+    let (min, max) = points.iter().fold((swim, swim), |(lo, hi), &(_, t)| (lo.min(t), hi.max(t)));
+    let (low, high) = ((min - 1.0).floor(), (max + 1.0).ceil());
+
+    let line = |data, color| {
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(data)
+    };
+    let chart = Chart::new(vec![
+        line(&swim_line, Color::Green),
+        line(&points[..split], Color::DarkGray),
+        line(&points[split..], Color::Cyan),
+    ])
+    .block(block.title(Span::styled(title, heading)))
+    .x_axis(
+        Axis::default()
+            .bounds([-WEEK_HOURS, WEEK_HOURS])
+            .style(dim)
+            .labels(vec![Span::raw("a week ago"), Span::raw("now"), Span::raw("in a week")]),
+    )
+    .y_axis(
+        Axis::default()
+            .bounds([low, high])
+            .style(dim)
+            .labels(vec![
+                Span::raw(format!("{:.0}°", low)),
+                Span::raw(format!("{:.0}°", (low + high) / 2.0)),
+                Span::raw(format!("{:.0}°", high)),
+            ]),
+    );
+    f.render_widget(chart, rows[1]);
+}
+
+// What a swimmer wants from the chart in one line: how warm it is now and when it crosses `swim`
+fn sea_outlook(sea: &series::TimeSeries<f64>, now: &str, swim: f64, units: Units) -> String {
+    let Some(current) = sea.iter().take_while(|(time, _)| *time <= now).last().map(|(_, &t)| t) else {
+        return "Sea surface temperature".to_string();
+    };
+    let ahead: Vec<_> = sea.iter().skip_while(|(time, _)| *time <= now).collect();
+    let degrees = |t: f64| units.temperature(t);
+    let outlook = if current >= swim {
+        format!("above {:.0}°", degrees(swim))
+    } else if let Some((time, _)) = ahead.iter().find(|(_, &t)| t >= swim) {
+        format!("reaching {:.0}° on {}", degrees(swim), day_and_hour(time).split(' ').next().unwrap_or_default())
+    } else {
+        let warmest = ahead.iter().map(|(_, &t)| t).fold(current, f64::max);
+        format!("below {:.0}° all week, {:.1}° at best", degrees(swim), degrees(warmest))
+    };
+    format!("Sea {:.1}° now, {}", degrees(current), outlook)
+}
+
+fn barograph(f: &mut Frame, app: &App, area: Rect) {
+    const SPAN_HOURS: f64 = 48.0;
+    let (Some(pressure), Some(data)) = (&app.barograph, &app.weather_data) else {
+        return;
+    };
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(Color::DarkGray);
+    let storm = Style::default().fg(Color::Red);
+    let now = &data.weather.current.time;
+    let current = pressure.slot_of(now).unwrap_or(0);
+
+    let mut text = vec![Line::from(Span::styled("Now", heading))];
+    if let Some(&value) = pressure.values.get(current) {
+        let tendency = match barograph::fall(&pressure.values, current) {
+            Some(fall) if fall >= 0.5 => format!(", falling {:.1} in the last {} h", fall, DROP_HOURS),
+            Some(fall) if fall <= -0.5 => format!(", rising {:.1} in the last {} h", -fall, DROP_HOURS),
+            Some(_) => ", steady".to_string(),
+            None => String::new(),
+        };
+        text.push(Line::from(format!("{:.1} {}{}", value, pressure.unit, tendency)));
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled(
+        format!("Falling more than {:.0} {} in {} h", STORM_DROP_HPA, pressure.unit, DROP_HOURS),
+        heading,
+    )));
+    let storms: Vec<(f64, usize)> = barograph::storm_drops(&pressure.values)
+        .into_iter()
+        .filter_map(|i| Some((hours_between(now, &pressure.time[i])?, i)))
+        .filter(|(hours, _)| hours.abs() <= SPAN_HOURS)
+        .collect();
+    if storms.is_empty() {
+        text.push(Line::from(Span::styled("Not in the two days either side of now", dim)));
+    }
+    for &(hours, i) in &storms {
+        let when = if hours > 0.0 { until(now, &pressure.time[i]) } else { format!("{:.0} h ago", -hours) };
+        text.push(Line::from(Span::styled(
+            format!(
+                "{} {:<10}  -{:.1} {}  {}",
+                icon(app, Icon::Storm),
+                day_and_hour(&pressure.time[i]),
+                barograph::fall(&pressure.values, i).unwrap_or_default(),
+                pressure.unit,
+                when
+            ),
+            storm,
+        )));
+    }
+
+    let title = format!("Barograph for {}, {} (G or ESC to go back)", data.location.name, data.location.country);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(text.len() as u16 + 2), Constraint::Min(0)])
+        .split(area);
+    let block = Block::default().borders(Borders::ALL);
+    f.render_widget(Paragraph::new(text).block(block.clone().title(title)), rows[0]);
+
+    let points: Vec<(f64, f64)> = pressure
+        .iter()
+        .filter_map(|(time, &p)| Some((hours_between(now, time)?, p)))
+        .filter(|(hours, _)| hours.abs() <= SPAN_HOURS)
+        .collect();
+    if points.is_empty() {
+        return;
+    }
+    let split = points.partition_point(|&(hours, _)| hours <= 0.0);
+    let (min, max) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, p)| (lo.min(p), hi.max(p)));
+    let (low, high) = ((min - 1.0).floor(), (max + 1.0).ceil());
+    let markers: Vec<[(f64, f64); 2]> = storms.iter().map(|&(hours, _)| [(hours, low), (hours, high)]).collect();
+
+    let line = |data, color| {
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(data)
+    };
+    // The storm lines go in first so the pressure is drawn over them; only the first is named, for the legend
+    let legend = format!("> {:.0} {} in {} h", STORM_DROP_HPA, pressure.unit, DROP_HOURS);
+    let mut datasets: Vec<Dataset> = markers
+        .iter()
+        .enumerate()
+        .map(|(i, marker)| if i == 0 { line(marker, Color::Red).name(legend.clone()) } else { line(marker, Color::Red) })
+        .collect();
+    datasets.push(line(&points[..split], Color::DarkGray));
+    datasets.push(line(&points[split..], Color::Cyan));
+    let chart = Chart::new(datasets)
+        .block(block.title(Span::styled(format!("Sea-level pressure ({})", pressure.unit), heading)))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+        .x_axis(
+            Axis::default()
+                .bounds([-SPAN_HOURS, SPAN_HOURS])
+                .style(dim)
+                .labels(vec![Span::raw("2 days ago"), Span::raw("now"), Span::raw("in 2 days")]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([low, high])
+                .style(dim)
+                .labels(vec![
+                    Span::raw(format!("{:.0}", low)),
+                    Span::raw(format!("{:.0}", (low + high) / 2.0)),
+                    Span::raw(format!("{:.0}", high)),
+                ]),
+        );
+    f.render_widget(chart, rows[1]);
+}
+
+fn dew_point(f: &mut Frame, app: &App, area: Rect) {
+    const HOURS: usize = 48;
+    let Some(data) = &app.weather_data else {
+        return;
+    };
+    let Some(hourly) = &data.weather.hourly else {
+        return;
+    };
+    let now = hourly.dew_point.slot_of(&data.weather.current.time).unwrap_or(0);
+    let ahead = hourly.dew_point.slice(now..now + HOURS);
+    let points: Vec<(f64, f64)> = ahead.values.iter().enumerate().map(|(h, &dew)| (h as f64, dew)).collect();
+    let Some(&(_, current)) = points.first() else {
+        return;
+    };
+    let (worst, worst_at) = ahead.iter().fold((current, ahead.time[0].as_str()), |(most, at), (time, &dew)| {
+        if dew > most { (dew, time) } else { (most, at) }
+    });
+    let units = data.units;
+    let summary = format!(
+        "Now {:.1}{} ({}), at worst {:.1}{} ({}) on {}",
+        units.temperature(current),
+        units.temperature_unit(),
+        Comfort::from_dew_point(current).label(),
+        units.temperature(worst),
+        units.temperature_unit(),
+        Comfort::from_dew_point(worst).label(),
+        day_and_hour(worst_at)
+    );
+
+    // Wide enough that every band shows, so there's always something to compare against. The chart
+    // stays in °C, where the bands are; only its labels follow the units
+    let (min, max) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, d)| (lo.min(d), hi.max(d)));
+    let (low, high) = ((min - 2.0).min(4.0).floor(), (max + 2.0).max(25.0).ceil());
+    let dim = Style::default().fg(Color::DarkGray);
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Black))
+        .data(&points);
+    let title = format!("Dew point for {}, {} - next {} h (ESC to go back)", data.location.name, data.location.country, HOURS);
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    let chart = Chart::new(vec![dataset])
+        .block(block.title_bottom(Span::styled(summary, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))))
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, HOURS as f64])
+                .style(dim)
+                .labels(vec![Span::raw("now"), Span::raw("+24h"), Span::raw("+48h")]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([low, high])
+                .style(dim)
+                .labels(vec![
+                    Span::raw(format!("{:.0}°", units.temperature(low))),
+                    Span::raw(format!("{:.0}°", units.temperature((low + high) / 2.0))),
+                    Span::raw(format!("{:.0}°", units.temperature(high))),
+                ]),
+        );
+    f.render_widget(chart, area);
+    shade_comfort(f.buffer_mut(), inner, low, high, units);
+}
+
+fn comfort_color(comfort: Comfort) -> Color {
+    match comfort {
+        Comfort::Dry => Color::Blue,
+        Comfort::Comfortable => Color::Green,
+        Comfort::Muggy => Color::Yellow,
+        Comfort::Oppressive => Color::Red,
+    }
+}
+
+// Colours the plot behind a dew point chart by comfort band, and names each band at its right-hand
+// end. The plot is found from the corner where ratatui's axes meet
+fn shade_comfort(buf: &mut Buffer, area: Rect, low: f64, high: f64, units: Units) {
+    let corner = (area.top()..area.bottom())
+        .rev()
+        .flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)))
+        .find(|&position| buf[position].symbol() == "└");
+    let Some((left, bottom)) = corner else {
+        return;
+    };
+    let plot = Rect::new(left + 1, area.y, area.right().saturating_sub(left + 1), bottom - area.y);
+    if plot.is_empty() {
+        return;
+    }
+    // The value in the middle of each row
+    let value = |y: u16| high - ((y - plot.y) as f64 + 0.5) / plot.height as f64 * (high - low);
+    for y in plot.top()..plot.bottom() {
+        let color = comfort_color(Comfort::from_dew_point(value(y)));
+        for x in plot.left()..plot.right() {
+            buf[(x, y)].set_bg(color);
+        }
+    }
+    for (from, comfort) in BANDS {
+        let rows: Vec<u16> = (plot.top()..plot.bottom()).filter(|&y| Comfort::from_dew_point(value(y)) == comfort).collect();
+        let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+            continue;
+        };
+        let label = if from.is_finite() {
+            format!(" {} from {:.0}° ", comfort.label(), units.temperature(from))
+        } else {
+            format!(" {} ", comfort.label())
+        };
+        let x = plot.right().saturating_sub(label.len() as u16).max(plot.x);
+        buf.set_string(x, (first + last) / 2, label, Style::default().fg(Color::Black).bg(comfort_color(comfort)));
+    }
+}
+
+// "2024-05-01T14:36" -> "Wed 14:36"
+fn day_and_hour(time: &str) -> String {
+    chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M")
+        .map(|t| t.format("%a %H:%M").to_string())
+        .unwrap_or_else(|_| time.to_string())
+}
+
+// Hours from one local time like "2024-05-01T14:36" to another, negative when `time` is earlier
+fn hours_between(now: &str, time: &str) -> Option<f64> {
+    let parse = |t: &str| chrono::NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M").ok();
+    Some((parse(time)? - parse(now)?).num_minutes() as f64 / 60.0)
+}
+
+// "in 2 h 05 min" between two local times like "2024-05-01T14:36"
+fn until(now: &str, time: &str) -> String {
+    let Some(hours) = hours_between(now, time) else {
+        return String::new();
+    };
+    match (hours * 60.0).round().max(0.0) as u64 {
+        0 => "now".to_string(),
+        m if m < 60 => format!("in {} min", m),
+        m => format!("in {} h {:02} min", m / 60, m % 60),
+    }
+}
+
+fn brief(f: &mut Frame, app: &App, area: Rect) {
+    let Some(brief) = &app.brief else {
+        return;
+    };
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut text = vec![Line::from(Span::styled("METAR", heading)), Line::from(brief.metar.clone())];
+    if let Some((direction, speed, gusts)) = brief.wind {
+        let gusts = gusts.map(|g| format!(" gusting {:.0}", g)).unwrap_or_default();
+        let wind = match direction {
+            Some(d) => format!("Wind {:03.0}° {:.0} kt{}", d, speed, gusts),
+            None => format!("Wind variable {:.0} kt{}", speed, gusts),
+        };
+        let components = match (direction, app.brief_runway) {
+            (Some(d), Some(runway)) => format!(" - {}", runway_components(d, speed, runway)),
+            (None, Some(_)) => " - assume a direct crosswind".to_string(),
+            _ => String::new(),
+        };
+        text.push(Line::from(Span::styled(format!("{}{}", wind, components), Style::default().fg(Color::Yellow))));
+    }
+
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled("TAF", heading)));
+    text.push(match &brief.taf {
+        Some(taf) => Line::from(taf.clone()),
+        None => Line::from(Span::styled("No TAF issued for this airport", dim)),
+    });
+
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled("Surface wind forecast (Open-Meteo, 10 m)", heading)));
+    for hour in &brief.surface {
+        let mut line = format!(
+            "{:>5}  {:03.0}° {:>3.0} kt G{:<3.0}",
+            forecast::hour_of(&hour.time),
+            hour.direction,
+            hour.speed,
+            hour.gusts
+        );
+        if let Some(runway) = app.brief_runway {
+            line.push_str(&format!("  {}", runway_components(hour.direction, hour.speed, runway)));
+        }
+        text.push(Line::from(line));
+    }
+    if app.brief_runway.is_none() {
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            "Set runway_heading for this airport in config.toml to see crosswind components.",
+            dim,
+        )));
+    }
+
+    let title = format!("Pre-flight brief: {} {} (P or ESC to go back)", brief.icao, brief.name);
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
+
+fn runway_components(wind_dir: f64, speed: f64, runway: f64) -> String {
+    let (head, cross) = geo::wind_components(wind_dir, speed, runway);
+    let along = if head >= 0.0 { "headwind" } else { "tailwind" };
+    let side = if cross >= 0.0 { "right" } else { "left" };
+    format!(
+        "rwy {:02}: {:.0} kt {}, {:.0} kt crosswind from the {}",
+        match (runway / 10.0).round() as u32 % 36 {
+            0 => 36,
+            n => n,
+        },
+        head.abs(),
+        along,
+        cross.abs(),
+        side
+    )
+}
+
+fn cyclones(f: &mut Frame, app: &App, area: Rect) {
+    let Some(data) = &app.weather_data else {
+        return;
+    };
+    let (lat, lon) = (data.location.latitude, data.location.longitude);
+    let title = "Active tropical cyclones (j/k to select, C or ESC to go back)";
+
+    if app.cyclones.is_empty() {
+        let text = vec![
+            Line::from(""),
+            Line::from("No active storms in the Atlantic or eastern/central Pacific right now."),
+            Line::from(Span::styled("Source: National Hurricane Center", Style::default().fg(Color::DarkGray))),
+        ];
+        let message = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(message, area);
+        return;
+    }
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(app.cyclones.len().min(6) as u16 + 2),
+            Constraint::Min(8),
+        ])
+        .split(area);
+
+    let items: Vec<ListItem> = app
+        .cyclones
+        .iter()
+        .enumerate()
+        .map(|(i, storm)| {
+            let distance = geo::distance_km(lat, lon, storm.latitude_numeric, storm.longitude_numeric);
+            let style = if i == app.selected_cyclone {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            ListItem::new(format!(
+                "{:<12} {:<5} {:>4.0} kt {:>5.0} mb  moving {} at {:.0} mph  {:.0} km away",
+                storm.name,
+                storm.category(),
+                storm.intensity,
+                storm.pressure,
+                geo::compass(storm.movement_dir),
+                storm.movement_speed,
+                distance
+            ))
+            .style(style)
+        })
+        .collect();
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+    let mut state = ListState::default().with_selected(Some(app.selected_cyclone));
+    f.render_stateful_widget(list, rows[0], &mut state);
+
+    let selected = &app.cyclones[app.selected_cyclone.min(app.cyclones.len() - 1)];
+    let track = projected_track(selected);
+
+    // Frame the location, every storm and the selected track with some margin
+    let lats = app.cyclones.iter().map(|c| c.latitude_numeric).chain(track.iter().map(|p| p.0)).chain([lat]);
+    let lons = app.cyclones.iter().map(|c| c.longitude_numeric).chain(track.iter().map(|p| p.1)).chain([lon]);
+    let (lat_min, lat_max) = lats.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let (lon_min, lon_max) = lons.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let pad_lat = ((20.0 - (lat_max - lat_min)) / 2.0).max(5.0);
+    let pad_lon = ((40.0 - (lon_max - lon_min)) / 2.0).max(10.0);
+    let y_bounds = [(lat_min - pad_lat).max(-90.0), (lat_max + pad_lat).min(90.0)];
+    let x_bounds = [(lon_min - pad_lon).max(-180.0), (lon_max + pad_lon).min(180.0)];
+
+    let canvas = Canvas::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{}: 48 h projected from current motion, not the official forecast", selected.name)),
+        )
+        .marker(symbols::Marker::Braille)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            ctx.draw(&Map { color: Color::DarkGray, resolution: MapResolution::High });
+            ctx.layer();
+            for pair in track.windows(2) {
+                ctx.draw(&CanvasLine {
+                    x1: pair[0].1,
+                    y1: pair[0].0,
+                    x2: pair[1].1,
+                    y2: pair[1].0,
+                    color: Color::Red,
+                });
+            }
+            for storm in &app.cyclones {
+                let color = if std::ptr::eq(storm, selected) { Color::Red } else { Color::Yellow };
+                ctx.print(
+                    storm.longitude_numeric,
+                    storm.latitude_numeric,
+                    Span::styled("@", Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                );
+            }
+            ctx.print(lon, lat, Span::styled("X", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+        });
+    f.render_widget(canvas, rows[1]);
+}
+
+// Dead reckoning in 12-hour steps; the NHC's own track isn't in the JSON feed
+fn projected_track(storm: &Cyclone) -> Vec<(f64, f64)> {
+    let km_per_hour = storm.movement_speed * 1.609;
+    (0..=4)
+        .map(|step| {
+            geo::destination(
+                storm.latitude_numeric,
+                storm.longitude_numeric,
+                storm.movement_dir,
+                km_per_hour * 12.0 * step as f64,
+            )
+        })
+        .collect()
+}
+
+// Height, temperature and wind along a route on one canvas, each stretched to the full height on its
+// own scale, so a cold pass or a windy ridge lines up with the climb that causes it
+pub fn route_profile(points: &[ProfilePoint], units: Units) -> impl Widget + '_ {
+    type Reading = fn(&ProfilePoint) -> f64;
+    let series: [(&str, &str, Color, Reading); 3] = [
+        ("elevation", "m", Color::Gray, |p| p.elevation),
+        ("temperature", units.temperature_unit(), Color::Red, |p| p.temperature),
+        ("wind", units.wind_speed_unit(), Color::Cyan, |p| p.wind),
+    ];
+    // Each line is scaled to its own range, so only the legend needs converting
+    let shown = |name: &str, v: f64| match name {
+        "temperature" => units.temperature(v),
+        "wind" => units.wind_speed(v),
+        _ => v,
+    };
+    let range = |value: Reading| {
+        let values = points.iter().map(value).filter(|v| v.is_finite());
+        values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)))
+    };
+    let mut legend = Vec::new();
+    for &(name, unit, color, value) in &series {
+        let (lo, hi) = range(value);
+        if lo <= hi {
+            legend.push(Span::styled(
+                format!(" {} {:.0}–{:.0} {} ", name, shown(name, lo), shown(name, hi), unit),
+                Style::default().fg(color),
+            ));
+        }
+    }
+    let total = points.last().map_or(0.0, |p| p.km);
+
+    Canvas::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Line::from(legend))
+                .title_bottom(Line::from(format!(" {:.0} km ", total)).right_aligned()),
+        )
+        .marker(symbols::Marker::Braille)
+        .x_bounds([0.0, total.max(0.1)])
+        .y_bounds([0.0, 1.0])
+        .paint(move |ctx| {
+            for &(_, _, color, value) in &series {
+                let (lo, hi) = range(value);
+                let scale = |v: f64| if hi > lo { 0.05 + 0.9 * (v - lo) / (hi - lo) } else { 0.5 };
+                // A stop outside the forecast leaves a gap rather than a line to zero
+                for pair in points.windows(2) {
+                    let (a, b) = (value(&pair[0]), value(&pair[1]));
+                    if a.is_finite() && b.is_finite() {
+                        ctx.draw(&CanvasLine { x1: pair[0].km, y1: scale(a), x2: pair[1].km, y2: scale(b), color });
+                    }
+                }
+            }
+        })
+}
+
+fn trend_range(data: &[(f64, f64)]) -> (f64, f64) {
+    let (min, max) = data
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, y)| (lo.min(y), hi.max(y)));
+    // Pad flat lines so the chart still has some vertical range
+    let pad = ((max - min) * 0.1).max(0.5);
+    (min - pad, max + pad)
+}
+
+// `lines` (day breaks, precipitation) and `marker` are drawn first, so the readings themselves stay on top
+fn trend_chart<'a>(
+    name: String,
+    data: &'a [(f64, f64)],
+    color: Color,
+    bounds: [f64; 2],
+    lines: &'a [([(f64, f64); 2], Color)],
+    marker: &'a [(f64, f64)],
+) -> Chart<'a> {
+    let (low, high) = trend_range(data);
+    let in_days = bounds[1] - bounds[0] > 48.0;
+
+    let mut datasets = Vec::new();
+    for (line, line_color) in lines {
+        datasets.push(
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*line_color))
+                .data(line),
+        );
+    }
+    if !marker.is_empty() {
+        datasets.push(Dataset::default().graph_type(GraphType::Line).style(Style::default().fg(Color::DarkGray)).data(marker));
+    }
+    datasets.push(
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(data),
+    );
+
+    Chart::new(datasets)
+        .block(Block::default().title(Span::styled(name, Style::default().fg(color))))
+        .x_axis(
+            Axis::default()
+                .bounds(bounds)
+                .style(Style::default().fg(Color::DarkGray))
+                .labels(vec![
+                    Span::raw(ago(bounds[0], in_days)),
+                    Span::raw(ago((bounds[0] + bounds[1]) / 2.0, in_days)),
+                    Span::raw(ago(bounds[1], in_days)),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([low, high])
+                .style(Style::default().fg(Color::DarkGray))
+                .labels(vec![
+                    Span::raw(format!("{:.1}", low)),
+                    Span::raw(format!("{:.1}", (low + high) / 2.0)),
+                    Span::raw(format!("{:.1}", high)),
+                ]),
+        )
+}
+
+// Only bothers in winter, when nobody's surprised to see it
+fn road_ice(data: &WeatherData) -> Option<roads::IceWarning> {
+    let current = &data.weather.current;
+    let month = current.time.get(5..7)?.parse().ok()?;
+    if !roads::winter(data.location.latitude, month) {
+        return None;
+    }
+    roads::black_ice(data.weather.hourly.as_ref()?, &current.time)
+}
+
+// A small hour-by-hour table; values changed by the last refresh stand out
+// What the weather on screen is shown in, or the configured units before there is any
+fn units(app: &App) -> Units {
+    app.weather_data.as_ref().map_or(app.config.units, |data| data.units)
+}
+
+fn next_hours(app: &App, hourly: &HourlyForecast, current_time: &str, units: Units) -> Vec<Line<'static>> {
+    const HOURS: usize = 8;
+    let Some(start) = hourly.temperature.slot_of(current_time) else {
+        return Vec::new();
+    };
+    let label = |text: &str| Span::styled(format!("{:<8}", text), Style::default().fg(Color::Cyan));
+    let changed = Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD);
+
+    let mut times = vec![label("Hour")];
+    let mut temperatures = vec![label("Temp")];
+    let mut rain = vec![label("Rain")];
+    let window = hourly.temperature.slice(start..start + HOURS);
+    for (time, temperature) in window.iter() {
+        times.push(Span::raw(format!("{:>8}", forecast::hour_of(time))));
+
+        let style = if app.forecast_diff.temperature.contains(time) { changed } else { Style::default() };
+        temperatures.push(Span::styled(format!("{:>7.1}°", units.temperature(*temperature)), style));
+
+        let style = if app.forecast_diff.precipitation.contains(time) { changed } else { Style::default() };
+        let mm = hourly.precipitation.at(time).copied().unwrap_or(0.0);
+        rain.push(Span::styled(format!("{:>6.1}mm", mm), style));
+    }
+
+    let mut lines = vec![Line::from(times), Line::from(temperatures), Line::from(rain)];
+    // A colored strip per activity: green for go, red for don't bother
+    for activity in app.config.activities() {
+        let name: String = activity.name.chars().take(7).collect();
+        let mut strip = vec![label(&name)];
+        for (time, _) in window.iter() {
+            strip.push(match score::at(activity, hourly, time) {
+                Some(s) => Span::styled(format!("{:>7.0} ", s), Style::default().fg(Color::Black).bg(score_color(s))),
+                None => Span::raw(format!("{:>8}", "-")),
+            });
+        }
+        lines.push(Line::from(strip));
+    }
+    lines
+}
+
+fn score_color(score: f64) -> Color {
+    match score {
+        s if s >= 7.0 => Color::Green,
+        s if s >= 4.0 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+// " · 412 km NNE of home" while a place is on screen and a home is configured
+fn from_home(app: &App) -> String {
+    let (Some(home), Some(data), AppState::Display) = (&app.home, &app.weather_data, &app.state) else {
+        return String::new();
+    };
+    let (lat, lon) = (data.location.latitude, data.location.longitude);
+    let km = geo::distance_km(home.latitude, home.longitude, lat, lon);
+    if km < 1.0 {
+        return " · at home".to_string();
+    }
+    let bearing = geo::bearing(home.latitude, home.longitude, lat, lon);
+    format!(" · {:.0} km {} of home", km, geo::compass(bearing))
+}
+
+// The keys for each action, as they're bound right now, e.g. "j/k"
+fn keys_in(state: &AppState, mode: &Mode, actions: &[Action]) -> String {
+    actions.iter().filter_map(|&a| action::key_for(state, mode, a)).collect::<Vec<_>>().join("/")
+}
+
+fn keys(app: &App, actions: &[Action]) -> String {
+    keys_in(&app.state, &app.mode, actions)
+}
+
+// The search screen's footer: "key=what it does" for each action that has a key
+fn hints(app: &App, prefix: &str, hints: &[(&[Action], &str)]) -> String {
+    // Whatever's on screen, the footer is about the search screen
+    let hints: Vec<String> = hints
+        .iter()
+        .map(|&(actions, what)| (keys_in(&AppState::Input, &app.mode, actions), what))
+        .filter(|(keys, _)| !keys.is_empty())
+        .map(|(keys, what)| format!("{}={}", keys, what))
+        .collect();
+    format!("{}{}", prefix, hints.join(" | "))
+}
+
+fn weather_title(app: &App) -> String {
+    let mut title = format!(
+        "Weather Information (Press '{}' to search again, '{}' to quit)",
+        keys(app, &[Action::NewSearch]),
+        keys(app, &[Action::Quit])
+    );
+    if let Some(ticks) = app.refresh_countdown {
+        let secs = ticks / TICKS_PER_SECOND;
+        title.push_str(&format!(" - refresh in {}:{:02}", secs / 60, secs % 60));
+    }
+    title
+}
+
+pub fn stats_lines(stats: &history::Stats, now: u64) -> Vec<Line<'static>> {
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled("Most searched", heading))];
+    lines.extend(stats.top.iter().map(|(query, times)| {
+        Line::from(vec![Span::styled(format!("  {:>4}×  ", times), Style::default().fg(Color::Yellow)), Span::raw(query.clone())])
+    }));
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!("{} searches, about {:.1} a week", stats.total, stats.per_week)));
+    if let Some(first) = stats.first_used.and_then(|t| chrono::DateTime::from_timestamp(t as i64, 0)) {
+        let days = now.saturating_sub(first.timestamp().max(0) as u64) / (24 * 3600);
+        let date = first.with_timezone(&chrono::Local).format("%-d %B %Y");
+        lines.push(Line::from(format!("First used on {} ({} days ago)", date, days)));
+    }
+    lines
+}
+
+pub fn help_lines() -> Vec<Line<'static>> {
+    let section = |title: &'static str| {
+        Line::from(Span::styled(title, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
+    };
+    let key = |keys: &'static str, desc: &'static str| {
+        Line::from(vec![
+            Span::styled(format!("  {:<14}", keys), Style::default().fg(Color::Yellow)),
+            Span::raw(desc),
+        ])
+    };
+
+    vec![
+        section("Anywhere but the search box"),
+        key("H", "weather at home"),
+        key("Ctrl-p", "find a command or saved place (works in the search box too)"),
+        key(":home set", "make the place on screen home"),
+        key(":stats", "what you search for most, and how often"),
+        key(":units f mph", "show this place in °F and mph from now on (:units alone to undo)"),
+        key(":export FILE", "the weather and the week ahead as Markdown (.org for Org, .html for the screen)"),
+        key(":bugreport", "save a report to attach to an issue"),
+        Line::from(""),
+        section("Normal mode"),
+        key("i / a", "insert before / after cursor"),
+        key("I / A", "insert at start / end"),
+        key("h / l", "move cursor"),
+        key("w / b", "next / previous word"),
+        key("0 ^ / $", "start / end of line"),
+        key("x", "delete char (or history entry in history pane)"),
+        key("Ctrl-d", "clear the search box"),
+        key("j / k", "navigate history"),
+        key("Tab / S-Tab", "next / previous pane"),
+        key("Alt-1 / Alt-2", "jump to search / history"),
+        key("Enter", "search / load history entry"),
+        key("Space", "mark history entries, then Enter to delete, favorite or open them all"),
+        key("?", "this help"),
+        key("Ctrl-z", "suspend to shell (any mode)"),
+        key("ESC", "quit"),
+        Line::from(""),
+        section("Insert mode"),
+        key("Up / Down", "select suggestion"),
+        key("Tab", "accept suggestion / switch panes"),
+        key("Alt+1-9", "accept numbered suggestion"),
+        key("Right / End", "take the dimmed completion at the end of the line"),
+        key("Alt-s / Alt-h", "jump to search / history"),
+        key("Enter", "search"),
+        key("ESC", "back to normal mode"),
+        Line::from(""),
+        section("Weather view"),
+        key("i", "search again"),
+        key("r", "pick a recent location"),
+        key("B", "toggle the big temperature banner"),
+        key("E", "toggle falling rain and snow"),
+        key("T", "trends from recorded observations"),
+        key("A", "how accurate the 24 h forecast was"),
+        key("C", "active hurricanes and tropical storms"),
+        key("M", "mountain forecast: freezing level, snowfall by height, upper winds"),
+        key("F", "free-flight wind profile and thermals"),
+        key("W", "marine forecast: tides and sea temperature"),
+        key("G", "barograph: pressure two days back and ahead, with storm warnings"),
+        key("V", "pick a chart: trends, barograph, or dew point and how muggy it will feel"),
+        key("N", "the forecast a few km each way, and how far apart it is"),
+        key("P", "pre-flight brief for a configured airport"),
+        key("L", "event log: alerts, forecast changes, failed refreshes"),
+        key("o", "open the place on a map (or your map_command)"),
+        key("O", "open the provider's forecast page for more detail"),
+        key("q / ESC", "quit"),
+        Line::from(""),
+        section("Search errors"),
+        key("1-9", "search a \"did you mean\" suggestion instead"),
+        Line::from(""),
+        section("Trends view"),
+        key("1 / 2 / 3 / 4", "last 12 / 24 / 48 hours / 7 days"),
+        key("+ / -", "zoom in / out"),
+        key("h / l", "pan back / forward in time"),
+        key("Left / Right", "read off the previous / next point (or hover with the mouse)"),
+        key("T / q / ESC", "back to the weather view"),
+        Line::from(""),
+        section("Forecast accuracy"),
+        key("A / q / ESC", "back to the weather view"),
+        Line::from(""),
+        section("Mountain forecast"),
+        key("M / q / ESC", "back to the weather view"),
+        Line::from(""),
+        section("Free-flight wind profile"),
+        key("F / q / ESC", "back to the weather view"),
+        Line::from(""),
+        section("Marine forecast"),
+        key("W / q / ESC", "back to the weather view"),
+        Line::from(""),
+        section("Nearby forecasts"),
+        key("N / q / ESC", "back to the weather view"),
+        Line::from(""),
+        section("Pre-flight brief"),
+        key("P / q / ESC", "back to the weather view"),
+        Line::from(""),
+        section("Tropical cyclones"),
+        key("j / k", "select a storm"),
+        key("C / q / ESC", "back to the weather view"),
+    ]
+}
+
+fn highlight_matches<'a>(text: &'a str, query: &str, style: Style) -> Vec<Span<'a>> {
+    let matched = fuzzy::fuzzy_match(text, query)
+        .map(|(_, positions)| positions)
+        .unwrap_or_default();
+    let highlight = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut run_matched = false;
+    for (i, (at, _)) in text.char_indices().enumerate() {
+        let is_match = matched.contains(&i);
+        if is_match != run_matched && at > start {
+            let style = if run_matched { highlight } else { style };
+            spans.push(Span::styled(&text[start..at], style));
+            start = at;
+        }
+        run_matched = is_match;
+    }
+    if start < text.len() {
+        spans.push(Span::styled(&text[start..], if run_matched { highlight } else { style }));
+    }
+    spans
+}
@@ -0,0 +1,228 @@
+// Waypoints out of the GPX and KML files hiking apps export, for `locations import`. Only names
+// and coordinates are wanted, so a few string searches do instead of an XML parser
+use crate::api::GeoLocation;
+
+// GPX waypoints and named route points, or KML placemarks that are points; tracks and paths
+// are thousands of unnamed points and are skipped
+pub fn parse(text: &str) -> Result<Vec<GeoLocation>, String> {
+    let points = if text.contains("<kml") {
+        kml(text)
+    } else if text.contains("<gpx") {
+        gpx(text)
+    } else {
+        return Err("Not a GPX or KML file".to_string());
+    };
+    if points.is_empty() {
+        return Err("No waypoints in the file (tracks and paths aren't imported)".to_string());
+    }
+    Ok(points)
+}
+
+pub struct TrackPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    // Metres, when the GPS or the planner recorded it
+    pub elevation: Option<f64>,
+}
+
+// The points of a GPX track, for `route`; a route's points if there's no track
+pub fn track(text: &str) -> Result<Vec<TrackPoint>, String> {
+    if !text.contains("<gpx") {
+        return Err("Not a GPX file".to_string());
+    }
+    for tag in ["trkpt", "rtept"] {
+        let points: Vec<TrackPoint> = elements(text, tag)
+            .filter_map(|(attributes, body)| {
+                let coordinate = |key| attribute(attributes, key).and_then(|v| v.trim().parse().ok());
+                Some(TrackPoint {
+                    latitude: coordinate("lat")?,
+                    longitude: coordinate("lon")?,
+                    elevation: text_of(body, "ele").and_then(|e| e.parse().ok()),
+                })
+            })
+            .collect();
+        if points.len() >= 2 {
+            return Ok(points);
+        }
+    }
+    Err("No track in the file".to_string())
+}
+
+fn gpx(text: &str) -> Vec<GeoLocation> {
+    let mut points = Vec::new();
+    for tag in ["wpt", "rtept"] {
+        for (attributes, body) in elements(text, tag) {
+            let name = text_of(body, "name");
+            if tag == "rtept" && name.is_none() {
+                continue;
+            }
+            let coordinate = |key| attribute(attributes, key).and_then(|v| v.trim().parse().ok());
+            if let (Some(latitude), Some(longitude)) = (coordinate("lat"), coordinate("lon")) {
+                points.extend(waypoint(name, latitude, longitude));
+            }
+        }
+    }
+    points
+}
+
+fn kml(text: &str) -> Vec<GeoLocation> {
+    let mut points = Vec::new();
+    for (_, placemark) in elements(text, "Placemark") {
+        let Some((_, point)) = elements(placemark, "Point").next() else {
+            continue;
+        };
+        // "longitude,latitude[,altitude]", the other way round from everywhere else
+        let coordinates = text_of(point, "coordinates").unwrap_or_default();
+        let mut parts = coordinates.split(',').map(|v| v.trim().parse::<f64>());
+        if let (Some(Ok(longitude)), Some(Ok(latitude))) = (parts.next(), parts.next()) {
+            points.extend(waypoint(text_of(placemark, "name"), latitude, longitude));
+        }
+    }
+    points
+}
+
+fn waypoint(name: Option<String>, latitude: f64, longitude: f64) -> Option<GeoLocation> {
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+    Some(GeoLocation {
+        name: name.unwrap_or_else(|| format!("{:.4}, {:.4}", latitude, longitude)),
+        latitude,
+        longitude,
+        country: String::new(),
+        admin1: None,
+        admin2: None,
+        admin3: None,
+        near_km: None,
+    })
+}
+
+// (attributes, contents) of each <tag ...>...</tag> or <tag .../>, not looking inside for nesting
+fn elements<'a>(text: &'a str, tag: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut rest = text;
+    std::iter::from_fn(move || loop {
+        let start = rest.find(&open)? + open.len();
+        rest = &rest[start..];
+        // <name> is not <namespace>
+        if !rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            continue;
+        }
+        let end = rest.find('>')?;
+        let attributes = &rest[..end];
+        rest = &rest[end + 1..];
+        if let Some(attributes) = attributes.strip_suffix('/') {
+            return Some((attributes, ""));
+        }
+        let end = rest.find(&close).unwrap_or(rest.len());
+        let body = &rest[..end];
+        rest = &rest[end..];
+        return Some((attributes, body));
+    })
+}
+
+fn attribute<'a>(attributes: &'a str, key: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+    loop {
+        let at = rest.find(key)?;
+        let before = rest[..at].chars().next_back();
+        rest = &rest[at + key.len()..];
+        let value = rest.trim_start().strip_prefix('=').map(str::trim_start);
+        if let (Some(true), Some(value)) = (before.map(char::is_whitespace), value) {
+            let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+            let value = &value[1..];
+            return value.find(quote).map(|end| &value[..end]);
+        }
+    }
+}
+
+fn text_of(body: &str, tag: &str) -> Option<String> {
+    let (_, text) = elements(body, tag).next()?;
+    let text = text.trim();
+    let text = text.strip_prefix("<![CDATA[").and_then(|t| t.strip_suffix("]]>")).map_or_else(|| unescape(text), str::to_string);
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let decoded = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(points: &[GeoLocation]) -> Vec<(&str, f64, f64)> {
+        points.iter().map(|p| (p.name.as_str(), p.latitude, p.longitude)).collect()
+    }
+
+    #[test]
+    fn gpx_waypoints_and_named_route_points() {
+        let gpx = r#"<?xml version="1.0"?>
+<gpx version="1.1" creator="test">
+  <wpt lat="61.6364" lon="8.3124"><ele>2469</ele><name>Galdhøpiggen</name></wpt>
+  <wpt lon='8.5669' lat='61.4836'><name><![CDATA[Gjendebu & hut]]></name></wpt>
+  <wpt lat="61.5" lon="8.4"/>
+  <rte><rtept lat="61.49" lon="8.81"><name>Memurubu</name></rtept><rtept lat="61.48" lon="8.80"/></rte>
+  <trk><trkseg><trkpt lat="61.1" lon="8.1"><name>ignored</name></trkpt></trkseg></trk>
+</gpx>"#;
+        let points = parse(gpx).unwrap();
+        assert_eq!(
+            names(&points),
+            [
+                ("Galdhøpiggen", 61.6364, 8.3124),
+                ("Gjendebu & hut", 61.4836, 8.5669),
+                ("61.5000, 8.4000", 61.5, 8.4),
+                ("Memurubu", 61.49, 8.81),
+            ]
+        );
+    }
+
+    #[test]
+    fn kml_points_but_not_paths_and_gpx_tracks_on_request() {
+        let kml = r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document>
+  <Placemark><name>Refuge du Goûter &amp; co</name><Point><coordinates> 6.8449,45.8506,3835 </coordinates></Point></Placemark>
+  <Placemark><name>The walk</name><LineString><coordinates>6.8,45.8 6.9,45.9</coordinates></LineString></Placemark>
+</Document></kml>"#;
+        assert_eq!(names(&parse(kml).unwrap()), [("Refuge du Goûter & co", 45.8506, 6.8449)]);
+        assert!(parse("<gpx><trk/></gpx>").is_err());
+        let track = r#"<gpx><trk><trkseg><trkpt lat="45.0" lon="6.0"/><trkpt lat="45.1" lon="6.1"><ele>900</ele></trkpt></trkseg></trk></gpx>"#;
+        let track: Vec<_> = super::track(track).unwrap().iter().map(|p| (p.latitude, p.longitude, p.elevation)).collect();
+        assert_eq!(track, [(45.0, 6.0, None), (45.1, 6.1, Some(900.0))]);
+        assert!(parse("lat,lon\n1,2").is_err());
+    }
+}
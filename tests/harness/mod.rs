@@ -0,0 +1,230 @@
+// Runs the real event loop against a TestBackend, fed with scripted keys and canned API answers
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use ratatui::{backend::TestBackend, Terminal};
+use serde_json::json;
+use std::{io, sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+
+use weather_searcher::api::{
+    Cyclone, DailyForecast, Earthquake, FetchError, FetchTimings, FlightBrief, FreeFlightForecast, GeoLocation,
+    MountainForecast, NearbyPoint, Release, SearchQuery, Source, Tides, WeatherData,
+};
+use weather_searcher::app::App;
+use weather_searcher::config::{Config, NtfyConfig};
+use weather_searcher::runtime;
+use weather_searcher::series::TimeSeries;
+use weather_searcher::units::Units;
+
+pub const WIDTH: u16 = 100;
+pub const HEIGHT: u16 = 30;
+
+// Knows a fixed list of places, all of them mild and cloudy
+pub struct Canned {
+    pub places: Vec<GeoLocation>,
+}
+
+impl Canned {
+    pub fn new(places: &[(&str, &str, f64, f64)]) -> Canned {
+        let places = places
+            .iter()
+            .map(|&(name, country, latitude, longitude)| GeoLocation {
+                name: name.to_string(),
+                latitude,
+                longitude,
+                country: country.to_string(),
+                admin1: None,
+                admin2: None,
+                admin3: None,
+                near_km: None,
+            })
+            .collect();
+        Canned { places }
+    }
+
+    fn find(&self, query: &SearchQuery) -> Option<&GeoLocation> {
+        self.places.iter().find(|p| p.name.eq_ignore_ascii_case(&query.name))
+    }
+}
+
+fn weather_at(location: GeoLocation) -> WeatherData {
+    let weather = serde_json::from_value(json!({
+        "current": {
+            "time": "2024-05-01T12:00",
+            "temperature_2m": 17.5,
+            "relative_humidity_2m": 60,
+            "apparent_temperature": 16.8,
+            "precipitation": 0.0,
+            "weather_code": 3,
+            "wind_speed_10m": 12.0,
+            "pressure_msl": 1014.2,
+        },
+        "current_units": { "temperature_2m": "°C", "wind_speed_10m": "km/h", "pressure_msl": "hPa" },
+    }))
+    .expect("canned forecast should parse");
+    WeatherData {
+        location,
+        weather,
+        timings: FetchTimings { geocode: None, forecast: Duration::from_millis(5), json_fallback: None },
+        fetched_at: 1_714_564_800,
+        units: Units::default(),
+    }
+}
+
+impl Source for Canned {
+    async fn fetch_weather(&self, query: &SearchQuery, cached: Option<GeoLocation>) -> Result<WeatherData, FetchError> {
+        match cached.or_else(|| self.find(query).cloned()) {
+            Some(location) => Ok(weather_at(location)),
+            None => Err(FetchError::NotFound(format!("'{}' not found. Try a different city name.", query.name))),
+        }
+    }
+
+    async fn fetch_autocomplete(&self, query: &SearchQuery) -> Result<Vec<GeoLocation>, String> {
+        let prefix = query.name.to_lowercase();
+        Ok(self.places.iter().filter(|p| p.name.to_lowercase().starts_with(&prefix)).cloned().collect())
+    }
+
+    async fn geocode(&self, query: &SearchQuery) -> Result<GeoLocation, String> {
+        self.find(query).cloned().ok_or_else(|| format!("'{}' not found.", query.name))
+    }
+
+    async fn fetch_earthquakes(&self, _: f64, _: f64, _: f64) -> Result<Vec<Earthquake>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_brief(&self, icao: &str) -> Result<FlightBrief, String> {
+        Err(format!("No brief for {} in tests", icao))
+    }
+
+    async fn fetch_cyclones(&self) -> Result<Vec<Cyclone>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_mountain(&self, _: f64, _: f64, _: &[f64]) -> Result<MountainForecast, String> {
+        Err("No mountains in tests".to_string())
+    }
+
+    async fn fetch_free_flight(&self, _: f64, _: f64) -> Result<FreeFlightForecast, String> {
+        Err("No upper air in tests".to_string())
+    }
+
+    // Cloudy all week, with rain on the third day
+    async fn fetch_daily(&self, _: &GeoLocation, days: u8) -> Result<DailyForecast, String> {
+        let time: Vec<String> = (1..=days).map(|day| format!("2024-05-{:02}", day)).collect();
+        let series = |value: f64, unit: &str| TimeSeries::new(time.clone(), vec![value; time.len()], unit);
+        let rain = (1..=days).map(|day| if day == 3 { 6.5 } else { 0.0 }).collect();
+        Ok(DailyForecast {
+            weather_code: TimeSeries::new(time.clone(), vec![3; time.len()], ""),
+            temperature_max: series(19.0, "°C"),
+            temperature_min: series(9.0, "°C"),
+            precipitation: TimeSeries::new(time.clone(), rain, "mm"),
+            wind_speed_max: series(20.0, "km/h"),
+        })
+    }
+
+    async fn fetch_tides(&self, _: f64, _: f64, _: Option<&str>) -> Result<Tides, String> {
+        Err("No tide station in tests".to_string())
+    }
+
+    // Flat all round except for a hill to the west, 3° colder
+    async fn fetch_nearby(&self, _: f64, _: f64, _: f64) -> Result<Vec<NearbyPoint>, String> {
+        let time: Vec<String> = (0..48).map(|h| format!("2024-05-{:02}T{:02}:00", 1 + h / 24, h % 24)).collect();
+        let series = |value: f64, unit: &str| TimeSeries::new(time.clone(), vec![value; time.len()], unit);
+        Ok(["Here", "N", "E", "S", "W"]
+            .into_iter()
+            .map(|label| {
+                let hill = label == "W";
+                NearbyPoint {
+                    label,
+                    elevation: if hill { 450.0 } else { 40.0 },
+                    temperature: series(if hill { 14.5 } else { 17.5 }, "°C"),
+                    precipitation: series(0.0, "mm"),
+                    wind_speed: series(12.0, "km/h"),
+                }
+            })
+            .collect())
+    }
+
+    // Warming steadily from a week before the canned "now" to a week after
+    async fn fetch_sea_temperature(&self, _: f64, _: f64) -> Result<TimeSeries<f64>, String> {
+        let start = chrono::NaiveDateTime::parse_from_str("2024-04-24T12:00", "%Y-%m-%dT%H:%M").map_err(|e| e.to_string())?;
+        let time = (0..=336).map(|h| (start + chrono::Duration::hours(h)).format("%Y-%m-%dT%H:%M").to_string()).collect();
+        let values = (0..=336).map(|h| 12.0 + 0.02 * h as f64).collect();
+        Ok(TimeSeries::new(time, values, "°C"))
+    }
+
+    // Steady until the canned "now", then a low rolls in: 5 hPa down in the first three hours
+    async fn fetch_pressure(&self, _: f64, _: f64) -> Result<TimeSeries<f64>, String> {
+        let start = chrono::NaiveDateTime::parse_from_str("2024-04-29T00:00", "%Y-%m-%dT%H:%M").map_err(|e| e.to_string())?;
+        let time = (0..120).map(|h| (start + chrono::Duration::hours(h)).format("%Y-%m-%dT%H:%M").to_string()).collect();
+        let values = (0..120).map(|h| 1016.0 - (h as f64 - 60.0).clamp(0.0, 4.0) * 1.5).collect();
+        Ok(TimeSeries::new(time, values, "hPa"))
+    }
+
+    async fn fetch_latest_release(&self) -> Result<Release, String> {
+        Ok(Release { version: "9.9.9".to_string(), url: "https://example.com/releases/v9.9.9".to_string() })
+    }
+
+    async fn post_webhook(&self, _: &str, _: &serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn publish_ntfy(&self, _: &NtfyConfig, _: &str, _: &str, _: u8) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub struct Ui {
+    input: UnboundedSender<io::Result<Event>>,
+    task: JoinHandle<io::Result<(App, Terminal<TestBackend>)>>,
+}
+
+impl Ui {
+    // Read-only, so nothing a test does ends up in the real history or event log
+    pub fn start(source: Canned) -> Ui {
+        let mut app = App::new(Vec::new(), false, Config::default());
+        app.read_only = true;
+        Ui::start_with(app, source)
+    }
+
+    pub fn start_with(app: App, source: Canned) -> Ui {
+        let (input, events) = unbounded();
+        let task = tokio::spawn(async move {
+            let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT))?;
+            let app = runtime::run_app(&mut terminal, app, events, Arc::new(source), |_| Ok(())).await?;
+            Ok((app, terminal))
+        });
+        Ui { input, task }
+    }
+
+    pub fn press(&self, code: KeyCode) {
+        let _ = self.input.unbounded_send(Ok(Event::Key(KeyEvent::new(code, KeyModifiers::NONE))));
+    }
+
+    pub fn ctrl(&self, c: char) {
+        let _ = self.input.unbounded_send(Ok(Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))));
+    }
+
+    pub fn type_text(&self, text: &str) {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c));
+        }
+    }
+
+    // Lets ticks, debounces and canned fetches run their course; the clock is paused, so this is instant
+    pub async fn settle(&self) {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+
+    // Ends the input stream and returns the app as it was left, plus the last frame as text
+    pub async fn finish(self) -> (App, String) {
+        drop(self.input);
+        let (app, terminal) = self.task.await.expect("event loop panicked").expect("event loop failed");
+        let buffer = terminal.backend().buffer();
+        let screen = (0..HEIGHT)
+            .map(|y| (0..WIDTH).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (app, screen)
+    }
+}
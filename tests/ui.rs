@@ -0,0 +1,400 @@
+mod harness;
+
+use crossterm::event::KeyCode;
+use harness::{Canned, Ui};
+use weather_searcher::app::{App, AppState};
+use weather_searcher::config::{Config, ViewStyle};
+use weather_searcher::events::{Event, EventKind};
+use weather_searcher::units::Units;
+
+fn places() -> Canned {
+    Canned::new(&[("Berlin", "Germany", 52.52, 13.41), ("Bergen", "Norway", 60.39, 5.32)])
+}
+
+#[tokio::test(start_paused = true)]
+async fn type_city_accept_suggestion_and_see_weather() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Berl");
+    ui.settle().await;
+    // The first Enter takes the suggestion, the second searches for it
+    ui.press(KeyCode::Enter);
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert_eq!(app.state, AppState::Display);
+    assert_eq!(app.weather_data.as_ref().map(|d| d.location.name.as_str()), Some("Berlin"));
+    assert!(screen.contains("Berlin"), "{}", screen);
+    assert!(screen.contains("17.5"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unknown_city_shows_the_error() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Atlantis");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert_eq!(app.state, AppState::Error);
+    assert!(screen.contains("'Atlantis' not found"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn escape_from_the_search_box_quits() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Ber");
+    ui.press(KeyCode::Esc);
+    ui.press(KeyCode::Esc);
+    ui.settle().await;
+
+    let (app, _) = ui.finish().await;
+    assert_eq!(app.state, AppState::Input);
+    assert_eq!(app.input, "Ber");
+}
+
+#[tokio::test(start_paused = true)]
+async fn end_takes_the_ghost_completion() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Berg");
+    ui.settle().await;
+    ui.press(KeyCode::End);
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, _) = ui.finish().await;
+    assert_eq!(app.state, AppState::Display);
+    assert_eq!(app.weather_data.as_ref().map(|d| d.location.name.as_str()), Some("Bergen"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn h_comes_back_home_from_elsewhere() {
+    let config = Config { home: Some("Berlin".to_string()), ..Config::default() };
+    let mut app = App::new(Vec::new(), false, config);
+    app.read_only = true;
+    let ui = Ui::start_with(app, places());
+    ui.settle().await;
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.press(KeyCode::Char('H'));
+    ui.settle().await;
+
+    let (app, _) = ui.finish().await;
+    assert_eq!(app.state, AppState::Display);
+    assert_eq!(app.weather_data.as_ref().map(|d| d.location.name.as_str()), Some("Berlin"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn palette_reopens_a_recent_search() {
+    let ui = Ui::start(places());
+    for city in ["Berlin", "Bergen"] {
+        ui.press(KeyCode::Char('i'));
+        ui.type_text(city);
+        ui.press(KeyCode::Enter);
+        ui.settle().await;
+    }
+    ui.ctrl('p');
+    ui.type_text("berli");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, _) = ui.finish().await;
+    assert_eq!(app.weather_data.as_ref().map(|d| d.location.name.as_str()), Some("Berlin"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn marine_view_opens_without_a_tide_station() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.press(KeyCode::Char('W'));
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert_eq!(app.state, AppState::Marine);
+    assert!(screen.contains("No tide station in tests"), "{}", screen);
+    assert!(screen.contains("Sea 15.4° now, reaching 16° on Thu"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn barograph_flags_the_low_coming_in() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.press(KeyCode::Char('G'));
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert_eq!(app.state, AppState::Barograph);
+    assert!(screen.contains("1016.0 hPa, steady"), "{}", screen);
+    assert!(screen.contains("⚑ Wed 15:00   -4.5 hPa  in 3 h 00 min"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn chart_picker_opens_the_barograph() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.press(KeyCode::Char('V'));
+    ui.press(KeyCode::Char('j'));
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, _) = ui.finish().await;
+    assert_eq!(app.state, AppState::Barograph);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_newer_release_outlasts_an_ordinary_toast() {
+    let config = Config { check_for_updates: true, ..Config::default() };
+    let mut app = App::new(Vec::new(), false, config);
+    app.read_only = true;
+    let ui = Ui::start_with(app, places());
+    ui.settle().await;
+    ui.settle().await;
+    // A toast goes over it and then away again, and Esc is the command line's before it's the notice's
+    ui.type_text(":nonsense");
+    ui.press(KeyCode::Enter);
+    ui.type_text(":");
+    ui.press(KeyCode::Esc);
+    ui.settle().await;
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert!(screen.contains("weather-tui 9.9.9 is out"), "{}", screen);
+    assert!(app.toast.is_none() && app.notice.is_some() && app.command_line.is_none());
+    assert_eq!(app.events.len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn compact_view_is_just_the_card() {
+    let config = Config { view: ViewStyle::Compact, ..Config::default() };
+    let mut app = App::new(Vec::new(), false, config);
+    app.read_only = true;
+    let ui = Ui::start_with(app, places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert_eq!(app.state, AppState::Display);
+    assert!(screen.contains("Bergen, Norway") && screen.contains("Feels like 17°C  Wind 12 km/h"), "{}", screen);
+    assert!(!screen.contains("History"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn large_print_drops_the_history_and_the_tables() {
+    let config = Config { view: ViewStyle::Large, ..Config::default() };
+    let mut app = App::new(Vec::new(), false, config);
+    app.read_only = true;
+    let ui = Ui::start_with(app, places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (_, screen) = ui.finish().await;
+    assert!(screen.contains("Feels 17°C") && screen.contains("Wind 12 km/h"), "{}", screen);
+    assert!(!screen.contains("History") && !screen.contains("Humidity"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn nearby_view_shows_how_far_apart_the_points_are() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.press(KeyCode::Char('N'));
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert_eq!(app.state, AppState::Nearby);
+    assert!(screen.contains("Up to 3.0° and 0.0 mm apart within 5 km"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_release_is_logged_once_not_every_start() {
+    let config = Config { check_for_updates: true, ..Config::default() };
+    let mut app = App::new(Vec::new(), false, config);
+    app.read_only = true;
+    let message = format!("weather-tui 9.9.9 is out (this is {}): https://example.com/releases/v9.9.9", env!("CARGO_PKG_VERSION"));
+    app.events.push(Event { timestamp: 0, kind: EventKind::UpdateAvailable, message });
+    let ui = Ui::start_with(app, places());
+    ui.settle().await;
+
+    let (app, _) = ui.finish().await;
+    assert!(app.notice.is_some());
+    assert_eq!(app.events.len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn nearby_view_follows_the_units() {
+    let mut app = App::new(Vec::new(), false, Config { units: Units::IMPERIAL, ..Config::default() });
+    app.read_only = true;
+    let ui = Ui::start_with(app, places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Bergen");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.press(KeyCode::Char('N'));
+    ui.settle().await;
+
+    let (_, screen) = ui.finish().await;
+    assert!(screen.contains("58.1° to 58.1°") && screen.contains("7 mph"), "{}", screen);
+    assert!(screen.contains("Up to 5.4° and 0.0 mm apart"), "{}", screen);
+    assert!(!screen.contains("km/h"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_long_address_scrolls_to_keep_the_cursor_in_view() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    let address = "Platz der Republik 1, Tiergarten, Mitte, 11011 Berlin, Brandenburg, Germany";
+    ui.type_text(address);
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert_eq!(app.input, address);
+    assert!(screen.contains("Brandenburg, Germany█"), "{}", screen);
+    assert!(!screen.contains("Platz der"), "{}", screen);
+}
+
+// Searches Berlin then Bergen and marks both in the history pane
+async fn mark_both_in_history() -> Ui {
+    let ui = Ui::start(places());
+    for city in ["Berlin", "Bergen"] {
+        ui.press(KeyCode::Char('i'));
+        ui.type_text(city);
+        ui.press(KeyCode::Enter);
+        ui.settle().await;
+    }
+    ui.press(KeyCode::Char('i'));
+    ui.press(KeyCode::Esc);
+    ui.press(KeyCode::Tab);
+    ui.press(KeyCode::Char(' '));
+    ui.press(KeyCode::Char(' '));
+    ui.press(KeyCode::Enter);
+    ui
+}
+
+#[tokio::test(start_paused = true)]
+async fn marked_history_opens_together_on_a_dashboard() {
+    let ui = mark_both_in_history().await;
+    ui.press(KeyCode::Down);
+    ui.press(KeyCode::Down);
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert!(app.kiosk);
+    let tiles: Vec<&str> = app.kiosk_tiles.iter().map(|t| t.query.as_str()).collect();
+    assert_eq!(tiles, ["Bergen", "Berlin"]);
+    assert!(screen.contains("Bergen, Norway") && screen.contains("Berlin, Germany"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn marked_history_stays_as_it_was_in_read_only() {
+    let ui = mark_both_in_history().await;
+    ui.press(KeyCode::Down);
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert!(app.search_history.iter().all(|e| !e.favorite));
+    assert!(app.marked_history.is_empty());
+    assert!(screen.contains("Can't change favorites in read-only mode"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn export_writes_the_week_as_markdown() {
+    let path = std::env::temp_dir().join(format!("weather-report-{}.md", std::process::id()));
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Berlin");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.type_text(&format!(":export {}", path.display()));
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (_, screen) = ui.finish().await;
+    let report = std::fs::read_to_string(&path).expect("report should be written");
+    let _ = std::fs::remove_file(&path);
+    assert!(screen.contains("Report saved to"), "{}", screen);
+    assert!(report.starts_with("# Weather in Berlin, Germany\n"), "{}", report);
+    assert!(report.contains("| Fri 3 May | Overcast | 9°C | 19°C | 6.5 mm | 20 km/h |"), "{}", report);
+    assert!(report.contains("Rain          █      0–6 mm"), "{}", report);
+}
+
+#[tokio::test(start_paused = true)]
+async fn export_to_html_saves_the_screen() {
+    let path = std::env::temp_dir().join(format!("weather-snapshot-{}.html", std::process::id()));
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Berlin");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.type_text(&format!(":export {}", path.display()));
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (_, screen) = ui.finish().await;
+    let html = std::fs::read_to_string(&path).expect("snapshot should be written");
+    let _ = std::fs::remove_file(&path);
+    assert!(screen.contains("Report saved to"), "{}", screen);
+    assert!(html.contains("<title>Weather in Berlin</title>"), "{}", html);
+    assert!(html.contains("17.5"), "{}", html);
+    // The page is the weather, not the command line that asked for it
+    assert!(!html.contains(":export"), "{}", html);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_favorite_keeps_its_own_units() {
+    let ui = Ui::start(places());
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Berlin");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    ui.type_text(":units fahrenheit mph");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+    // Looked up again, it comes back in °F
+    ui.press(KeyCode::Char('i'));
+    ui.type_text("Berlin");
+    ui.press(KeyCode::Enter);
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert!(screen.contains("63.5°F"), "{}", screen);
+    assert!(screen.contains("7.5 mph"), "{}", screen);
+    let berlin = app.search_history.iter().find(|e| e.query == "Berlin").unwrap();
+    assert!(berlin.favorite && berlin.units.is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_city_on_the_command_line_opens_straight_to_it() {
+    let config = Config { units: Units::IMPERIAL, ..Config::default() };
+    let mut app = App::new(Vec::new(), false, config);
+    app.read_only = true;
+    app.open_with = Some("Berlin".to_string());
+    let ui = Ui::start_with(app, places());
+    ui.settle().await;
+
+    let (app, screen) = ui.finish().await;
+    assert!(app.weather_data.as_ref().is_some_and(|d| d.location.name == "Berlin"));
+    assert!(screen.contains("63.5°F"), "{}", screen);
+}